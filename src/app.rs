@@ -24,6 +24,11 @@ pub fn create_app(
         .wrap(Logger::default())
         .app_data(Data::from(reasoning_service))
         .app_data(Data::from(config))
+        .route("/health", web::get().to(handlers::health))
+        .route("/ready", web::get().to(handlers::ready))
+        .route("/metrics", web::get().to(handlers::metrics))
+        .route("/openapi.json", web::get().to(handlers::openapi))
+        .route("/admin/usage", web::get().to(handlers::usage))
         .service(
             web::scope("/v1")
                 .route("/models", web::get().to(handlers::models))
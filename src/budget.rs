@@ -0,0 +1,116 @@
+use crate::config::ModelConfig;
+use crate::consts;
+use crate::models::request::{Message, MessageContent, MessageContentPart};
+
+/// Reasoning budget scaled to the size of the conversation when the model sets
+/// both `reasoning_budget_min` and `reasoning_budget_max`, the fixed budget otherwise.
+/// A budget from the reasoning budget header takes precedence over both.
+pub fn estimate_reasoning_budget(messages: &[Message], model_config: &ModelConfig) -> i32 {
+    if let Some(budget) = model_config.header_reasoning_budget {
+        return budget;
+    }
+    let (Some(min), Some(max)) = (
+        model_config.reasoning_budget_min,
+        model_config.reasoning_budget_max,
+    ) else {
+        return model_config.reasoning_budget;
+    };
+
+    let input_chars: usize = messages.iter().map(message_chars).sum();
+    let input_tokens = (input_chars / consts::CHARS_PER_TOKEN_ESTIMATE) as i64;
+    let scaled = min as i64 + input_tokens * consts::REASONING_TOKENS_PER_INPUT_TOKEN;
+
+    scaled.clamp(min as i64, max.max(min) as i64) as i32
+}
+
+fn message_chars(message: &Message) -> usize {
+    match message {
+        Message::User(message) | Message::System(message) => content_chars(&message.content),
+        Message::Assistant(message) => message.content.as_deref().map_or(0, str::len),
+        Message::Tool(message) => content_chars(&message.content),
+    }
+}
+
+fn content_chars(content: &MessageContent) -> usize {
+    match content {
+        MessageContent::String(text) => text.len(),
+        MessageContent::Array(parts) => parts
+            .iter()
+            .map(|part| match part {
+                MessageContentPart::Text { text } => text.len(),
+                _ => 0,
+            })
+            .sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::request::MessageSystemUser;
+
+    fn user(text: &str) -> Message {
+        Message::User(MessageSystemUser {
+            content: MessageContent::String(text.to_string()),
+        })
+    }
+
+    fn adaptive_config() -> ModelConfig {
+        ModelConfig {
+            reasoning_budget: 1000,
+            reasoning_budget_min: Some(200),
+            reasoning_budget_max: Some(4000),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_estimate_reasoning_budget_grows_with_input() {
+        let model_config = adaptive_config();
+
+        let short = estimate_reasoning_budget(&[user("What is 2+2?")], &model_config);
+        let long =
+            estimate_reasoning_budget(&[user(&"Prove this lemma. ".repeat(40))], &model_config);
+
+        assert!(short >= 200);
+        assert!(long > short);
+    }
+
+    #[test]
+    fn test_estimate_reasoning_budget_clamps_to_bounds() {
+        let model_config = adaptive_config();
+
+        assert_eq!(estimate_reasoning_budget(&[], &model_config), 200);
+        assert_eq!(
+            estimate_reasoning_budget(&[user(&"word ".repeat(10_000))], &model_config),
+            4000
+        );
+    }
+
+    #[test]
+    fn test_estimate_reasoning_budget_uses_fixed_budget_without_bounds() {
+        let model_config = ModelConfig {
+            reasoning_budget: 1000,
+            reasoning_budget_min: Some(200),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            estimate_reasoning_budget(&[user(&"word ".repeat(10_000))], &model_config),
+            1000
+        );
+    }
+
+    #[test]
+    fn test_estimate_reasoning_budget_prefers_header_budget() {
+        let model_config = ModelConfig {
+            header_reasoning_budget: Some(50),
+            ..adaptive_config()
+        };
+
+        assert_eq!(
+            estimate_reasoning_budget(&[user(&"word ".repeat(10_000))], &model_config),
+            50
+        );
+    }
+}
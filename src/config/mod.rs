@@ -1,28 +1,346 @@
 use std::collections::HashMap;
+use std::net::{SocketAddr, ToSocketAddrs};
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::consts;
 use crate::errors::ReasonerError;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthScheme {
+    #[default]
+    Bearer,
+    Header {
+        name: String,
+        /// Placed before the key, nothing by default.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        prefix: Option<String>,
+    },
+    Query {
+        name: String,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UpstreamMode {
+    #[default]
+    #[serde(rename = "openai")]
+    OpenAI,
+    Azure {
+        deployment: String,
+        api_version: String,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestTemplate {
+    #[default]
+    #[serde(rename = "openai")]
+    OpenAI,
+    Azure,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReasoningBudgetExceededBehavior {
+    #[default]
+    Stub,
+    RetryWithLargerBudget {
+        max_budget: i32,
+    },
+    ReturnLength,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReasoningToolCallBehavior {
+    #[default]
+    Answer,
+    Surface,
+}
+
+/// How the upstream model marks off its reasoning from the answer.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReasoningDelimiterStyle {
+    #[default]
+    XmlTags,
+    MarkdownFence,
+}
+
+/// Where a configured system prompt goes relative to the client's own system message.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemPromptPlacement {
+    #[default]
+    Prepend,
+    Append,
+}
+
+/// Scale applied to the reasoning budget for each client `reasoning_effort` level.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct ReasoningEffortMultipliers {
+    pub low: f64,
+    pub medium: f64,
+    pub high: f64,
+}
+
+impl Default for ReasoningEffortMultipliers {
+    fn default() -> Self {
+        Self {
+            low: consts::REASONING_EFFORT_LOW_MULTIPLIER,
+            medium: consts::REASONING_EFFORT_MEDIUM_MULTIPLIER,
+            high: consts::REASONING_EFFORT_HIGH_MULTIPLIER,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ConfidenceGate {
+    pub min_avg_logprob: f64,
+    pub max_retries: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SamplingParam {
+    Temperature,
+    TopP,
+    N,
+    BestOf,
+    Seed,
+    PresencePenalty,
+    FrequencyPenalty,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct ModelConfig {
     pub model_name: String,
     pub api_url: String,
+    #[serde(default)]
+    pub completions_path: Option<String>,
     pub api_key: String,
+    #[serde(default)]
+    pub answer_api_key: Option<String>,
     pub reasoning_budget: i32,
     pub extra: Option<HashMap<String, Value>>,
+    #[serde(default)]
+    pub max_answer_chars: Option<usize>,
+    #[serde(default)]
+    pub auth_scheme: AuthScheme,
+    /// Deprecated: use the header `auth_scheme` with a `prefix` instead.
+    #[serde(default)]
+    pub auth_header: Option<String>,
+    /// Deprecated: use the header `auth_scheme` with a `prefix` instead.
+    #[serde(default)]
+    pub auth_prefix: Option<String>,
+    #[serde(default)]
+    pub upstream: UpstreamMode,
+    #[serde(default)]
+    pub allowed_upstream_models: Vec<String>,
+    #[serde(default)]
+    pub single_pass: bool,
+    #[serde(default)]
+    pub max_retries: Option<u32>,
+    #[serde(default)]
+    pub retry_base_delay_ms: Option<u64>,
+    #[serde(default)]
+    pub max_retry_after_secs: Option<u64>,
+    #[serde(default)]
+    pub max_total_retries: Option<u32>,
+    #[serde(default)]
+    pub retryable_error_substrings: Vec<String>,
+    #[serde(default)]
+    pub reasoning_budget_exceeded_behavior: ReasoningBudgetExceededBehavior,
+    #[serde(default)]
+    pub deterministic_ids: bool,
+    #[serde(default)]
+    pub reuse_client_reasoning: bool,
+    #[serde(default)]
+    pub accepted_content_types: Vec<String>,
+    #[serde(default)]
+    pub reasoning_param_filter: Option<Vec<SamplingParam>>,
+    #[serde(default)]
+    pub drop_empty_assistant_messages: bool,
+    #[serde(default)]
+    pub request_template: RequestTemplate,
+    #[serde(default)]
+    pub speculative_answer: bool,
+    #[serde(default)]
+    pub created: Option<i64>,
+    #[serde(default)]
+    pub input_price_per_1k: Option<f64>,
+    #[serde(default)]
+    pub output_price_per_1k: Option<f64>,
+    #[serde(default)]
+    pub answer_stop: Option<Vec<String>>,
+    #[serde(default)]
+    pub confidence_gate: Option<ConfidenceGate>,
+    #[serde(default)]
+    pub reasoning_default_temperature: Option<f64>,
+    #[serde(default)]
+    pub answer_default_temperature: Option<f64>,
+    #[serde(default)]
+    pub reasoning_tool_calls: ReasoningToolCallBehavior,
+    #[serde(default)]
+    pub default_max_tokens: Option<i32>,
+    #[serde(default)]
+    pub min_answer_tokens: i32,
+    #[serde(default)]
+    pub max_stop_sequences: Option<usize>,
+    #[serde(default)]
+    pub synthesize_answer_from_reasoning: bool,
+    #[serde(default)]
+    pub think_start: Option<String>,
+    #[serde(default)]
+    pub think_end: Option<String>,
+    #[serde(default)]
+    pub reasoning_delimiter_style: ReasoningDelimiterStyle,
+    #[serde(default)]
+    pub emit_reasoning_content: bool,
+    #[serde(default)]
+    pub max_retained_reasoning_turns: Option<usize>,
+    #[serde(default)]
+    pub forward_stream_options: Option<bool>,
+    #[serde(default)]
+    pub prime_think_content: Option<bool>,
+    #[serde(default)]
+    pub field_renames: HashMap<String, String>,
+    #[serde(default)]
+    pub fallbacks: Option<Vec<String>>,
+    #[serde(default)]
+    pub reasoning_prompt_augment: Option<String>,
+    #[serde(default)]
+    pub reasoning_budget_min: Option<i32>,
+    #[serde(default)]
+    pub reasoning_budget_max: Option<i32>,
+    /// Budget set for a single request by the `X-AR-Reasoning-Budget` header,
+    /// never read from the config file.
+    #[serde(skip)]
+    pub header_reasoning_budget: Option<i32>,
+    #[serde(default)]
+    pub reasoning_turn_messages: bool,
+    #[serde(default)]
+    pub reasoning_cutoff_stub: Option<String>,
+    #[serde(default)]
+    pub answer_system_prompt: Option<String>,
+    #[serde(default)]
+    pub answer_system_prompt_placement: SystemPromptPlacement,
+    #[serde(default)]
+    pub short_circuit_reasoning_refusal: bool,
+    #[serde(default)]
+    pub native_reasoning: bool,
+    #[serde(default)]
+    pub retry_blank_reasoning: bool,
+    /// Non-streaming only: streamed answers are forwarded as they arrive.
+    #[serde(default)]
+    pub retry_empty_answer: u8,
+    #[serde(default)]
+    pub reasoning_effort_multipliers: ReasoningEffortMultipliers,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServerConfig {
+    #[serde(default)]
+    pub max_concurrent_streams: Option<usize>,
+    #[serde(default)]
+    pub stream_reasoning_progress: bool,
+    #[serde(default)]
+    pub stream_diagnostics: bool,
+    #[serde(default)]
+    pub lenient_roles: bool,
+    #[serde(default)]
+    pub require_stream_usage: bool,
+    #[serde(default)]
+    pub redact_tool_arguments: bool,
+    #[serde(default)]
+    pub max_decoded_image_bytes: Option<usize>,
+    #[serde(default = "default_sse_done_sentinel")]
+    pub sse_done_sentinel: Option<String>,
+    #[serde(default)]
+    pub allowed_upstream_hosts: Option<Vec<String>>,
+    #[serde(default)]
+    pub max_header_reasoning_budget: Option<i32>,
+    #[serde(default)]
+    pub stream_idle_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub max_stream_duration_secs: Option<u64>,
+    #[serde(default = "default_stream_keep_alive_secs")]
+    pub stream_keep_alive_secs: Option<u64>,
+    #[serde(default)]
+    pub reasoning_cache_capacity: usize,
+    #[serde(default)]
+    pub reasoning_cache_ttl_secs: Option<u64>,
+    #[serde(default)]
+    pub reasoning_cache_include_unseeded: bool,
+    #[serde(default)]
+    pub single_flight: bool,
+    #[serde(default)]
+    pub discover_upstream_models: bool,
+    #[serde(default)]
+    pub preserve_upstream_ids: bool,
+    #[serde(default)]
+    pub hide_unhealthy_models: bool,
+    #[serde(default)]
+    pub allow_request_log_level: bool,
+    #[serde(default)]
+    pub shutdown_grace_period_secs: Option<u64>,
+}
+
+fn default_sse_done_sentinel() -> Option<String> {
+    Some(consts::SSE_DONE_SENTINEL.to_string())
+}
+
+fn default_stream_keep_alive_secs() -> Option<u64> {
+    Some(consts::DEFAULT_STREAM_KEEP_ALIVE_SECS)
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_streams: None,
+            stream_reasoning_progress: false,
+            stream_diagnostics: false,
+            lenient_roles: false,
+            require_stream_usage: false,
+            redact_tool_arguments: false,
+            max_decoded_image_bytes: None,
+            sse_done_sentinel: default_sse_done_sentinel(),
+            allowed_upstream_hosts: None,
+            max_header_reasoning_budget: None,
+            stream_idle_timeout_secs: None,
+            max_stream_duration_secs: None,
+            stream_keep_alive_secs: default_stream_keep_alive_secs(),
+            reasoning_cache_capacity: 0,
+            reasoning_cache_ttl_secs: None,
+            reasoning_cache_include_unseeded: false,
+            single_flight: false,
+            discover_upstream_models: false,
+            preserve_upstream_ids: false,
+            hide_unhealthy_models: false,
+            allow_request_log_level: false,
+            shutdown_grace_period_secs: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Config {
     pub models: HashMap<String, ModelConfig>,
+    #[serde(default)]
+    pub server: ServerConfig,
 }
 
 pub trait ConfigLoader: Send + Sync {
     fn load_config(&self) -> Result<Config, ReasonerError>;
 }
 
+#[derive(Default)]
 pub struct FileConfigLoader;
 
 impl FileConfigLoader {
@@ -35,17 +353,445 @@ impl ConfigLoader for FileConfigLoader {
     fn load_config(&self) -> Result<Config, ReasonerError> {
         let config_file = std::env::var("AR_CONFIG_FILE").unwrap_or("./config.json".to_string());
         let config_str = std::fs::read_to_string(&config_file)?;
-        let mut config: Config = serde_json::from_str(&config_str)?;
+        let mut config = parse_config(&config_file, &config_str)?;
 
         for model_config in config.models.values_mut() {
             model_config.api_key = std::env::var(&model_config.api_key).unwrap_or_default();
+            if let Some(answer_api_key) = model_config.answer_api_key.as_mut() {
+                *answer_api_key = std::env::var(&*answer_api_key).unwrap_or_default();
+            }
         }
+        config.validate()?;
 
         Ok(config)
     }
 }
 
+impl Config {
+    /// Checks every model for a usable upstream URL, a resolved API key and
+    /// reasoning budgets within range. Expects API keys to be resolved already.
+    pub fn validate(&self) -> Result<(), ReasonerError> {
+        for (model_name, model_config) in &self.models {
+            model_config.validate(model_name)?;
+        }
+        Ok(())
+    }
+}
+
+impl ModelConfig {
+    /// Scheme the API key is sent with, mapping the deprecated `auth_header`
+    /// and `auth_prefix` fields onto the header scheme.
+    pub fn resolve_auth_scheme(&self) -> AuthScheme {
+        if self.auth_header.is_none() && self.auth_prefix.is_none() {
+            return self.auth_scheme.clone();
+        }
+        AuthScheme::Header {
+            name: self
+                .auth_header
+                .clone()
+                .unwrap_or(consts::DEFAULT_AUTH_HEADER.to_string()),
+            prefix: Some(
+                self.auth_prefix
+                    .clone()
+                    .unwrap_or(consts::DEFAULT_AUTH_PREFIX.to_string()),
+            ),
+        }
+    }
+
+    fn validate(&self, model_name: &str) -> Result<(), ReasonerError> {
+        if self.api_url.trim().is_empty() {
+            return Err(ReasonerError::config(format!(
+                "model {model_name}: api_url is empty"
+            )));
+        }
+        let api_url = reqwest::Url::parse(&self.api_url).map_err(|e| {
+            ReasonerError::config(format!(
+                "model {model_name}: api_url {:?} is not a valid URL: {e}",
+                self.api_url
+            ))
+            .with_source(e)
+        })?;
+        if !matches!(api_url.scheme(), "http" | "https") {
+            return Err(ReasonerError::config(format!(
+                "model {model_name}: api_url {:?} is not a valid URL: expected an http or https scheme",
+                self.api_url
+            )));
+        }
+        if self.api_key.trim().is_empty() {
+            return Err(ReasonerError::config(format!(
+                "model {model_name}: api_key environment variable is unset or empty"
+            )));
+        }
+        if self
+            .answer_api_key
+            .as_ref()
+            .is_some_and(|answer_api_key| answer_api_key.trim().is_empty())
+        {
+            return Err(ReasonerError::config(format!(
+                "model {model_name}: answer_api_key environment variable is unset or empty"
+            )));
+        }
+        if self.auth_header.is_some() || self.auth_prefix.is_some() {
+            if self.auth_scheme != AuthScheme::Bearer {
+                return Err(ReasonerError::config(format!(
+                    "model {model_name}: the deprecated auth_header and auth_prefix cannot be combined with auth_scheme"
+                )));
+            }
+            log::warn!(
+                "model {model_name}: auth_header and auth_prefix are deprecated, use the header auth_scheme with a prefix"
+            );
+        }
+        // The Azure upstream mode implies the Azure template, which on its own
+        // would drop the model without a deployment to select it
+        if self.request_template == RequestTemplate::Azure && self.upstream == UpstreamMode::OpenAI
+        {
+            return Err(ReasonerError::config(format!(
+                "model {model_name}: request_template azure requires the azure upstream mode"
+            )));
+        }
+
+        validate_reasoning_budget(model_name, self.reasoning_budget)?;
+        if let ReasoningBudgetExceededBehavior::RetryWithLargerBudget { max_budget } =
+            self.reasoning_budget_exceeded_behavior
+        {
+            validate_reasoning_budget(model_name, max_budget)?;
+        }
+        for budget in [self.reasoning_budget_min, self.reasoning_budget_max]
+            .into_iter()
+            .flatten()
+        {
+            validate_reasoning_budget(model_name, budget)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses config contents as YAML for `.yaml`/`.yml` paths and as JSON otherwise.
+pub fn parse_config(path: &str, contents: &str) -> Result<Config, ReasonerError> {
+    let is_yaml = std::path::Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            extension.eq_ignore_ascii_case("yaml") || extension.eq_ignore_ascii_case("yml")
+        });
+    let mut config_value: Value = if is_yaml {
+        serde_norway::from_str(contents).map_err(|e| {
+            ReasonerError::config(format!("error: invalid YAML config {path}: {e}")).with_source(e)
+        })?
+    } else {
+        serde_json::from_str(contents)?
+    };
+    resolve_auto_reasoning_budgets(&mut config_value);
+    Ok(serde_json::from_value(config_value)?)
+}
+
+pub fn resolve_auto_reasoning_budget(model_name: &str) -> i32 {
+    let model_name = model_name.to_lowercase();
+    consts::AUTO_REASONING_BUDGETS
+        .iter()
+        .find(|(pattern, _)| model_name.contains(pattern))
+        .map_or(consts::DEFAULT_AUTO_REASONING_BUDGET, |(_, budget)| *budget)
+}
+
+/// Replaces `"reasoning_budget": "auto"` with the built-in budget for the model's `model_name`.
+fn resolve_auto_reasoning_budgets(config: &mut Value) {
+    let Some(models) = config.get_mut("models").and_then(Value::as_object_mut) else {
+        return;
+    };
+    for model in models.values_mut() {
+        if model.get("reasoning_budget").and_then(Value::as_str)
+            != Some(consts::AUTO_REASONING_BUDGET)
+        {
+            continue;
+        }
+        let model_name = model
+            .get("model_name")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        model["reasoning_budget"] = resolve_auto_reasoning_budget(model_name).into();
+    }
+}
+
+pub fn validate_reasoning_budget(model_name: &str, budget: i32) -> Result<(), ReasonerError> {
+    if !(0..=consts::MAX_REASONING_BUDGET).contains(&budget) {
+        return Err(ReasonerError::config(format!(
+            "model {model_name}: reasoning_budget {budget} is outside of the allowed range 0..={}",
+            consts::MAX_REASONING_BUDGET
+        )));
+    }
+    Ok(())
+}
+
 pub fn load_config() -> Result<Config, ReasonerError> {
     let loader = FileConfigLoader::new();
     loader.load_config()
 }
+
+/// Address the server listens on, from `AR_BIND_ADDR` and `AR_PORT` when set.
+pub fn load_bind_address() -> Result<SocketAddr, ReasonerError> {
+    resolve_bind_address(
+        std::env::var("AR_BIND_ADDR").ok().as_deref(),
+        std::env::var("AR_PORT").ok().as_deref(),
+    )
+}
+
+pub fn resolve_bind_address(
+    bind_addr: Option<&str>,
+    port: Option<&str>,
+) -> Result<SocketAddr, ReasonerError> {
+    let bind_addr = bind_addr.unwrap_or(consts::SERVER_BIND_ADDR).trim();
+    let port = match port {
+        Some(port) => port.trim().parse::<u16>().map_err(|e| {
+            ReasonerError::config(format!("error: invalid AR_PORT {port:?}: {e}")).with_source(e)
+        })?,
+        None => consts::SERVER_PORT,
+    };
+    (bind_addr, port)
+        .to_socket_addrs()
+        .map_err(|e| {
+            ReasonerError::config(format!("error: invalid AR_BIND_ADDR {bind_addr:?}: {e}"))
+                .with_source(e)
+        })?
+        .next()
+        .ok_or_else(|| {
+            ReasonerError::config(format!(
+                "error: AR_BIND_ADDR {bind_addr:?} does not resolve to an address"
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_auto_reasoning_budget_by_model_pattern() {
+        assert_eq!(resolve_auto_reasoning_budget("DeepSeek-R1"), 8192);
+        assert_eq!(
+            resolve_auto_reasoning_budget("deepseek-r1-distill-qwen-32b"),
+            4096
+        );
+        assert_eq!(resolve_auto_reasoning_budget("Qwen/QwQ-32B"), 16384);
+        assert_eq!(
+            resolve_auto_reasoning_budget("unknown-model"),
+            consts::DEFAULT_AUTO_REASONING_BUDGET
+        );
+    }
+
+    #[test]
+    fn test_auto_reasoning_budget_in_config() {
+        let mut config_value = serde_json::json!({
+            "models": {
+                "auto": {
+                    "model_name": "qwq-32b",
+                    "api_url": "http://localhost",
+                    "api_key": "KEY",
+                    "reasoning_budget": "auto",
+                },
+                "fixed": {
+                    "model_name": "qwq-32b",
+                    "api_url": "http://localhost",
+                    "api_key": "KEY",
+                    "reasoning_budget": 100,
+                },
+            }
+        });
+
+        resolve_auto_reasoning_budgets(&mut config_value);
+        let config: Config = serde_json::from_value(config_value).unwrap();
+
+        assert_eq!(config.models["auto"].reasoning_budget, 16384);
+        assert_eq!(config.models["fixed"].reasoning_budget, 100);
+    }
+
+    #[test]
+    fn test_parse_yaml_config_matches_json() {
+        let json = parse_config(
+            "example_config.json",
+            include_str!("../../example_config.json"),
+        )
+        .unwrap();
+        let yaml = parse_config(
+            "example_config.yaml",
+            include_str!("../../example_config.yaml"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&yaml).unwrap(),
+            serde_json::to_value(&json).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_yaml_config() {
+        match parse_config("config.yml", "models: [") {
+            Err(ReasonerError::ConfigError { message, .. }) => {
+                assert!(message.contains("config.yml"), "{message}");
+            }
+            other => panic!("Expected ConfigError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_resolve_bind_address() {
+        assert_eq!(
+            resolve_bind_address(None, None).unwrap(),
+            SocketAddr::from(([0, 0, 0, 0], consts::SERVER_PORT))
+        );
+        assert_eq!(
+            resolve_bind_address(Some("127.0.0.1"), Some("9000")).unwrap(),
+            SocketAddr::from(([127, 0, 0, 1], 9000))
+        );
+        assert_eq!(
+            resolve_bind_address(Some("::1"), Some(" 0 ")).unwrap(),
+            SocketAddr::from(([0, 0, 0, 0, 0, 0, 0, 1], 0))
+        );
+    }
+
+    #[test]
+    fn test_resolve_bind_address_rejects_invalid_values() {
+        for (bind_addr, port, expected) in [
+            (None, Some("http"), "AR_PORT"),
+            (None, Some("65536"), "AR_PORT"),
+            (Some("not an address"), None, "AR_BIND_ADDR"),
+        ] {
+            match resolve_bind_address(bind_addr, port) {
+                Err(ReasonerError::ConfigError { message, .. }) => {
+                    assert!(message.contains(expected), "{message}");
+                }
+                other => panic!("Expected ConfigError, got {:?}", other),
+            }
+        }
+    }
+
+    fn config_with_model(update: impl FnOnce(&mut ModelConfig)) -> Config {
+        let mut model_config = ModelConfig {
+            model_name: "upstream-model".to_string(),
+            api_url: "http://localhost:8080/v1".to_string(),
+            api_key: "sk-test".to_string(),
+            reasoning_budget: 512,
+            ..Default::default()
+        };
+        update(&mut model_config);
+
+        let mut config = Config::default();
+        config.models.insert("test-model".to_string(), model_config);
+        config
+    }
+
+    fn assert_validation_error(config: &Config, expected: &str) {
+        match config.validate() {
+            Err(ReasonerError::ConfigError { message, .. }) => {
+                assert!(message.contains("test-model"), "{message}");
+                assert!(message.contains(expected), "{message}");
+            }
+            other => panic!("Expected ConfigError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_config_accepts_valid_models() {
+        assert!(config_with_model(|_| {}).validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_config_rejects_empty_api_url() {
+        let config = config_with_model(|model| model.api_url = " ".to_string());
+        assert_validation_error(&config, "api_url is empty");
+    }
+
+    #[test]
+    fn test_validate_config_rejects_invalid_api_url() {
+        for api_url in ["not a url", "localhost:8080/v1"] {
+            let config = config_with_model(|model| model.api_url = api_url.to_string());
+            assert_validation_error(&config, "not a valid URL");
+        }
+    }
+
+    #[test]
+    fn test_validate_config_rejects_blank_api_key() {
+        let config = config_with_model(|model| model.api_key = String::new());
+        assert_validation_error(&config, "api_key");
+
+        let config = config_with_model(|model| model.answer_api_key = Some(String::new()));
+        assert_validation_error(&config, "answer_api_key");
+    }
+
+    #[test]
+    fn test_validate_config_rejects_auth_header_with_other_auth_scheme() {
+        let config = config_with_model(|model| {
+            model.auth_scheme = AuthScheme::Query {
+                name: "key".to_string(),
+            };
+            model.auth_prefix = Some(String::new());
+        });
+        assert_validation_error(&config, "auth_header and auth_prefix");
+    }
+
+    #[test]
+    fn test_resolve_auth_scheme_maps_deprecated_fields_to_header_scheme() {
+        let mut model_config = ModelConfig::default();
+        assert_eq!(model_config.resolve_auth_scheme(), AuthScheme::Bearer);
+
+        model_config.auth_prefix = Some("Token ".to_string());
+        assert_eq!(
+            model_config.resolve_auth_scheme(),
+            AuthScheme::Header {
+                name: "Authorization".to_string(),
+                prefix: Some("Token ".to_string()),
+            }
+        );
+
+        model_config.auth_header = Some("api-key".to_string());
+        model_config.auth_prefix = None;
+        assert_eq!(
+            model_config.resolve_auth_scheme(),
+            AuthScheme::Header {
+                name: "api-key".to_string(),
+                prefix: Some("Bearer ".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_config_rejects_azure_template_without_azure_upstream() {
+        let config = config_with_model(|model| model.request_template = RequestTemplate::Azure);
+        assert_validation_error(&config, "request_template azure");
+
+        let config = config_with_model(|model| {
+            model.request_template = RequestTemplate::Azure;
+            model.upstream = UpstreamMode::Azure {
+                deployment: "my-deployment".to_string(),
+                api_version: "2024-06-01".to_string(),
+            };
+        });
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_config_rejects_negative_reasoning_budget() {
+        let config = config_with_model(|model| model.reasoning_budget = -1);
+        assert_validation_error(&config, "reasoning_budget -1");
+    }
+
+    #[test]
+    fn test_validate_reasoning_budget_bounds() {
+        assert!(validate_reasoning_budget("test", 0).is_ok());
+        assert!(validate_reasoning_budget("test", 1).is_ok());
+        assert!(validate_reasoning_budget("test", consts::MAX_REASONING_BUDGET).is_ok());
+    }
+
+    #[test]
+    fn test_validate_reasoning_budget_out_of_range() {
+        for budget in [-1, i32::MIN, consts::MAX_REASONING_BUDGET + 1, i32::MAX] {
+            match validate_reasoning_budget("test-model", budget) {
+                Err(ReasonerError::ConfigError { message: msg, .. }) => {
+                    assert!(msg.contains("test-model"));
+                    assert!(msg.contains(&budget.to_string()));
+                }
+                other => panic!("Expected ConfigError, got {:?}", other),
+            }
+        }
+    }
+}
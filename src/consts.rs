@@ -1,15 +1,81 @@
+use crate::config::SamplingParam;
+
 pub(crate) const THINK_START: &str = "<think>";
 pub(crate) const THINK_END: &str = "</think>";
+pub(crate) const FENCE_THINK_START: &str = "```thinking\n";
+pub(crate) const FENCE_THINK_END: &str = "\n```";
 
 pub(crate) const REASONING_CUTOFF_STUB: &str =
     "Right, this is taking too long... Time to write the answer.";
 
+pub(crate) const UPSTREAM_MODEL_HEADER: &str = "X-AR-Upstream-Model";
+pub(crate) const REASONING_BUDGET_HEADER: &str = "X-AR-Reasoning-Budget";
+pub(crate) const REQUEST_LOG_LEVEL_HEADER: &str = "X-AR-Log-Level";
+pub(crate) const REQUEST_LOG_LEVELS: &[log::LevelFilter] =
+    &[log::LevelFilter::Debug, log::LevelFilter::Trace];
+
+pub(crate) const DEFAULT_AUTH_HEADER: &str = "Authorization";
+pub(crate) const DEFAULT_AUTH_PREFIX: &str = "Bearer ";
+pub(crate) const AZURE_API_KEY_HEADER: &str = "api-key";
+pub(crate) const DEFAULT_COMPLETIONS_PATH: &str = "/chat/completions";
+pub(crate) const MODELS_PATH: &str = "/models";
+
+pub(crate) const SSE_KEEP_ALIVE_COMMENT: &str = "keep-alive";
+pub(crate) const DEFAULT_STREAM_KEEP_ALIVE_SECS: u64 = 15;
+pub(crate) const SSE_DONE_SENTINEL: &str = "[DONE]";
+
+pub(crate) const HTML_ERROR_SNIPPET_CHARS: usize = 200;
+
+pub(crate) const DEFAULT_MAX_RETRIES: u32 = 2;
+pub(crate) const DEFAULT_RETRY_BASE_DELAY_MS: u64 = 250;
+pub(crate) const DEFAULT_MAX_RETRY_AFTER_SECS: u64 = 60;
+
+pub(crate) const DEFAULT_REASONING_PARAMS: &[SamplingParam] = &[
+    SamplingParam::Temperature,
+    SamplingParam::TopP,
+    SamplingParam::Seed,
+    SamplingParam::PresencePenalty,
+    SamplingParam::FrequencyPenalty,
+];
+
 pub(crate) const DEFAULT_MAX_TOKENS: i32 = 1024 * 1024;
+pub(crate) const MAX_REASONING_BUDGET: i32 = DEFAULT_MAX_TOKENS;
+pub(crate) const DEFAULT_MAX_STOP_SEQUENCES: usize = 4;
+
+pub(crate) const CHARS_PER_TOKEN_ESTIMATE: usize = 4;
+pub(crate) const REASONING_TOKENS_PER_INPUT_TOKEN: i64 = 4;
+
+pub(crate) const REASONING_EFFORT_LOW_MULTIPLIER: f64 = 0.5;
+pub(crate) const REASONING_EFFORT_MEDIUM_MULTIPLIER: f64 = 1.0;
+pub(crate) const REASONING_EFFORT_HIGH_MULTIPLIER: f64 = 2.0;
+
+pub(crate) const AUTO_REASONING_BUDGET: &str = "auto";
+pub(crate) const DEFAULT_AUTO_REASONING_BUDGET: i32 = 4096;
+/// Lowercase `model_name` substrings and their budgets, most specific first.
+pub(crate) const AUTO_REASONING_BUDGETS: &[(&str, i32)] = &[
+    ("deepseek-r1-distill", 4096),
+    ("deepseek-r1", 8192),
+    ("qwq", 16384),
+    ("qwen3", 8192),
+    ("phi-4-reasoning", 8192),
+    ("llama", 2048),
+    ("mistral", 2048),
+    ("gemma", 2048),
+];
 
 #[allow(dead_code)]
 pub(crate) const CONNECT_TIMEOUT_SECS: u64 = 30;
 #[allow(dead_code)]
 pub(crate) const READ_TIMEOUT_SECS: u64 = 60;
+pub(crate) const MAX_REQUEST_TIMEOUT_SECS: u64 = 600;
+pub(crate) const READINESS_PROBE_TIMEOUT_SECS: u64 = 2;
+pub(crate) const UNHEALTHY_MODEL_FAILURES: u32 = 3;
+#[allow(dead_code)]
+pub(crate) const DEFAULT_REASONING_CACHE_TTL_SECS: u64 = 300;
 pub const CHANNEL_BUFFER_SIZE: usize = 100;
+pub(crate) const STREAM_LIMIT_RETRY_AFTER_SECS: u64 = 1;
 #[allow(dead_code)]
+pub(crate) const DEFAULT_SHUTDOWN_GRACE_PERIOD_SECS: u64 = 30;
+pub(crate) const STREAM_DRAIN_POLL_INTERVAL_MS: u64 = 50;
+pub(crate) const SERVER_BIND_ADDR: &str = "0.0.0.0";
 pub(crate) const SERVER_PORT: u16 = 8080;
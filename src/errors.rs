@@ -1,77 +1,274 @@
+use std::error::Error;
 use std::fmt;
+use std::sync::Arc;
 
+// Shared rather than boxed so errors stay cloneable
+pub type ErrorSource = Arc<dyn Error + Send + Sync>;
+
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone)]
 pub enum ReasonerError {
-    ValidationError(String),
-    ApiError(String),
-    ParseError(String),
-    ConfigError(String),
-    NetworkError(String),
+    ValidationError {
+        message: String,
+        source: Option<ErrorSource>,
+    },
+    /// Upstream failure worth retrying: a 5xx response or a malformed reply.
+    ApiError {
+        message: String,
+        status: Option<u16>,
+        body: Option<String>,
+        source: Option<ErrorSource>,
+    },
+    /// Upstream answered 429 Too Many Requests.
+    RateLimitError {
+        message: String,
+        status: u16,
+        body: String,
+        source: Option<ErrorSource>,
+    },
+    /// Upstream rejected the request with a 4xx response; retrying will not help.
+    ClientError {
+        message: String,
+        status: u16,
+        body: String,
+        source: Option<ErrorSource>,
+    },
+    ParseError {
+        message: String,
+        source: Option<ErrorSource>,
+    },
+    ConfigError {
+        message: String,
+        source: Option<ErrorSource>,
+    },
+    NetworkError {
+        message: String,
+        source: Option<ErrorSource>,
+    },
+    TimeoutError {
+        message: String,
+        source: Option<ErrorSource>,
+    },
+}
+
+impl ReasonerError {
+    pub fn validation(message: impl Into<String>) -> Self {
+        ReasonerError::ValidationError {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    pub fn api(message: impl Into<String>) -> Self {
+        ReasonerError::ApiError {
+            message: message.into(),
+            status: None,
+            body: None,
+            source: None,
+        }
+    }
+
+    /// Non-success upstream response, kept whole so it can be relayed to the client
+    /// and classified so callers can tell retryable failures from rejections.
+    pub fn upstream_status(status: reqwest::StatusCode, body: String) -> Self {
+        let message = format!("error: status {status}, text {body}");
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return ReasonerError::RateLimitError {
+                message,
+                status: status.as_u16(),
+                body,
+                source: None,
+            };
+        }
+        if status.is_client_error() {
+            return ReasonerError::ClientError {
+                message,
+                status: status.as_u16(),
+                body,
+                source: None,
+            };
+        }
+        ReasonerError::ApiError {
+            message,
+            status: Some(status.as_u16()),
+            body: Some(body),
+            source: None,
+        }
+    }
+
+    pub fn parse(message: impl Into<String>) -> Self {
+        ReasonerError::ParseError {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    pub fn config(message: impl Into<String>) -> Self {
+        ReasonerError::ConfigError {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    pub fn network(message: impl Into<String>) -> Self {
+        ReasonerError::NetworkError {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    pub fn timeout(message: impl Into<String>) -> Self {
+        ReasonerError::TimeoutError {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    pub fn with_source(mut self, error: impl Error + Send + Sync + 'static) -> Self {
+        match &mut self {
+            ReasonerError::ValidationError { source, .. }
+            | ReasonerError::ApiError { source, .. }
+            | ReasonerError::RateLimitError { source, .. }
+            | ReasonerError::ClientError { source, .. }
+            | ReasonerError::ParseError { source, .. }
+            | ReasonerError::ConfigError { source, .. }
+            | ReasonerError::NetworkError { source, .. }
+            | ReasonerError::TimeoutError { source, .. } => *source = Some(Arc::new(error)),
+        }
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            ReasonerError::ValidationError { message, .. }
+            | ReasonerError::ApiError { message, .. }
+            | ReasonerError::RateLimitError { message, .. }
+            | ReasonerError::ClientError { message, .. }
+            | ReasonerError::ParseError { message, .. }
+            | ReasonerError::ConfigError { message, .. }
+            | ReasonerError::NetworkError { message, .. }
+            | ReasonerError::TimeoutError { message, .. } => message,
+        }
+    }
+
+    /// Status and body of the upstream response this error was built from, if any.
+    pub fn upstream_response(&self) -> Option<(u16, Option<&str>)> {
+        match self {
+            ReasonerError::ApiError {
+                status: Some(status),
+                body,
+                ..
+            } => Some((*status, body.as_deref())),
+            ReasonerError::RateLimitError { status, body, .. }
+            | ReasonerError::ClientError { status, body, .. } => Some((*status, Some(body))),
+            _ => None,
+        }
+    }
+
+    /// Whether the failure may go away when the request is sent again.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ReasonerError::ApiError { .. } | ReasonerError::NetworkError { .. }
+        )
+    }
+
+    /// OpenAI-style `error.type` reported to clients.
+    pub fn error_type(&self) -> &'static str {
+        match self {
+            ReasonerError::ValidationError { .. } => "invalid_request_error",
+            ReasonerError::ApiError { .. } | ReasonerError::ParseError { .. } => "api_error",
+            ReasonerError::RateLimitError { .. } => "rate_limit_exceeded",
+            ReasonerError::ClientError { .. } => "invalid_request_error",
+            ReasonerError::ConfigError { .. } => "server_error",
+            ReasonerError::NetworkError { .. } => "api_connection_error",
+            ReasonerError::TimeoutError { .. } => "timeout",
+        }
+    }
 }
 
 impl fmt::Display for ReasonerError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            ReasonerError::ValidationError { .. } => "Validation error",
+            ReasonerError::ApiError { .. } => "API error",
+            ReasonerError::RateLimitError { .. } => "Rate limit error",
+            ReasonerError::ClientError { .. } => "Client error",
+            ReasonerError::ParseError { .. } => "Parse error",
+            ReasonerError::ConfigError { .. } => "Config error",
+            ReasonerError::NetworkError { .. } => "Network error",
+            ReasonerError::TimeoutError { .. } => "Timeout error",
+        };
+        write!(f, "{}: {}", kind, self.message())
+    }
+}
+
+impl Error for ReasonerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
-            ReasonerError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
-            ReasonerError::ApiError(msg) => write!(f, "API error: {}", msg),
-            ReasonerError::ParseError(msg) => write!(f, "Parse error: {}", msg),
-            ReasonerError::ConfigError(msg) => write!(f, "Config error: {}", msg),
-            ReasonerError::NetworkError(msg) => write!(f, "Network error: {}", msg),
+            ReasonerError::ValidationError { source, .. }
+            | ReasonerError::ApiError { source, .. }
+            | ReasonerError::RateLimitError { source, .. }
+            | ReasonerError::ClientError { source, .. }
+            | ReasonerError::ParseError { source, .. }
+            | ReasonerError::ConfigError { source, .. }
+            | ReasonerError::NetworkError { source, .. }
+            | ReasonerError::TimeoutError { source, .. } => source
+                .as_deref()
+                .map(|source| source as &(dyn Error + 'static)),
         }
     }
 }
 
-impl std::error::Error for ReasonerError {}
-
 impl From<String> for ReasonerError {
     fn from(msg: String) -> Self {
-        ReasonerError::ValidationError(msg)
+        ReasonerError::validation(msg)
     }
 }
 
 impl From<&str> for ReasonerError {
     fn from(msg: &str) -> Self {
-        ReasonerError::ValidationError(msg.to_string())
+        ReasonerError::validation(msg)
     }
 }
 
 impl From<reqwest::Error> for ReasonerError {
     fn from(err: reqwest::Error) -> Self {
         if err.is_timeout() || err.is_connect() {
-            ReasonerError::NetworkError(err.to_string())
+            ReasonerError::network(err.to_string()).with_source(err)
         } else {
-            ReasonerError::ApiError(err.to_string())
+            ReasonerError::api(err.to_string()).with_source(err)
         }
     }
 }
 
 impl From<reqwest::header::ToStrError> for ReasonerError {
     fn from(err: reqwest::header::ToStrError) -> Self {
-        ReasonerError::ParseError(err.to_string())
+        ReasonerError::parse(err.to_string()).with_source(err)
     }
 }
 
 impl From<serde_json::Error> for ReasonerError {
     fn from(err: serde_json::Error) -> Self {
-        ReasonerError::ParseError(err.to_string())
+        ReasonerError::parse(err.to_string()).with_source(err)
     }
 }
 
 impl From<actix_web::error::Error> for ReasonerError {
     fn from(err: actix_web::error::Error) -> Self {
-        ReasonerError::ApiError(err.to_string())
+        ReasonerError::api(err.to_string())
     }
 }
 
 impl From<std::io::Error> for ReasonerError {
     fn from(err: std::io::Error) -> Self {
-        ReasonerError::ConfigError(err.to_string())
+        ReasonerError::config(err.to_string()).with_source(err)
     }
 }
 
 impl From<actix_web::mime::FromStrError> for ReasonerError {
     fn from(err: actix_web::mime::FromStrError) -> Self {
-        ReasonerError::ParseError(err.to_string())
+        ReasonerError::parse(err.to_string()).with_source(err)
     }
 }
 
@@ -82,39 +279,60 @@ mod tests {
 
     #[test]
     fn test_error_display_validation_error() {
-        let error = ReasonerError::ValidationError("test message".to_string());
+        let error = ReasonerError::validation("test message".to_string());
         assert_eq!(error.to_string(), "Validation error: test message");
     }
 
     #[test]
     fn test_error_display_api_error() {
-        let error = ReasonerError::ApiError("API failed".to_string());
+        let error = ReasonerError::api("API failed".to_string());
         assert_eq!(error.to_string(), "API error: API failed");
     }
 
     #[test]
     fn test_error_display_parse_error() {
-        let error = ReasonerError::ParseError("Invalid JSON".to_string());
+        let error = ReasonerError::parse("Invalid JSON".to_string());
         assert_eq!(error.to_string(), "Parse error: Invalid JSON");
     }
 
     #[test]
     fn test_error_display_config_error() {
-        let error = ReasonerError::ConfigError("Missing config".to_string());
+        let error = ReasonerError::config("Missing config".to_string());
         assert_eq!(error.to_string(), "Config error: Missing config");
     }
 
     #[test]
     fn test_error_display_network_error() {
-        let error = ReasonerError::NetworkError("Connection refused".to_string());
+        let error = ReasonerError::network("Connection refused".to_string());
         assert_eq!(error.to_string(), "Network error: Connection refused");
     }
 
+    #[test]
+    fn test_error_display_timeout_error() {
+        let error = ReasonerError::timeout("Deadline exceeded".to_string());
+        assert_eq!(error.to_string(), "Timeout error: Deadline exceeded");
+    }
+
+    #[test]
+    fn test_error_upstream_status() {
+        let body = r#"{"error": {"message": "Rate limit reached"}}"#.to_string();
+        let error = ReasonerError::upstream_status(reqwest::StatusCode::TOO_MANY_REQUESTS, body);
+        match &error {
+            ReasonerError::RateLimitError { status, body, .. } => {
+                assert_eq!(*status, 429);
+                assert!(body.contains("Rate limit reached"));
+            }
+            _ => panic!("Expected RateLimitError"),
+        }
+        assert!(error.to_string().contains("status 429 Too Many Requests"));
+        assert_eq!(error.upstream_response().unwrap().0, 429);
+    }
+
     #[test]
     fn test_error_from_string() {
         let error: ReasonerError = "test error".to_string().into();
         match error {
-            ReasonerError::ValidationError(msg) => {
+            ReasonerError::ValidationError { message: msg, .. } => {
                 assert_eq!(msg, "test error");
             }
             _ => panic!("Expected ValidationError"),
@@ -125,7 +343,7 @@ mod tests {
     fn test_error_from_str() {
         let error: ReasonerError = "test error".into();
         match error {
-            ReasonerError::ValidationError(msg) => {
+            ReasonerError::ValidationError { message: msg, .. } => {
                 assert_eq!(msg, "test error");
             }
             _ => panic!("Expected ValidationError"),
@@ -134,7 +352,7 @@ mod tests {
 
     #[test]
     fn test_error_debug() {
-        let error = ReasonerError::ValidationError("test".to_string());
+        let error = ReasonerError::validation("test".to_string());
         let debug_str = format!("{:?}", error);
         assert!(debug_str.contains("ValidationError"));
         assert!(debug_str.contains("test"));
@@ -142,13 +360,46 @@ mod tests {
 
     #[test]
     fn test_error_source() {
-        let error = ReasonerError::ApiError("API error".to_string());
+        let error = ReasonerError::api("API error".to_string());
         assert!(error.source().is_none());
     }
 
+    #[test]
+    fn test_error_source_from_reqwest_error() {
+        let err = reqwest::Client::new().get("not a url").build().unwrap_err();
+        let message = err.to_string();
+
+        let error = ReasonerError::from(err);
+        assert!(matches!(error, ReasonerError::ApiError { .. }));
+        assert_eq!(error.to_string(), format!("API error: {message}"));
+        let source = error.source().unwrap();
+        assert!(source.downcast_ref::<reqwest::Error>().is_some());
+    }
+
+    #[test]
+    fn test_error_source_from_serde_json_error() {
+        let err = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        let message = err.to_string();
+
+        let error = ReasonerError::from(err);
+        assert!(matches!(error, ReasonerError::ParseError { .. }));
+        assert_eq!(error.to_string(), format!("Parse error: {message}"));
+        let source = error.source().unwrap();
+        assert_eq!(
+            source
+                .downcast_ref::<serde_json::Error>()
+                .unwrap()
+                .to_string(),
+            message
+        );
+
+        let cloned = error.clone();
+        assert!(cloned.source().is_some());
+    }
+
     #[test]
     fn test_error_clone() {
-        let error = ReasonerError::NetworkError("timeout".to_string());
+        let error = ReasonerError::network("timeout".to_string());
         let cloned = error.clone();
         assert_eq!(error.to_string(), cloned.to_string());
     }
@@ -1,4 +1,6 @@
-use actix_web::http::StatusCode;
+use std::collections::BTreeSet;
+
+use actix_web::http::{StatusCode, header};
 use actix_web::web::{Bytes, Data};
 use actix_web::mime;
 use tokio::sync::mpsc;
@@ -7,18 +9,32 @@ use tokio_stream::wrappers::ReceiverStream;
 use crate::config;
 use crate::consts;
 use crate::errors::ReasonerError;
+use crate::llm_request;
+use crate::log_context;
 use crate::models::{model_list, request};
+use crate::openapi;
 use crate::service::ReasoningService;
 
-pub async fn models(config: Data<config::Config>) -> impl actix_web::Responder {
+pub async fn models(
+    service: Data<ReasoningService>,
+    config: Data<config::Config>,
+) -> impl actix_web::Responder {
     let mut model_list: Vec<model_list::Model> = vec![];
 
-    for model_name in config.models.keys() {
+    for (model_name, model_config) in config.models.iter() {
+        if config.server.hide_unhealthy_models && !service.model_healthy(model_name) {
+            continue;
+        }
+        let mut upstream = service.discovered_model(model_name).unwrap_or_default();
+        for field in ["id", "object", "created", "owned_by"] {
+            upstream.remove(field);
+        }
         model_list.push(model_list::Model {
             id: model_name.to_string(),
             object: model_list::ObjectType::Model,
-            created: 0,
+            created: model_config.created.unwrap_or(service.started_at()),
             owned_by: model_list::Owner::AdaptiveReasoner,
+            upstream,
         });
     }
 
@@ -27,50 +43,327 @@ pub async fn models(config: Data<config::Config>) -> impl actix_web::Responder {
     actix_web::HttpResponse::Ok().json(model_list)
 }
 
+pub async fn metrics(service: Data<ReasoningService>) -> impl actix_web::Responder {
+    actix_web::HttpResponse::Ok()
+        .content_type(mime::TEXT_PLAIN)
+        .body(format!(
+            "adaptive_reasoner_active_streams {}\n",
+            service.active_streams()
+        ))
+}
+
+pub async fn usage(service: Data<ReasoningService>) -> impl actix_web::Responder {
+    actix_web::HttpResponse::Ok().json(serde_json::json!({ "models": service.usage() }))
+}
+
+pub async fn health(config: Data<config::Config>) -> impl actix_web::Responder {
+    actix_web::HttpResponse::Ok().json(serde_json::json!({
+        "status": "ok",
+        "models": config.models.len(),
+    }))
+}
+
+pub async fn ready(
+    service: Data<ReasoningService>,
+    config: Data<config::Config>,
+) -> impl actix_web::Responder {
+    let api_urls: BTreeSet<String> = config
+        .models
+        .values()
+        .map(|model_config| model_config.api_url.clone())
+        .collect();
+
+    let upstreams = api_urls.len();
+    let mut probes = tokio::task::JoinSet::new();
+    for api_url in api_urls {
+        let service = service.clone();
+        probes.spawn(async move { service.upstream_reachable(&api_url).await });
+    }
+    let reachable = probes
+        .join_all()
+        .await
+        .into_iter()
+        .filter(|reachable| *reachable)
+        .count();
+
+    let status = if reachable > 0 {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    actix_web::HttpResponse::build(status).json(serde_json::json!({
+        "status": if reachable > 0 { "ready" } else { "unavailable" },
+        "upstreams": upstreams,
+        "reachable": reachable,
+    }))
+}
+
+pub async fn openapi() -> impl actix_web::Responder {
+    actix_web::HttpResponse::Ok().json(openapi::openapi_document())
+}
+
+fn apply_upstream_model_override(
+    http_request: &actix_web::HttpRequest,
+    model_config: &mut config::ModelConfig,
+) -> Result<(), ReasonerError> {
+    let Some(header) = http_request.headers().get(consts::UPSTREAM_MODEL_HEADER) else {
+        return Ok(());
+    };
+
+    let upstream_model = header
+        .to_str()
+        .map_err(|e| ReasonerError::validation(e.to_string()).with_source(e))?;
+    if !model_config
+        .allowed_upstream_models
+        .iter()
+        .any(|allowed| allowed == upstream_model)
+    {
+        return Err(ReasonerError::validation(format!(
+            "error: upstream model override not allowed: {upstream_model}"
+        )));
+    }
+
+    model_config.model_name = upstream_model.to_string();
+    Ok(())
+}
+
+// The header is only honored when the server sets a ceiling for it. Returns the
+// budget the header applied, if any.
+fn apply_reasoning_budget_override(
+    http_request: &actix_web::HttpRequest,
+    model_config: &mut config::ModelConfig,
+    max_header_reasoning_budget: Option<i32>,
+) -> Result<Option<i32>, ReasonerError> {
+    let Some(max_budget) = max_header_reasoning_budget else {
+        return Ok(None);
+    };
+    let Some(header) = http_request.headers().get(consts::REASONING_BUDGET_HEADER) else {
+        return Ok(None);
+    };
+
+    let header = header
+        .to_str()
+        .map_err(|e| ReasonerError::validation(e.to_string()).with_source(e))?
+        .trim();
+    let budget: i32 = if header.eq_ignore_ascii_case(consts::AUTO_REASONING_BUDGET) {
+        config::resolve_auto_reasoning_budget(&model_config.model_name)
+    } else {
+        header.parse().map_err(|e| {
+            ReasonerError::validation(format!("error: invalid reasoning budget header: {e}"))
+        })?
+    };
+
+    let budget = budget.clamp(0, max_budget.min(consts::MAX_REASONING_BUDGET));
+    model_config.header_reasoning_budget = Some(budget);
+    Ok(Some(budget))
+}
+
+// `"reasoning_budget": "auto"` is resolved like the header value, once the
+// upstream model is known
+fn take_auto_reasoning_budget(request_body: &mut serde_json::Value) -> bool {
+    let is_auto = request_body
+        .get("reasoning_budget")
+        .and_then(serde_json::Value::as_str)
+        .is_some_and(|budget| budget.eq_ignore_ascii_case(consts::AUTO_REASONING_BUDGET));
+    if is_auto && let Some(request_body) = request_body.as_object_mut() {
+        request_body.remove("reasoning_budget");
+    }
+    is_auto
+}
+
+// Keeps a budget from the request body within the header's budget, or within
+// the server's header ceiling when no header was sent
+fn clamp_request_reasoning_budget(
+    request: &mut request::ChatCompletionCreate,
+    ceiling: Option<i32>,
+) {
+    if let (Some(budget), Some(ceiling)) = (request.reasoning_budget.as_mut(), ceiling) {
+        *budget = (*budget).min(ceiling);
+    }
+}
+
+// The header is only honored when the server allows request log levels
+fn request_log_level(
+    http_request: &actix_web::HttpRequest,
+    allow_request_log_level: bool,
+) -> Result<Option<log::LevelFilter>, ReasonerError> {
+    if !allow_request_log_level {
+        return Ok(None);
+    }
+    let Some(header) = http_request.headers().get(consts::REQUEST_LOG_LEVEL_HEADER) else {
+        return Ok(None);
+    };
+
+    let header = header
+        .to_str()
+        .map_err(|e| ReasonerError::validation(e.to_string()).with_source(e))?;
+    log_context::parse_level(header).map(Some)
+}
+
 pub async fn chat_completion(
     service: Data<ReasoningService>,
     config: Data<config::Config>,
-    request: actix_web::web::Json<request::ChatCompletionCreate>,
+    http_request: actix_web::HttpRequest,
+    request: actix_web::web::Json<serde_json::Value>,
 ) -> impl actix_web::Responder {
-    let model_config = match config.models.get(&request.0.model).cloned() {
+    let log_level = match request_log_level(&http_request, config.server.allow_request_log_level) {
+        Ok(log_level) => log_level,
+        Err(e) => {
+            log::info!("{e}");
+            return error_response(&e);
+        }
+    };
+
+    log_context::scope(
+        log_level,
+        handle_chat_completion(service, config, http_request, request.into_inner()),
+    )
+    .await
+}
+
+async fn handle_chat_completion(
+    service: Data<ReasoningService>,
+    config: Data<config::Config>,
+    http_request: actix_web::HttpRequest,
+    mut request_body: serde_json::Value,
+) -> actix_web::HttpResponse {
+    if config.server.lenient_roles {
+        request::normalize_message_roles(&mut request_body);
+    }
+    let auto_reasoning_budget = take_auto_reasoning_budget(&mut request_body);
+    let mut request: request::ChatCompletionCreate = match serde_json::from_value(request_body) {
+        Ok(request) => request,
+        Err(e) => {
+            log::info!("error: invalid request: {e}");
+            return actix_web::HttpResponse::BadRequest().finish();
+        }
+    };
+
+    let mut model_config = match config.models.get(&request.model).cloned() {
         Some(model_config) => model_config,
         None => {
-            log::info!("error: model not found: {:?}", request.0.model);
+            log::info!("error: model not found: {:?}", request.model);
             return actix_web::HttpResponse::BadRequest().finish();
         }
     };
 
-    log::debug!("request: {:?}", request.0);
+    if let Err(e) = apply_upstream_model_override(&http_request, &mut model_config) {
+        log::info!("{e}");
+        return error_response(&e);
+    }
+
+    if auto_reasoning_budget {
+        request.reasoning_budget = Some(config::resolve_auto_reasoning_budget(
+            &model_config.model_name,
+        ));
+    }
+
+    match apply_reasoning_budget_override(
+        &http_request,
+        &mut model_config,
+        config.server.max_header_reasoning_budget,
+    ) {
+        Ok(header_budget) => clamp_request_reasoning_budget(
+            &mut request,
+            header_budget.or(config.server.max_header_reasoning_budget),
+        ),
+        Err(e) => {
+            log::info!("{e}");
+            return error_response(&e);
+        }
+    }
+
+    if let Err(e) = llm_request::validate_stream_usage(&request, config.server.require_stream_usage)
+    {
+        log::info!("{e}");
+        return error_response(&e);
+    }
+
+    if let Err(e) =
+        llm_request::validate_image_size(&request, config.server.max_decoded_image_bytes)
+    {
+        log::info!("{e}");
+        return error_response(&e);
+    }
+
+    if config.server.redact_tool_arguments {
+        log::debug!(
+            "request: {:?}",
+            llm_request::redact_tool_arguments(&request)
+        );
+    } else {
+        log::debug!("request: {:?}", request);
+    }
 
     if request.stream.unwrap_or(false) {
+        let Some(stream_permit) = service.try_acquire_stream() else {
+            log::info!("error: concurrent stream limit reached");
+            return actix_web::HttpResponse::ServiceUnavailable()
+                .insert_header((
+                    header::RETRY_AFTER,
+                    consts::STREAM_LIMIT_RETRY_AFTER_SECS.to_string(),
+                ))
+                .finish();
+        };
+
         let (sender, receiver) =
             mpsc::channel::<Result<Bytes, ReasonerError>>(consts::CHANNEL_BUFFER_SIZE);
-        actix_web::rt::spawn(async move {
+        actix_web::rt::spawn(log_context::scope(log_context::current(), async move {
+            let _stream_permit = stream_permit;
             if let Err(e) = service
-                .stream_completion(request.0, &model_config, sender)
+                .stream_completion(request, &model_config, sender)
                 .await
             {
                 log::error!("stream_chat_completion error: {:?}", e);
             }
-        });
+        }));
 
         return actix_web::HttpResponse::Ok()
             .content_type(mime::TEXT_EVENT_STREAM)
             .streaming(ReceiverStream::new(receiver));
     }
 
-    match service.create_completion(request.0, &model_config).await {
+    match service.create_completion(request, &model_config).await {
         Ok(chat_completion) => actix_web::HttpResponse::Ok().json(chat_completion),
         Err(e) => {
             log::error!("create_chat_completion error: {:?}", e);
-            let status = match e {
-                ReasonerError::ValidationError(_) => StatusCode::BAD_REQUEST,
-                ReasonerError::ApiError(_) => StatusCode::BAD_GATEWAY,
-                ReasonerError::ParseError(_) => StatusCode::BAD_GATEWAY,
-                ReasonerError::ConfigError(_) => StatusCode::INTERNAL_SERVER_ERROR,
-                ReasonerError::NetworkError(_) => StatusCode::BAD_GATEWAY,
-            };
-            actix_web::HttpResponse::build(status).finish()
+            error_response(&e)
         }
     }
 }
+
+/// Relays upstream failures with their original status, passing OpenAI-shaped
+/// error bodies through untouched and wrapping everything else in one.
+fn error_response(error: &ReasonerError) -> actix_web::HttpResponse {
+    if let Some((status, body)) = error.upstream_response()
+        && let Ok(status) = StatusCode::from_u16(status)
+    {
+        let upstream_body = body
+            .and_then(|body| serde_json::from_str::<serde_json::Value>(body).ok())
+            .filter(|body| body.get("error").is_some_and(serde_json::Value::is_object));
+        return actix_web::HttpResponse::build(status)
+            .json(upstream_body.unwrap_or_else(|| error_body(error)));
+    }
+
+    let status = match error {
+        ReasonerError::ValidationError { .. } => StatusCode::BAD_REQUEST,
+        ReasonerError::ApiError { .. } => StatusCode::BAD_GATEWAY,
+        ReasonerError::RateLimitError { .. } => StatusCode::TOO_MANY_REQUESTS,
+        ReasonerError::ClientError { .. } => StatusCode::BAD_REQUEST,
+        ReasonerError::ParseError { .. } => StatusCode::BAD_GATEWAY,
+        ReasonerError::ConfigError { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        ReasonerError::NetworkError { .. } => StatusCode::BAD_GATEWAY,
+        ReasonerError::TimeoutError { .. } => StatusCode::GATEWAY_TIMEOUT,
+    };
+    actix_web::HttpResponse::build(status).json(error_body(error))
+}
+
+fn error_body(error: &ReasonerError) -> serde_json::Value {
+    serde_json::json!({
+        "error": {
+            "message": error.message(),
+            "type": error.error_type(),
+            "code": null,
+        }
+    })
+}
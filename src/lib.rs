@@ -1,9 +1,12 @@
 pub mod app;
+pub mod budget;
 pub mod config;
 pub mod consts;
 pub mod errors;
 pub mod handlers;
 pub mod llm_client;
 pub mod llm_request;
+pub mod log_context;
 pub mod models;
+pub mod openapi;
 pub mod service;
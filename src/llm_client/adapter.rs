@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::config::RequestTemplate;
+
+pub(crate) fn adapt_request(template: RequestTemplate, body: &mut Value) {
+    match template {
+        RequestTemplate::OpenAI => {}
+        RequestTemplate::Azure => {
+            // The deployment in the URL selects the model
+            if let Some(body) = body.as_object_mut() {
+                body.remove("model");
+            }
+        }
+    }
+}
+
+pub(crate) fn adapt_response(template: RequestTemplate, body: &mut Value) {
+    match template {
+        RequestTemplate::OpenAI => {}
+        RequestTemplate::Azure => {
+            let Some(body) = body.as_object_mut() else {
+                return;
+            };
+            body.remove("prompt_filter_results");
+            if let Some(choices) = body.get_mut("choices").and_then(Value::as_array_mut) {
+                for choice in choices.iter_mut().filter_map(Value::as_object_mut) {
+                    choice.remove("content_filter_results");
+                }
+            }
+        }
+    }
+}
+
+/// Renames top-level request fields for upstreams with their own naming.
+pub(crate) fn rename_fields(renames: &HashMap<String, String>, body: &mut Value) {
+    let Some(body) = body.as_object_mut() else {
+        return;
+    };
+    for (from, to) in renames {
+        if let Some(value) = body.remove(from) {
+            body.insert(to.clone(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_openai_template_keeps_body() {
+        let original = json!({"model": "gpt", "messages": [], "choices": []});
+        let mut body = original.clone();
+
+        adapt_request(RequestTemplate::OpenAI, &mut body);
+        adapt_response(RequestTemplate::OpenAI, &mut body);
+
+        assert_eq!(body, original);
+    }
+
+    #[test]
+    fn test_azure_template_reshapes_bodies() {
+        let mut request = json!({"model": "gpt", "messages": [{"role": "user", "content": "Hi"}]});
+        adapt_request(RequestTemplate::Azure, &mut request);
+        assert_eq!(
+            request,
+            json!({"messages": [{"role": "user", "content": "Hi"}]})
+        );
+
+        let mut response = json!({
+            "id": "chatcmpl-1",
+            "prompt_filter_results": [{"prompt_index": 0}],
+            "choices": [{"index": 0, "content_filter_results": {"hate": {"filtered": false}}}],
+        });
+        adapt_response(RequestTemplate::Azure, &mut response);
+        assert_eq!(
+            response,
+            json!({"id": "chatcmpl-1", "choices": [{"index": 0}]})
+        );
+    }
+
+    #[test]
+    fn test_rename_fields() {
+        let renames = HashMap::from([
+            ("max_tokens".to_string(), "maxTokens".to_string()),
+            ("top_k".to_string(), "topK".to_string()),
+        ]);
+        let mut body = json!({"model": "gpt", "max_tokens": 100, "messages": []});
+
+        rename_fields(&renames, &mut body);
+
+        assert_eq!(
+            body,
+            json!({"model": "gpt", "maxTokens": 100, "messages": []})
+        );
+    }
+}
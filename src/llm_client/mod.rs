@@ -1,13 +1,27 @@
 use std::collections::HashMap;
+use std::hash::{BuildHasher, RandomState};
+use std::time::{Duration, SystemTime};
 
 use actix_web::mime;
 use async_trait::async_trait;
 use reqwest::Response;
 use serde_json::Value;
 
+use crate::config::{AuthScheme, ModelConfig, RequestTemplate, UpstreamMode};
+use crate::consts;
 use crate::errors::ReasonerError;
+use crate::llm_request::truncate_to_chars;
 use crate::models::request;
 
+mod adapter;
+mod retry_budget;
+mod upstream;
+
+pub use retry_budget::RetryBudget;
+#[allow(unused_imports)]
+pub use upstream::{StubReply, StubUpstream};
+pub use upstream::{Upstream, UpstreamStream};
+
 #[async_trait]
 pub trait LLMClientTrait: Send + Sync {
     async fn request_chat_completion(
@@ -20,24 +34,222 @@ pub trait LLMClientTrait: Send + Sync {
 pub struct LLMClient {
     client: reqwest::Client,
     base_url: String,
+    completions_path: String,
     api_key: String,
+    auth_scheme: AuthScheme,
+    upstream: UpstreamMode,
     extra_body: Option<HashMap<String, Value>>,
+    max_retries: u32,
+    retry_base_delay: Duration,
+    max_retry_after: Duration,
+    retry_budget: RetryBudget,
+    retryable_error_substrings: Vec<String>,
+    accepted_content_types: Vec<String>,
+    request_template: RequestTemplate,
+    field_renames: HashMap<String, String>,
+    allowed_hosts: Option<Vec<String>>,
 }
 
 impl LLMClient {
-    pub fn new(
-        client: reqwest::Client,
-        base_url: &str,
-        api_key: &str,
-        extra_body: &Option<HashMap<String, Value>>,
-    ) -> Self {
+    pub fn new(client: reqwest::Client, model_config: &ModelConfig) -> Self {
+        let (auth_scheme, request_template) = match model_config.upstream {
+            UpstreamMode::OpenAI => (
+                model_config.resolve_auth_scheme(),
+                model_config.request_template,
+            ),
+            UpstreamMode::Azure { .. } => (
+                AuthScheme::Header {
+                    name: consts::AZURE_API_KEY_HEADER.to_string(),
+                    prefix: None,
+                },
+                RequestTemplate::Azure,
+            ),
+        };
+
         Self {
             client,
-            base_url: base_url.to_string(),
-            api_key: api_key.to_string(),
-            extra_body: extra_body.clone(),
+            base_url: model_config.api_url.to_string(),
+            completions_path: model_config
+                .completions_path
+                .clone()
+                .unwrap_or(consts::DEFAULT_COMPLETIONS_PATH.to_string()),
+            api_key: model_config.api_key.to_string(),
+            auth_scheme,
+            upstream: model_config.upstream.clone(),
+            extra_body: model_config.extra.clone(),
+            max_retries: model_config
+                .max_retries
+                .unwrap_or(consts::DEFAULT_MAX_RETRIES),
+            retry_base_delay: Duration::from_millis(
+                model_config
+                    .retry_base_delay_ms
+                    .unwrap_or(consts::DEFAULT_RETRY_BASE_DELAY_MS),
+            ),
+            max_retry_after: Duration::from_secs(
+                model_config
+                    .max_retry_after_secs
+                    .unwrap_or(consts::DEFAULT_MAX_RETRY_AFTER_SECS),
+            ),
+            retry_budget: RetryBudget::new(model_config.max_total_retries),
+            retryable_error_substrings: model_config
+                .retryable_error_substrings
+                .iter()
+                .map(|substring| substring.to_lowercase())
+                .collect(),
+            accepted_content_types: model_config.accepted_content_types.clone(),
+            request_template,
+            field_renames: model_config.field_renames.clone(),
+            allowed_hosts: None,
+        }
+    }
+
+    /// Shares `retry_budget` with other clients serving the same completion.
+    pub fn with_retry_budget(mut self, retry_budget: RetryBudget) -> Self {
+        self.retry_budget = retry_budget;
+        self
+    }
+
+    pub fn with_allowed_hosts(mut self, allowed_hosts: Option<Vec<String>>) -> Self {
+        self.allowed_hosts = allowed_hosts;
+        self
+    }
+
+    fn check_allowed_host(&self, url: &str) -> Result<(), ReasonerError> {
+        check_allowed_host(self.allowed_hosts.as_deref(), url)
+    }
+
+    fn accepts_content_type(&self, content_type: &mime::Mime, expected: &mime::Mime) -> bool {
+        if *expected != mime::APPLICATION_JSON || self.accepted_content_types.is_empty() {
+            return content_type.essence_str() == expected.essence_str();
+        }
+        self.accepted_content_types
+            .iter()
+            .any(|accepted| accepted.eq_ignore_ascii_case(content_type.essence_str()))
+    }
+
+    // Only buffered JSON bodies are adapted, streamed chunks pass through as-is
+    async fn adapt_json_response(&self, response: Response) -> Result<Response, ReasonerError> {
+        let status = response.status();
+        let mut headers = response.headers().clone();
+        let mut body: Value = response.json().await?;
+        adapter::adapt_response(self.request_template, &mut body);
+
+        headers.remove(reqwest::header::CONTENT_LENGTH);
+        let mut adapted = http::Response::new(serde_json::to_vec(&body)?);
+        *adapted.status_mut() = status;
+        *adapted.headers_mut() = headers;
+        Ok(Response::from(adapted))
+    }
+
+    // Every retry counts against both the per-call attempts and the completion's budget
+    fn next_attempt(&self, attempt: u32) -> Option<u32> {
+        (attempt < self.max_retries && self.retry_budget.take()).then_some(attempt + 1)
+    }
+
+    fn inspects_error_body(&self, response: &Response) -> bool {
+        !response.status().is_success()
+            && !self.retryable_error_substrings.is_empty()
+            && !is_html_response(response)
+    }
+
+    // Some upstreams only tell transient failures apart in the error body
+    fn is_retryable_error(&self, text: &str) -> bool {
+        let text = text.to_lowercase();
+        self.retryable_error_substrings
+            .iter()
+            .any(|substring| text.contains(substring.as_str()))
+    }
+
+    /// Entries of the upstream `/models` listing.
+    pub async fn list_models(&self) -> Result<Vec<Value>, ReasonerError> {
+        let url = match &self.upstream {
+            UpstreamMode::OpenAI => self.upstream_url(consts::MODELS_PATH),
+            UpstreamMode::Azure { .. } => {
+                return Err(ReasonerError::config(
+                    "error: model discovery is not supported for Azure upstreams",
+                ));
+            }
+        };
+        self.check_allowed_host(&url)?;
+
+        let response = self.authorize(self.client.get(&url)).send().await?;
+        if !response.status().is_success() {
+            return Err(status_error(response).await);
+        }
+
+        let mut body: Value = response.json().await?;
+        match body.get_mut("data").map(Value::take) {
+            Some(Value::Array(models)) => Ok(models),
+            _ => Err(ReasonerError::parse(
+                "error: upstream model listing has no data array",
+            )),
         }
     }
+
+    /// Joins `path` onto the base URL with exactly one slash between them.
+    fn upstream_url(&self, path: &str) -> String {
+        format!(
+            "{}/{}",
+            self.base_url.trim_end_matches('/'),
+            path.trim_start_matches('/')
+        )
+    }
+
+    fn completions_url(&self) -> String {
+        match &self.upstream {
+            UpstreamMode::OpenAI => self.upstream_url(&self.completions_path),
+            UpstreamMode::Azure { deployment, .. } => format!(
+                "{}/openai/deployments/{}/chat/completions",
+                self.base_url.trim_end_matches('/'),
+                deployment
+            ),
+        }
+    }
+
+    fn build_request(&self, url: &str) -> reqwest::RequestBuilder {
+        let mut request = self.client.post(url);
+        if let UpstreamMode::Azure { api_version, .. } = &self.upstream {
+            request = request.query(&[("api-version", api_version)]);
+        }
+        self.authorize(request)
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.auth_scheme {
+            AuthScheme::Bearer => {
+                request.header("Authorization", format!("Bearer {}", self.api_key))
+            }
+            AuthScheme::Header { name, prefix } => request.header(
+                name,
+                format!("{}{}", prefix.as_deref().unwrap_or_default(), self.api_key),
+            ),
+            AuthScheme::Query { name } => request.query(&[(name, &self.api_key)]),
+        }
+    }
+}
+
+/// Rejects `url` unless its host is one of `allowed_hosts`; any host passes
+/// when no allow-list is configured.
+pub(crate) fn check_allowed_host(
+    allowed_hosts: Option<&[String]>,
+    url: &str,
+) -> Result<(), ReasonerError> {
+    let Some(allowed_hosts) = allowed_hosts else {
+        return Ok(());
+    };
+
+    let url = reqwest::Url::parse(url)
+        .map_err(|e| ReasonerError::config(format!("error: invalid upstream url: {e}")))?;
+    let host = url.host_str().unwrap_or_default();
+    if !allowed_hosts
+        .iter()
+        .any(|allowed| allowed.eq_ignore_ascii_case(host))
+    {
+        return Err(ReasonerError::config(format!(
+            "error: upstream host not allowed: {host}"
+        )));
+    }
+    Ok(())
 }
 
 #[async_trait]
@@ -51,32 +263,296 @@ impl LLMClientTrait for LLMClient {
             request.extra = extra_body;
         }
 
-        let response = self
-            .client
-            .post(format!("{}{}", self.base_url, "/chat/completions"))
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&request)
-            .send()
-            .await?;
+        let url = self.completions_url();
+        self.check_allowed_host(&url)?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
+        let mut body = serde_json::to_value(&request)?;
+        adapter::adapt_request(self.request_template, &mut body);
+        adapter::rename_fields(&self.field_renames, &mut body);
 
-            return Err(ReasonerError::ApiError(format!(
-                "error: status {status}, text {text}"
-            )));
+        let mut attempt = 0;
+        let response = loop {
+            let result = self.build_request(&url).json(&body).send().await;
+            let retry_after = result.as_ref().ok().and_then(rate_limit_retry_after);
+            let retryable = match &result {
+                // Other server errors are only retried under a total retry budget
+                Ok(response) => {
+                    is_transient_status(response.status())
+                        || retry_after.is_some()
+                        || (response.status().is_server_error() && self.retry_budget.is_capped())
+                }
+                Err(e) => e.is_timeout() || e.is_connect(),
+            };
+            let (result, retryable) = match result {
+                Ok(response) if !retryable && self.inspects_error_body(&response) => {
+                    let status = response.status();
+                    let text = response.text().await.unwrap_or_default();
+                    let retryable = self.is_retryable_error(&text);
+                    (Err(ReasonerError::upstream_status(status, text)), retryable)
+                }
+                result => (result.map_err(ReasonerError::from), retryable),
+            };
+            if retryable && let Some(next_attempt) = self.next_attempt(attempt) {
+                attempt = next_attempt;
+                let delay = match retry_after {
+                    Some(retry_after) => retry_after.min(self.max_retry_after),
+                    None => backoff_delay(self.retry_base_delay, attempt),
+                };
+                log::warn!(
+                    "retrying upstream failure in {delay:?}, attempt {attempt} of {}",
+                    self.max_retries
+                );
+                tokio::time::sleep(delay).await;
+                continue;
+            }
+            break result?;
+        };
+
+        if is_html_response(&response) {
+            return Err(html_error(response).await);
+        }
+
+        if !response.status().is_success() {
+            return Err(status_error(response).await);
         }
 
         let content_type: mime::Mime = response.headers()[reqwest::header::CONTENT_TYPE]
             .to_str()?
             .parse()?;
-        if content_type.essence_str() != expected_content_type.essence_str() {
-            return Err(ReasonerError::ParseError(format!(
+        if !self.accepts_content_type(&content_type, &expected_content_type) {
+            return Err(ReasonerError::parse(format!(
                 "content-type: {content_type}, expected: {expected_content_type}"
             )));
         }
 
+        if expected_content_type == mime::APPLICATION_JSON
+            && self.request_template != RequestTemplate::OpenAI
+        {
+            return self.adapt_json_response(response).await;
+        }
+
         Ok(response)
     }
 }
+
+/// Classifies a non-success response by its status: 429 as a rate limit, other
+/// 4xx as a client error, and everything else as a retryable upstream failure.
+async fn status_error(response: Response) -> ReasonerError {
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    ReasonerError::upstream_status(status, text)
+}
+
+fn is_transient_status(status: reqwest::StatusCode) -> bool {
+    matches!(
+        status,
+        reqwest::StatusCode::BAD_GATEWAY
+            | reqwest::StatusCode::SERVICE_UNAVAILABLE
+            | reqwest::StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+fn rate_limit_retry_after(response: &Response) -> Option<Duration> {
+    if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    let value = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?;
+    parse_retry_after(value, SystemTime::now())
+}
+
+/// `Retry-After` holds either delay seconds or an HTTP date to wait until.
+fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let retry_at = httpdate::parse_http_date(value).ok()?;
+    Some(retry_at.duration_since(now).unwrap_or_default())
+}
+
+/// Doubles the base delay with every attempt and adds up to as much again in
+/// jitter, so clients retrying together do not hit the upstream in lockstep.
+fn backoff_delay(base_delay: Duration, attempt: u32) -> Duration {
+    let delay = base_delay.saturating_mul(1 << attempt.saturating_sub(1).min(16));
+    let jitter_ms = match delay.as_millis() as u64 {
+        0 => 0,
+        delay_ms => RandomState::new().hash_one(attempt) % (delay_ms + 1),
+    };
+    delay + Duration::from_millis(jitter_ms)
+}
+
+fn is_html_response(response: &Response) -> bool {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<mime::Mime>().ok())
+        .is_some_and(|content_type| content_type.essence_str() == mime::TEXT_HTML.essence_str())
+}
+
+async fn html_error(response: Response) -> ReasonerError {
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let (snippet, _) = truncate_to_chars(&text, consts::HTML_ERROR_SNIPPET_CHARS);
+
+    ReasonerError::api(format!(
+        "error: status {status}, upstream returned an HTML error page: {snippet}"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay_grows_exponentially_with_jitter() {
+        let base_delay = Duration::from_millis(100);
+        for (attempt, expected_ms) in [(1, 100), (2, 200), (3, 400)] {
+            let delay = backoff_delay(base_delay, attempt);
+            assert!(delay >= Duration::from_millis(expected_ms));
+            assert!(delay <= Duration::from_millis(expected_ms * 2));
+        }
+        assert_eq!(backoff_delay(Duration::ZERO, 3), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = httpdate::parse_http_date("Wed, 21 Oct 2015 07:28:00 GMT").unwrap();
+
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_completions_url_normalizes_slashes() {
+        for (api_url, completions_path, expected) in [
+            (
+                "http://localhost/v1",
+                None,
+                "http://localhost/v1/chat/completions",
+            ),
+            (
+                "http://localhost/v1/",
+                None,
+                "http://localhost/v1/chat/completions",
+            ),
+            (
+                "http://localhost/",
+                Some("openai/v1/chat/completions"),
+                "http://localhost/openai/v1/chat/completions",
+            ),
+            (
+                "http://localhost",
+                Some("/openai/v1/chat/completions"),
+                "http://localhost/openai/v1/chat/completions",
+            ),
+        ] {
+            let model_config = ModelConfig {
+                api_url: api_url.to_string(),
+                completions_path: completions_path.map(str::to_string),
+                ..Default::default()
+            };
+            let client = LLMClient::new(reqwest::Client::new(), &model_config);
+            assert_eq!(client.completions_url(), expected);
+        }
+    }
+
+    #[test]
+    fn test_models_url_normalizes_slashes() {
+        for api_url in ["http://localhost/v1", "http://localhost/v1/"] {
+            let model_config = ModelConfig {
+                api_url: api_url.to_string(),
+                ..Default::default()
+            };
+            let client = LLMClient::new(reqwest::Client::new(), &model_config);
+            assert_eq!(
+                client.upstream_url(consts::MODELS_PATH),
+                "http://localhost/v1/models"
+            );
+        }
+    }
+
+    fn mock_response(status: u16, body: &str) -> Response {
+        http::Response::builder()
+            .status(status)
+            .body(body.to_string())
+            .unwrap()
+            .into()
+    }
+
+    #[tokio::test]
+    async fn test_status_error_maps_status_classes() {
+        let error = status_error(mock_response(429, "slow down")).await;
+        assert!(matches!(
+            &error,
+            ReasonerError::RateLimitError { status: 429, body, .. } if body == "slow down"
+        ));
+        assert!(!error.is_retryable());
+
+        for status in [400, 401, 404, 422] {
+            let error = status_error(mock_response(status, "bad request")).await;
+            assert!(matches!(error, ReasonerError::ClientError { status: s, .. } if s == status));
+            assert!(!error.is_retryable());
+        }
+
+        for status in [500, 502, 503] {
+            let error = status_error(mock_response(status, "upstream down")).await;
+            assert!(matches!(
+                error,
+                ReasonerError::ApiError { status: Some(s), .. } if s == status
+            ));
+            assert!(error.is_retryable());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeouts_map_to_network_error() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let _silent = tokio::spawn(async move {
+            let _connection = listener.accept().await;
+            std::future::pending::<()>().await;
+        });
+
+        let error = reqwest::Client::new()
+            .get(format!("http://{address}"))
+            .timeout(Duration::from_millis(50))
+            .send()
+            .await
+            .unwrap_err();
+
+        let error = ReasonerError::from(error);
+        assert!(matches!(error, ReasonerError::NetworkError { .. }));
+        assert!(error.is_retryable());
+    }
+
+    #[test]
+    fn test_is_transient_status() {
+        for status in [502, 503, 504] {
+            assert!(is_transient_status(
+                reqwest::StatusCode::from_u16(status).unwrap()
+            ));
+        }
+        for status in [400, 429, 500] {
+            assert!(!is_transient_status(
+                reqwest::StatusCode::from_u16(status).unwrap()
+            ));
+        }
+    }
+}
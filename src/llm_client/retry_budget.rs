@@ -0,0 +1,51 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Retries left for a whole completion, shared by every upstream call made for
+/// it. Unlimited when no `max_total_retries` is configured.
+#[derive(Clone, Default)]
+pub struct RetryBudget(Option<Arc<AtomicU32>>);
+
+impl RetryBudget {
+    pub fn new(max_total_retries: Option<u32>) -> Self {
+        Self(max_total_retries.map(|retries| Arc::new(AtomicU32::new(retries))))
+    }
+
+    pub(crate) fn is_capped(&self) -> bool {
+        self.0.is_some()
+    }
+
+    pub(crate) fn take(&self) -> bool {
+        self.0.as_ref().is_none_or(|remaining| {
+            remaining
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |remaining| {
+                    remaining.checked_sub(1)
+                })
+                .is_ok()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_budget_is_shared_between_clones() {
+        let budget = RetryBudget::new(Some(2));
+        let shared = budget.clone();
+
+        assert!(budget.take());
+        assert!(shared.take());
+        assert!(!budget.take());
+        assert!(!shared.take());
+    }
+
+    #[test]
+    fn test_uncapped_retry_budget_never_runs_out() {
+        let budget = RetryBudget::new(None);
+
+        assert!(!budget.is_capped());
+        assert!((0..10).all(|_| budget.take()));
+    }
+}
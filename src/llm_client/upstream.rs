@@ -0,0 +1,167 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use actix_web::mime;
+use actix_web::web::Bytes;
+use async_trait::async_trait;
+
+use super::{LLMClient, LLMClientTrait};
+use crate::errors::ReasonerError;
+use crate::models::request;
+use crate::models::response_direct::ChatCompletion;
+use crate::models::response_stream::ChatCompletionChunk;
+
+/// Transport-agnostic backend the reasoning service orchestrates both phases against.
+#[async_trait]
+pub trait Upstream: Send + Sync {
+    async fn complete(
+        &self,
+        request: request::ChatCompletionCreate,
+    ) -> Result<ChatCompletion, ReasonerError>;
+
+    async fn stream(
+        &self,
+        request: request::ChatCompletionCreate,
+    ) -> Result<Box<dyn UpstreamStream>, ReasonerError>;
+}
+
+/// Raw server-sent event bytes of a streamed completion, `None` once exhausted.
+#[async_trait]
+pub trait UpstreamStream: Send {
+    async fn next_bytes(&mut self) -> Result<Option<Bytes>, ReasonerError>;
+}
+
+#[async_trait]
+impl Upstream for LLMClient {
+    async fn complete(
+        &self,
+        request: request::ChatCompletionCreate,
+    ) -> Result<ChatCompletion, ReasonerError> {
+        let response = self
+            .request_chat_completion(request, mime::APPLICATION_JSON)
+            .await?;
+        Ok(response.json::<ChatCompletion>().await?)
+    }
+
+    async fn stream(
+        &self,
+        request: request::ChatCompletionCreate,
+    ) -> Result<Box<dyn UpstreamStream>, ReasonerError> {
+        let response = self
+            .request_chat_completion(request, mime::TEXT_EVENT_STREAM)
+            .await?;
+        Ok(Box::new(response))
+    }
+}
+
+#[async_trait]
+impl UpstreamStream for reqwest::Response {
+    async fn next_bytes(&mut self) -> Result<Option<Bytes>, ReasonerError> {
+        self.chunk().await.map_err(|e| {
+            log::debug!("Error reading upstream events: {e}");
+            ReasonerError::network(e.to_string()).with_source(e)
+        })
+    }
+}
+
+/// Canned reply served by [`StubUpstream`].
+#[allow(dead_code)]
+pub enum StubReply {
+    Completion(ChatCompletion),
+    Chunks(Vec<ChatCompletionChunk>),
+    Error(ReasonerError),
+}
+
+/// In-process upstream replaying canned replies in order, for driving the
+/// service without a network. The last reply repeats once the queue runs dry.
+#[derive(Default)]
+pub struct StubUpstream {
+    replies: Mutex<VecDeque<StubReply>>,
+    requests: Mutex<Vec<request::ChatCompletionCreate>>,
+}
+
+#[allow(dead_code)]
+impl StubUpstream {
+    pub fn new(replies: Vec<StubReply>) -> Self {
+        Self {
+            replies: Mutex::new(replies.into()),
+            requests: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn requests(&self) -> Vec<request::ChatCompletionCreate> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn next_reply(
+        &self,
+        request: request::ChatCompletionCreate,
+    ) -> Result<StubReply, ReasonerError> {
+        self.requests.lock().unwrap().push(request);
+
+        let mut replies = self.replies.lock().unwrap();
+        let reply = if replies.len() > 1 {
+            replies.pop_front()
+        } else {
+            replies.front().map(StubReply::duplicate)
+        };
+        reply.ok_or_else(|| ReasonerError::api("error: stub upstream has no replies"))
+    }
+}
+
+impl StubReply {
+    fn duplicate(&self) -> Self {
+        match self {
+            StubReply::Completion(completion) => StubReply::Completion(completion.clone()),
+            StubReply::Chunks(chunks) => StubReply::Chunks(chunks.clone()),
+            StubReply::Error(error) => StubReply::Error(error.clone()),
+        }
+    }
+}
+
+#[async_trait]
+impl Upstream for StubUpstream {
+    async fn complete(
+        &self,
+        request: request::ChatCompletionCreate,
+    ) -> Result<ChatCompletion, ReasonerError> {
+        match self.next_reply(request)? {
+            StubReply::Completion(completion) => Ok(completion),
+            StubReply::Chunks(_) => Err(ReasonerError::api(
+                "error: stub upstream expected a streaming request",
+            )),
+            StubReply::Error(error) => Err(error),
+        }
+    }
+
+    async fn stream(
+        &self,
+        request: request::ChatCompletionCreate,
+    ) -> Result<Box<dyn UpstreamStream>, ReasonerError> {
+        match self.next_reply(request)? {
+            StubReply::Chunks(chunks) => {
+                let mut events = chunks
+                    .iter()
+                    .map(|chunk| Ok(format!("data: {}\n\n", serde_json::to_string(chunk)?)))
+                    .collect::<Result<VecDeque<String>, ReasonerError>>()?;
+                events.push_back(format!("data: {}\n\n", crate::consts::SSE_DONE_SENTINEL));
+                Ok(Box::new(StubStream { events }))
+            }
+            StubReply::Completion(_) => Err(ReasonerError::api(
+                "error: stub upstream expected a non-streaming request",
+            )),
+            StubReply::Error(error) => Err(error),
+        }
+    }
+}
+
+struct StubStream {
+    events: VecDeque<String>,
+}
+
+#[async_trait]
+impl UpstreamStream for StubStream {
+    async fn next_bytes(&mut self) -> Result<Option<Bytes>, ReasonerError> {
+        Ok(self.events.pop_front().map(Bytes::from))
+    }
+}
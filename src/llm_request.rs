@@ -1,76 +1,884 @@
+use std::time::Duration;
+
+use crate::budget::estimate_reasoning_budget;
 use crate::config;
 use crate::errors::ReasonerError;
 use crate::models::request;
+use crate::models::response_direct::ChatCompletion;
+use crate::models::{FinishReason, LogProbs};
 
 pub(crate) fn calculate_remaining_tokens(max_tokens: Option<i32>, reasoning_tokens: i32) -> i32 {
-    max_tokens.unwrap_or(crate::consts::DEFAULT_MAX_TOKENS) - reasoning_tokens
+    max_tokens
+        .unwrap_or(crate::consts::DEFAULT_MAX_TOKENS)
+        .saturating_sub(reasoning_tokens)
+}
+
+pub(crate) fn resolve_max_tokens(
+    request: &request::ChatCompletionCreate,
+    model_config: &config::ModelConfig,
+) -> Option<i32> {
+    request.max_tokens.or(model_config.default_max_tokens)
+}
+
+/// The request may override the configured budget. Reasoning is capped so that
+/// `min_answer_tokens` of the effective total always remain for the answer, and
+/// `reasoning_effort` can lower a budget set by the header but not raise it.
+pub(crate) fn resolve_reasoning_budget(
+    request: &request::ChatCompletionCreate,
+    model_config: &config::ModelConfig,
+) -> i32 {
+    let available = calculate_remaining_tokens(
+        resolve_max_tokens(request, model_config),
+        model_config.min_answer_tokens.max(0),
+    );
+    request
+        .reasoning_budget
+        .unwrap_or_else(|| {
+            let budget = estimate_reasoning_budget(&request.messages, model_config);
+            match request.reasoning_effort {
+                Some(effort) => scale_reasoning_budget(budget, effort, model_config),
+                None => budget,
+            }
+        })
+        .min(model_config.header_reasoning_budget.unwrap_or(i32::MAX))
+        .min(available.max(0))
+}
+
+fn scale_reasoning_budget(
+    budget: i32,
+    effort: request::ReasoningEffort,
+    model_config: &config::ModelConfig,
+) -> i32 {
+    let multipliers = &model_config.reasoning_effort_multipliers;
+    let multiplier = match effort {
+        request::ReasoningEffort::Low => multipliers.low,
+        request::ReasoningEffort::Medium => multipliers.medium,
+        request::ReasoningEffort::High => multipliers.high,
+    };
+    (budget as f64 * multiplier.max(0.0))
+        .round()
+        .min(crate::consts::MAX_REASONING_BUDGET as f64) as i32
+}
+
+/// Phrase appended to reasoning cut off by its budget before the answer is requested.
+pub(crate) fn resolve_reasoning_cutoff_stub(model_config: &config::ModelConfig) -> &str {
+    model_config
+        .reasoning_cutoff_stub
+        .as_deref()
+        .unwrap_or(crate::consts::REASONING_CUTOFF_STUB)
 }
 
-pub(crate) fn build_reasoning_request(
+/// Delimiters the upstream model wraps its reasoning in, set by the delimiter style
+/// (`<think>`/`</think>` by default) unless overridden.
+pub(crate) fn resolve_think_tags(model_config: &config::ModelConfig) -> (&str, &str) {
+    let (default_start, default_end) = match model_config.reasoning_delimiter_style {
+        config::ReasoningDelimiterStyle::XmlTags => {
+            (crate::consts::THINK_START, crate::consts::THINK_END)
+        }
+        config::ReasoningDelimiterStyle::MarkdownFence => (
+            crate::consts::FENCE_THINK_START,
+            crate::consts::FENCE_THINK_END,
+        ),
+    };
+    (
+        model_config.think_start.as_deref().unwrap_or(default_start),
+        model_config.think_end.as_deref().unwrap_or(default_end),
+    )
+}
+
+/// Delimiters to scrub from answer content; a bare closing fence also ends
+/// ordinary code blocks, so it never counts as leaked.
+pub(crate) fn resolve_leaked_think_tags(model_config: &config::ModelConfig) -> Vec<String> {
+    let (think_start, think_end) = resolve_think_tags(model_config);
+    [think_start, think_end]
+        .into_iter()
+        .filter(|tag| *tag != crate::consts::FENCE_THINK_END)
+        .map(str::to_string)
+        .collect()
+}
+
+pub fn build_reasoning_request(
     request: request::ChatCompletionCreate,
     model_config: &config::ModelConfig,
 ) -> request::ChatCompletionCreate {
-    let mut reasoning_request: request::ChatCompletionCreate = request.clone();
+    let reasoning_budget = resolve_reasoning_budget(&request, model_config);
+    let (think_start, think_end) = resolve_think_tags(model_config);
+    let mut reasoning_request: request::ChatCompletionCreate = request;
     reasoning_request.model = model_config.model_name.to_string();
+    if let Some(augment) = &model_config.reasoning_prompt_augment {
+        augment_last_user_message(&mut reasoning_request.messages, augment);
+    }
 
-    let message_assistant = request::MessageAssistant {
-        reasoning_content: None,
-        content: Some(crate::consts::THINK_START.to_string()),
-        tool_calls: None,
-    };
-    reasoning_request
-        .messages
-        .push(request::Message::Assistant(message_assistant));
-    reasoning_request.stop = Some(vec![crate::consts::THINK_END.to_string()]);
-    reasoning_request.max_tokens = Some(model_config.reasoning_budget);
+    if model_config.prime_think_content.unwrap_or(true) {
+        let message_assistant = request::MessageAssistant {
+            reasoning_content: None,
+            content: Some(think_start.to_string()),
+            tool_calls: None,
+            refusal: None,
+        };
+        reasoning_request
+            .messages
+            .push(request::Message::Assistant(message_assistant));
+    }
+    let mut stop = vec![think_end.to_string()];
+    stop.extend(
+        reasoning_request
+            .stop
+            .take()
+            .into_iter()
+            .flatten()
+            .filter(|sequence| sequence != think_end),
+    );
+    cap_stop_sequences(&mut stop, resolve_max_stop_sequences(model_config).max(1));
+    reasoning_request.stop = Some(stop);
+    reasoning_request.max_tokens = Some(reasoning_budget);
+    let client_temperature = reasoning_request.temperature;
+    filter_reasoning_params(&mut reasoning_request, model_config);
+    if client_temperature.is_none() {
+        reasoning_request.temperature = model_config.reasoning_default_temperature;
+    }
+    reasoning_request.logprobs = None;
+    reasoning_request.top_logprobs = None;
 
     reasoning_request
 }
 
-pub(crate) fn build_answer_request(
+fn augment_last_user_message(messages: &mut [request::Message], augment: &str) {
+    let Some(message) = messages.iter_mut().rev().find_map(|message| match message {
+        request::Message::User(message) => Some(message),
+        _ => None,
+    }) else {
+        return;
+    };
+
+    match &mut message.content {
+        request::MessageContent::String(text) => {
+            text.push_str("\n\n");
+            text.push_str(augment);
+        }
+        request::MessageContent::Array(parts) => parts.push(request::MessageContentPart::Text {
+            text: augment.to_string(),
+        }),
+    }
+}
+
+/// Merges `prompt` into the client's leading system message, or adds one if there is none.
+fn apply_system_prompt(
+    messages: &mut Vec<request::Message>,
+    prompt: &str,
+    placement: config::SystemPromptPlacement,
+) {
+    let Some(request::Message::System(message)) = messages.first_mut() else {
+        messages.insert(
+            0,
+            request::Message::System(request::MessageSystemUser {
+                content: request::MessageContent::String(prompt.to_string()),
+            }),
+        );
+        return;
+    };
+
+    let part = request::MessageContentPart::Text {
+        text: prompt.to_string(),
+    };
+    match (&mut message.content, placement) {
+        (request::MessageContent::String(text), config::SystemPromptPlacement::Prepend) => {
+            *text = format!("{prompt}\n\n{text}");
+        }
+        (request::MessageContent::String(text), config::SystemPromptPlacement::Append) => {
+            text.push_str("\n\n");
+            text.push_str(prompt);
+        }
+        (request::MessageContent::Array(parts), config::SystemPromptPlacement::Prepend) => {
+            parts.insert(0, part);
+        }
+        (request::MessageContent::Array(parts), config::SystemPromptPlacement::Append) => {
+            parts.push(part);
+        }
+    }
+}
+
+fn resolve_max_stop_sequences(model_config: &config::ModelConfig) -> usize {
+    model_config
+        .max_stop_sequences
+        .unwrap_or(crate::consts::DEFAULT_MAX_STOP_SEQUENCES)
+}
+
+fn cap_stop_sequences(stop: &mut Vec<String>, max_stop_sequences: usize) {
+    if stop.len() > max_stop_sequences {
+        let dropped = stop.split_off(max_stop_sequences);
+        log::warn!("Dropping stop sequences over the limit of {max_stop_sequences}: {dropped:?}");
+    }
+}
+
+fn filter_reasoning_params(
+    reasoning_request: &mut request::ChatCompletionCreate,
+    model_config: &config::ModelConfig,
+) {
+    let forwarded = model_config
+        .reasoning_param_filter
+        .as_deref()
+        .unwrap_or(crate::consts::DEFAULT_REASONING_PARAMS);
+    let forwards = |param| forwarded.contains(&param);
+
+    if !forwards(config::SamplingParam::Temperature) {
+        reasoning_request.temperature = None;
+    }
+    if !forwards(config::SamplingParam::TopP) {
+        reasoning_request.top_p = None;
+    }
+    if !forwards(config::SamplingParam::N) {
+        reasoning_request.n = None;
+    }
+    if !forwards(config::SamplingParam::BestOf) {
+        reasoning_request.best_of = None;
+    }
+    if !forwards(config::SamplingParam::Seed) {
+        reasoning_request.seed = None;
+    }
+    if !forwards(config::SamplingParam::PresencePenalty) {
+        reasoning_request.presence_penalty = None;
+    }
+    if !forwards(config::SamplingParam::FrequencyPenalty) {
+        reasoning_request.frequency_penalty = None;
+    }
+}
+
+pub(crate) fn build_reasoning_retry_request(
+    request: request::ChatCompletionCreate,
+    model_config: &config::ModelConfig,
+    reasoning_text: &str,
+    retry_budget: i32,
+) -> request::ChatCompletionCreate {
+    let (think_start, _) = resolve_think_tags(model_config);
+    let mut retry_request = build_reasoning_request(request, model_config);
+    if let Some(request::Message::Assistant(message_assistant)) = retry_request.messages.last_mut()
+    {
+        message_assistant.content = Some(format!("{}{}", think_start, reasoning_text));
+    }
+    retry_request.max_tokens = Some(retry_budget);
+
+    retry_request
+}
+
+pub(crate) fn resolve_reasoning_retry_budget(
+    model_config: &config::ModelConfig,
+    reasoning_tokens: i32,
+) -> Option<i32> {
+    match model_config.reasoning_budget_exceeded_behavior {
+        config::ReasoningBudgetExceededBehavior::RetryWithLargerBudget { max_budget } => {
+            Some(max_budget.saturating_sub(reasoning_tokens)).filter(|budget| *budget > 0)
+        }
+        _ => None,
+    }
+}
+
+pub fn build_answer_request(
     request: request::ChatCompletionCreate,
     model_config: &config::ModelConfig,
     reasoning_text: &str,
     max_tokens: i32,
 ) -> request::ChatCompletionCreate {
-    let mut answer_request: request::ChatCompletionCreate = request.clone();
+    let (think_start, think_end) = resolve_think_tags(model_config);
+    // Whitespace-only reasoning would otherwise leave blank lines inside the think block
+    let reasoning_text = if reasoning_text.trim().is_empty() {
+        ""
+    } else {
+        reasoning_text
+    };
+    let mut answer_request: request::ChatCompletionCreate = request;
     answer_request.model = model_config.model_name.to_string();
+    if let Some(prompt) = &model_config.answer_system_prompt {
+        apply_system_prompt(
+            &mut answer_request.messages,
+            prompt,
+            model_config.answer_system_prompt_placement,
+        );
+    }
 
     let message_assistant = request::MessageAssistant {
         reasoning_content: None,
-        content: Some(format!(
-            "{}{}{}",
-            crate::consts::THINK_START,
-            reasoning_text,
-            crate::consts::THINK_END,
-        )),
+        content: Some(format!("{}{}{}", think_start, reasoning_text, think_end)),
         tool_calls: None,
+        refusal: None,
     };
     answer_request
         .messages
         .push(request::Message::Assistant(message_assistant));
     answer_request.max_tokens = Some(max_tokens);
+    if answer_request.temperature.is_none() {
+        answer_request.temperature = model_config.answer_default_temperature;
+    }
+    if let Some(answer_stop) = &model_config.answer_stop {
+        answer_request
+            .stop
+            .get_or_insert_with(Vec::new)
+            .extend(answer_stop.iter().cloned());
+    }
+    if let Some(stop) = answer_request.stop.as_mut() {
+        cap_stop_sequences(stop, resolve_max_stop_sequences(model_config));
+    }
 
     answer_request
 }
 
+pub(crate) fn build_single_pass_request(
+    request: request::ChatCompletionCreate,
+    model_config: &config::ModelConfig,
+) -> request::ChatCompletionCreate {
+    let (think_start, _) = resolve_think_tags(model_config);
+    let mut single_pass_request: request::ChatCompletionCreate = request;
+    single_pass_request.model = model_config.model_name.to_string();
+
+    let message_assistant = request::MessageAssistant {
+        reasoning_content: None,
+        content: Some(think_start.to_string()),
+        tool_calls: None,
+        refusal: None,
+    };
+    single_pass_request
+        .messages
+        .push(request::Message::Assistant(message_assistant));
+
+    single_pass_request
+}
+
+/// Plain upstream request for when there is no reasoning budget to spend.
+pub(crate) fn build_passthrough_request(
+    request: request::ChatCompletionCreate,
+    model_config: &config::ModelConfig,
+) -> request::ChatCompletionCreate {
+    let mut passthrough_request: request::ChatCompletionCreate = request;
+    passthrough_request.model = model_config.model_name.to_string();
+    if passthrough_request.temperature.is_none() {
+        passthrough_request.temperature = model_config.answer_default_temperature;
+    }
+
+    passthrough_request
+}
+
+pub(crate) fn resolve_choice_count(request: &request::ChatCompletionCreate) -> i32 {
+    request.n.unwrap_or(1).max(1)
+}
+
+pub(crate) fn resolve_deterministic_id(
+    request: &request::ChatCompletionCreate,
+    model_config: &config::ModelConfig,
+) -> Option<String> {
+    if !model_config.deterministic_ids {
+        return None;
+    }
+    let seed = request.seed?;
+    let prompt = serde_json::to_string(&request.messages).ok()?;
+
+    let hash = stable_hash(&[
+        &seed.to_le_bytes(),
+        request.model.as_bytes(),
+        prompt.as_bytes(),
+    ]);
+    Some(format!("chatcmpl-{:016x}", hash))
+}
+
+pub(crate) fn resolve_reasoning_cache_key(
+    request: &request::ChatCompletionCreate,
+    model_config: &config::ModelConfig,
+    include_unseeded: bool,
+) -> Option<u64> {
+    if request.seed.is_none() && !include_unseeded {
+        return None;
+    }
+
+    let mut reasoning_request = build_reasoning_request(request.clone(), model_config);
+    reasoning_request.stream = None;
+    reasoning_request.stream_options = None;
+    let reasoning_request = serde_json::to_string(&reasoning_request).ok()?;
+
+    Some(stable_hash(&[reasoning_request.as_bytes()]))
+}
+
+/// Key for collapsing identical requests, covering the parts of the model
+/// config that per-request headers can override. The proxy-only request
+/// fields are not serialized with the body, so they are hashed on their own.
+pub(crate) fn resolve_single_flight_key(
+    request: &request::ChatCompletionCreate,
+    model_config: &config::ModelConfig,
+) -> Option<u64> {
+    let body = serde_json::to_string(request).ok()?;
+    let overrides = serde_json::to_string(&(
+        request.reasoning_budget,
+        request.reasoning_effort,
+        request.max_answer_chars,
+        request.single_pass,
+        request.timeout_secs,
+    ))
+    .ok()?;
+    Some(stable_hash(&[
+        body.as_bytes(),
+        overrides.as_bytes(),
+        model_config.model_name.as_bytes(),
+        model_config.api_url.as_bytes(),
+        &model_config.reasoning_budget.to_le_bytes(),
+        &model_config
+            .header_reasoning_budget
+            .unwrap_or(-1)
+            .to_le_bytes(),
+    ]))
+}
+
+pub(crate) fn redact_tool_arguments(
+    request: &request::ChatCompletionCreate,
+) -> request::ChatCompletionCreate {
+    let mut redacted = request.clone();
+    for message in redacted.messages.iter_mut() {
+        let request::Message::Assistant(message_assistant) = message else {
+            continue;
+        };
+        for tool_call in message_assistant.tool_calls.iter_mut().flatten() {
+            if let Some(arguments) = tool_call.pointer_mut("/function/arguments") {
+                let hash = stable_hash(&[arguments.to_string().as_bytes()]);
+                *arguments = serde_json::Value::String(format!("[redacted:{:016x}]", hash));
+            }
+        }
+    }
+    redacted
+}
+
+// FNV-1a keeps hashes stable across builds, unlike std's DefaultHasher
+fn stable_hash(parts: &[&[u8]]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in parts.iter().flat_map(|part| part.iter()) {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+pub(crate) fn resolve_request_timeout(request: &request::ChatCompletionCreate) -> Option<Duration> {
+    request
+        .timeout_secs
+        .map(|secs| Duration::from_secs(secs.min(crate::consts::MAX_REQUEST_TIMEOUT_SECS)))
+}
+
+/// Best-effort answer token count for a stream cut short by `max_answer_chars`.
+/// Upstream usage is either never read after the cutoff or covers text that was
+/// not forwarded, so each forwarded content delta counts as one token, capped by
+/// the upstream figure when one arrived.
+pub(crate) fn estimate_truncated_answer_tokens(forwarded_deltas: i32, upstream_tokens: i32) -> i32 {
+    if upstream_tokens > 0 {
+        forwarded_deltas.min(upstream_tokens)
+    } else {
+        forwarded_deltas
+    }
+}
+
+pub(crate) fn calculate_tokens_per_second(tokens: i32, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs > 0.0 {
+        tokens as f64 / secs
+    } else {
+        0.0
+    }
+}
+
+pub(crate) fn estimate_cost(
+    model_config: &config::ModelConfig,
+    prompt_tokens: i32,
+    completion_tokens: i32,
+) -> Option<f64> {
+    if model_config.input_price_per_1k.is_none() && model_config.output_price_per_1k.is_none() {
+        return None;
+    }
+
+    let input_cost = model_config.input_price_per_1k.unwrap_or(0.0) * prompt_tokens as f64;
+    let output_cost = model_config.output_price_per_1k.unwrap_or(0.0) * completion_tokens as f64;
+    Some((input_cost + output_cost) / 1000.0)
+}
+
+pub(crate) fn average_logprob(logprobs: Option<&LogProbs>) -> Option<f64> {
+    let tokens = logprobs?.content.as_ref()?;
+    if tokens.is_empty() {
+        return None;
+    }
+    let total: f64 = tokens.iter().map(|token| token.logprob as f64).sum();
+    Some(total / tokens.len() as f64)
+}
+
+// Answers without logprobs are never considered low confidence
+pub(crate) fn is_low_confidence(answer: &ChatCompletion, gate: &config::ConfidenceGate) -> bool {
+    answer.choices.iter().any(|choice| {
+        average_logprob(choice.logprobs.as_ref())
+            .is_some_and(|average| average < gate.min_avg_logprob)
+    })
+}
+
+pub(crate) fn resolve_single_pass(
+    request: &request::ChatCompletionCreate,
+    model_config: &config::ModelConfig,
+) -> bool {
+    request.single_pass.unwrap_or(model_config.single_pass)
+}
+
+/// Stream options sent upstream, `None` for providers that reject the field.
+/// Usage then has to be estimated from the streamed deltas.
+pub(crate) fn resolve_upstream_stream_options(
+    model_config: &config::ModelConfig,
+) -> Option<request::StreamOptions> {
+    model_config
+        .forward_stream_options
+        .unwrap_or(true)
+        .then_some(request::StreamOptions {
+            include_usage: Some(true),
+        })
+}
+
+pub(crate) fn strip_echoed_think_start<'a>(content: &'a str, think_start: &str) -> &'a str {
+    content
+        .trim_start()
+        .strip_prefix(think_start)
+        .unwrap_or(content)
+}
+
+/// Whether the reasoning response carries nothing but whitespace, with no tool
+/// calls or refusal that would explain the missing reasoning.
+pub(crate) fn is_blank_reasoning(
+    reasoning_response: &ChatCompletion,
+    model_config: &config::ModelConfig,
+) -> bool {
+    let (think_start, _) = resolve_think_tags(model_config);
+    reasoning_response.choices.first().is_some_and(|choice| {
+        let content = choice.message.content.as_deref().unwrap_or_default();
+        strip_echoed_think_start(content, think_start)
+            .trim()
+            .is_empty()
+            && choice
+                .message
+                .tool_calls
+                .as_ref()
+                .is_none_or(|tool_calls| tool_calls.is_empty())
+            && choice.message.refusal.is_none()
+    })
+}
+
+/// Whether the answer response has no content, tool calls or refusal at all.
+pub(crate) fn is_empty_answer(answer_response: &ChatCompletion) -> bool {
+    answer_response.choices.first().is_some_and(|choice| {
+        choice
+            .message
+            .content
+            .as_deref()
+            .is_none_or(|content| content.trim().is_empty())
+            && choice
+                .message
+                .tool_calls
+                .as_ref()
+                .is_none_or(|tool_calls| tool_calls.is_empty())
+            && choice.message.refusal.is_none()
+    })
+}
+
+/// Whether reasoning leaves a speculative answer standing: it is blank, or it
+/// already arrives at the content of every answer choice.
+pub(crate) fn reasoning_confirms_answer(
+    reasoning_text: &str,
+    answer_response: &ChatCompletion,
+) -> bool {
+    let normalize = |text: &str| {
+        text.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    };
+    let reasoning_text = normalize(reasoning_text);
+    if reasoning_text.is_empty() {
+        return true;
+    }
+    !answer_response.choices.is_empty()
+        && answer_response.choices.iter().all(|choice| {
+            let answer_text = normalize(choice.message.content.as_deref().unwrap_or_default());
+            choice.message.tool_calls.is_none()
+                && !answer_text.is_empty()
+                && reasoning_text.contains(&answer_text)
+        })
+}
+
+pub(crate) fn resolve_answer_stops(
+    request: &request::ChatCompletionCreate,
+    model_config: &config::ModelConfig,
+) -> Vec<String> {
+    request
+        .stop
+        .iter()
+        .chain(model_config.answer_stop.iter())
+        .flatten()
+        .filter(|stop| !stop.is_empty())
+        .cloned()
+        .collect()
+}
+
+/// Some providers leave the matched stop sequence at the tail of the answer.
+pub(crate) fn strip_trailing_stop<'a>(text: &'a str, stops: &[String]) -> &'a str {
+    stops
+        .iter()
+        .find_map(|stop| text.strip_suffix(stop.as_str()))
+        .unwrap_or(text)
+}
+
+/// Falls back to the final paragraph of the reasoning when the answer phase came back empty.
+pub(crate) fn synthesize_answer(
+    model_config: &config::ModelConfig,
+    reasoning_text: &str,
+    reasoning_finish_reason: FinishReason,
+) -> Option<String> {
+    if !model_config.synthesize_answer_from_reasoning
+        || reasoning_finish_reason == FinishReason::Length
+    {
+        return None;
+    }
+    reasoning_text
+        .rsplit("\n\n")
+        .map(str::trim)
+        .find(|paragraph| !paragraph.is_empty())
+        .map(str::to_string)
+}
+
+pub(crate) fn split_reasoning_and_answer(content: &str, think_end: &str) -> (String, String) {
+    match content.split_once(think_end) {
+        Some((reasoning, answer)) => (reasoning.trim().to_string(), answer.trim().to_string()),
+        None => (content.trim().to_string(), "".to_string()),
+    }
+}
+
+pub(crate) fn extract_client_reasoning(
+    request: &request::ChatCompletionCreate,
+    model_config: &config::ModelConfig,
+) -> Option<String> {
+    if !model_config.reuse_client_reasoning {
+        return None;
+    }
+    let Some(request::Message::User(message)) = request.messages.last() else {
+        return None;
+    };
+
+    let text = match &message.content {
+        request::MessageContent::String(text) => text.clone(),
+        request::MessageContent::Array(parts) => parts
+            .iter()
+            .filter_map(|part| match part {
+                request::MessageContentPart::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+
+    let (think_start, think_end) = resolve_think_tags(model_config);
+    let (_, after_start) = text.rsplit_once(think_start)?;
+    let (reasoning, _) = after_start.split_once(think_end)?;
+    let reasoning = reasoning.trim();
+    if reasoning.is_empty() {
+        return None;
+    }
+    Some(reasoning.to_string())
+}
+
 pub(crate) fn validate_chat_request(
     request: &request::ChatCompletionCreate,
 ) -> Result<(), ReasonerError> {
     if request.messages.is_empty() {
-        return Err(ReasonerError::ValidationError(
+        return Err(ReasonerError::validation(
             "error: empty messages".to_string(),
         ));
     }
     if let request::Message::Assistant(_) = request.messages.last().unwrap() {
-        return Err(ReasonerError::ValidationError(
+        return Err(ReasonerError::validation(
             "error: cannot process partial assistant response content in messages yet!".to_string(),
         ));
     }
+    if let Some(budget) = request.reasoning_budget
+        && !(0..=crate::consts::MAX_REASONING_BUDGET).contains(&budget)
+    {
+        return Err(ReasonerError::validation(format!(
+            "error: reasoning_budget {budget} is outside of the allowed range 0..={}",
+            crate::consts::MAX_REASONING_BUDGET
+        )));
+    }
+    Ok(())
+}
+
+fn decoded_data_uri_len(url: &str) -> usize {
+    let Some((metadata, payload)) = url
+        .strip_prefix("data:")
+        .and_then(|data| data.split_once(','))
+    else {
+        return 0;
+    };
+    if !metadata.ends_with(";base64") {
+        return payload.len();
+    }
+    payload.trim_end_matches('=').len() * 3 / 4
+}
+
+pub(crate) fn validate_image_size(
+    request: &request::ChatCompletionCreate,
+    max_decoded_image_bytes: Option<usize>,
+) -> Result<(), ReasonerError> {
+    let Some(max_decoded_image_bytes) = max_decoded_image_bytes else {
+        return Ok(());
+    };
+
+    let decoded_image_bytes: usize = request
+        .messages
+        .iter()
+        .filter_map(|message| match message {
+            request::Message::User(message) | request::Message::System(message) => {
+                Some(&message.content)
+            }
+            _ => None,
+        })
+        .filter_map(|content| match content {
+            request::MessageContent::Array(parts) => Some(parts),
+            request::MessageContent::String(_) => None,
+        })
+        .flatten()
+        .map(|part| match part {
+            request::MessageContentPart::ImageUrl { image_url } => {
+                decoded_data_uri_len(&image_url.url)
+            }
+            request::MessageContentPart::Text { .. } => 0,
+        })
+        .sum();
+    if decoded_image_bytes > max_decoded_image_bytes {
+        return Err(ReasonerError::validation(format!(
+            "error: decoded image size {decoded_image_bytes} bytes exceeds limit of {max_decoded_image_bytes} bytes"
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn drop_empty_assistant_messages(
+    mut request: request::ChatCompletionCreate,
+    model_config: &config::ModelConfig,
+) -> request::ChatCompletionCreate {
+    if model_config.drop_empty_assistant_messages {
+        request.messages.retain(|message| match message {
+            request::Message::Assistant(message_assistant) => {
+                let has_content = message_assistant
+                    .content
+                    .as_ref()
+                    .is_some_and(|content| !content.is_empty());
+                let has_tool_calls = message_assistant
+                    .tool_calls
+                    .as_ref()
+                    .is_some_and(|tool_calls| !tool_calls.is_empty());
+                has_content || has_tool_calls
+            }
+            _ => true,
+        });
+    }
+    request
+}
+
+/// Agent loops feed earlier answers back in, inlined reasoning included, so
+/// the history grows with every turn. Only the latest assistant turns keep it.
+pub(crate) fn strip_prior_reasoning(
+    mut request: request::ChatCompletionCreate,
+    model_config: &config::ModelConfig,
+) -> request::ChatCompletionCreate {
+    let Some(max_retained_turns) = model_config.max_retained_reasoning_turns else {
+        return request;
+    };
+    let (think_start, think_end) = resolve_think_tags(model_config);
+
+    request
+        .messages
+        .iter_mut()
+        .rev()
+        .filter_map(|message| match message {
+            request::Message::Assistant(message_assistant) => Some(message_assistant),
+            _ => None,
+        })
+        .skip(max_retained_turns)
+        .for_each(|message_assistant| {
+            message_assistant.reasoning_content = None;
+            if let Some(content) = &mut message_assistant.content {
+                *content = strip_think_blocks(content, think_start, think_end);
+            }
+        });
+    request
+}
+
+fn strip_think_blocks(content: &str, think_start: &str, think_end: &str) -> String {
+    let mut stripped = String::new();
+    let mut rest = content;
+    while let Some((before, after_start)) = rest.split_once(think_start) {
+        stripped.push_str(before);
+        rest = match after_start.split_once(think_end) {
+            Some((_, after_end)) => after_end.trim_start(),
+            None => "",
+        };
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+/// Removes stray think markers the provider echoed into answer content.
+pub(crate) fn strip_think_tags(content: &str, tags: &[String]) -> String {
+    tags.iter()
+        .fold(content.to_string(), |content, tag| content.replace(tag, ""))
+}
+
+pub(crate) fn validate_stream_usage(
+    request: &request::ChatCompletionCreate,
+    require_stream_usage: bool,
+) -> Result<(), ReasonerError> {
+    let include_usage = request
+        .stream_options
+        .as_ref()
+        .and_then(|stream_options| stream_options.include_usage)
+        .unwrap_or(false);
+    if require_stream_usage && request.stream.unwrap_or(false) && !include_usage {
+        return Err(ReasonerError::validation(
+            "error: streaming requests must set stream_options.include_usage".to_string(),
+        ));
+    }
     Ok(())
 }
 
+/// The answer phase produces the user-visible output, so its finish reason wins
+/// regardless of how reasoning ended. When the answer was never attempted,
+/// reasoning used up the token budget and the response is cut off by length.
+pub(crate) fn resolve_finish_reason(answer_finish_reason: Option<FinishReason>) -> FinishReason {
+    answer_finish_reason.unwrap_or(FinishReason::Length)
+}
+
+pub(crate) fn resolve_max_answer_chars(
+    request: &request::ChatCompletionCreate,
+    model_config: &config::ModelConfig,
+) -> Option<usize> {
+    request.max_answer_chars.or(model_config.max_answer_chars)
+}
+
+pub(crate) fn truncate_to_chars(text: &str, max_chars: usize) -> (&str, bool) {
+    match text.char_indices().nth(max_chars) {
+        Some((byte_index, _)) => (&text[..byte_index], true),
+        None => (text, false),
+    }
+}
+
+pub(crate) fn apply_max_answer_chars(
+    answer_text: String,
+    finish_reason: FinishReason,
+    max_answer_chars: Option<usize>,
+) -> (String, FinishReason) {
+    let Some(max_chars) = max_answer_chars else {
+        return (answer_text, finish_reason);
+    };
 
+    match truncate_to_chars(&answer_text, max_chars) {
+        (truncated, true) => (truncated.to_string(), FinishReason::Length),
+        (_, false) => (answer_text, finish_reason),
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -87,12 +895,7 @@ mod tests {
                 }),
             ],
             max_tokens: None,
-            stop: None,
-            stream: None,
-            stream_options: None,
-            tools: None,
-            tool_choice: None,
-            extra: Default::default(),
+            ..Default::default()
         };
 
         assert!(validate_chat_request(&request).is_ok());
@@ -104,18 +907,13 @@ mod tests {
             model: "test".to_string(),
             messages: vec![],
             max_tokens: None,
-            stop: None,
-            stream: None,
-            stream_options: None,
-            tools: None,
-            tool_choice: None,
-            extra: Default::default(),
+            ..Default::default()
         };
 
         let result = validate_chat_request(&request);
         assert!(result.is_err());
         match result.unwrap_err() {
-            ReasonerError::ValidationError(msg) => {
+            ReasonerError::ValidationError { message: msg, .. } => {
                 assert!(msg.contains("empty messages"));
             }
             _ => panic!("Expected ValidationError"),
@@ -134,21 +932,17 @@ mod tests {
                     reasoning_content: None,
                     content: Some("Hi".to_string()),
                     tool_calls: None,
+                    refusal: None,
                 }),
             ],
             max_tokens: None,
-            stop: None,
-            stream: None,
-            stream_options: None,
-            tools: None,
-            tool_choice: None,
-            extra: Default::default(),
+            ..Default::default()
         };
 
         let result = validate_chat_request(&request);
         assert!(result.is_err());
         match result.unwrap_err() {
-            ReasonerError::ValidationError(msg) => {
+            ReasonerError::ValidationError { message: msg, .. } => {
                 assert!(msg.contains("cannot process partial assistant"));
             }
             _ => panic!("Expected ValidationError"),
@@ -156,10 +950,222 @@ mod tests {
     }
 
     #[test]
-    fn test_calculate_remaining_tokens_with_max_tokens() {
-        let result = calculate_remaining_tokens(Some(1000), 200);
-        assert_eq!(result, 800);
-    }
+    fn test_validate_stream_usage() {
+        let mut request = request::ChatCompletionCreate {
+            model: "test".to_string(),
+            stream: Some(true),
+            ..Default::default()
+        };
+        assert!(validate_stream_usage(&request, false).is_ok());
+        assert!(matches!(
+            validate_stream_usage(&request, true),
+            Err(ReasonerError::ValidationError { .. })
+        ));
+
+        request.stream_options = Some(request::StreamOptions {
+            include_usage: Some(true),
+        });
+        assert!(validate_stream_usage(&request, true).is_ok());
+
+        request.stream = None;
+        request.stream_options = None;
+        assert!(validate_stream_usage(&request, true).is_ok());
+    }
+
+    #[test]
+    fn test_validate_image_size() {
+        let image = |url: &str| request::MessageContentPart::ImageUrl {
+            image_url: request::ImageUrl {
+                url: url.to_string(),
+                detail: None,
+            },
+        };
+        let request = request::ChatCompletionCreate {
+            model: "test".to_string(),
+            messages: vec![request::Message::User(MessageSystemUser {
+                content: MessageContent::Array(vec![
+                    request::MessageContentPart::Text {
+                        text: "Describe these".to_string(),
+                    },
+                    // "hello world" is 11 bytes decoded
+                    image("data:image/png;base64,aGVsbG8gd29ybGQ="),
+                    image("data:image/png;base64,aGVsbG8gd29ybGQ="),
+                    image("https://example.com/image.png"),
+                ]),
+            })],
+            ..Default::default()
+        };
+
+        assert!(validate_image_size(&request, None).is_ok());
+        assert!(validate_image_size(&request, Some(22)).is_ok());
+        assert!(matches!(
+            validate_image_size(&request, Some(21)),
+            Err(ReasonerError::ValidationError { .. })
+        ));
+    }
+
+    fn conversation_with_empty_assistant() -> request::ChatCompletionCreate {
+        let user = |text: &str| {
+            request::Message::User(MessageSystemUser {
+                content: MessageContent::String(text.to_string()),
+            })
+        };
+        let assistant = |content: Option<&str>, tool_calls: Option<Vec<serde_json::Value>>| {
+            request::Message::Assistant(MessageAssistant {
+                reasoning_content: None,
+                content: content.map(str::to_string),
+                tool_calls,
+                refusal: None,
+            })
+        };
+        request::ChatCompletionCreate {
+            model: "test".to_string(),
+            messages: vec![
+                user("Hello"),
+                assistant(None, None),
+                user("Are you there?"),
+                assistant(Some(""), Some(vec![serde_json::json!({"id": "call_1"})])),
+                user("And now?"),
+                assistant(Some("Yes"), None),
+                user("Good"),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_drop_empty_assistant_messages() {
+        let mut model_config = config::ModelConfig {
+            model_name: "upstream-model".to_string(),
+            reasoning_budget: 100,
+            ..Default::default()
+        };
+
+        let kept =
+            drop_empty_assistant_messages(conversation_with_empty_assistant(), &model_config);
+        assert_eq!(kept.messages.len(), 7);
+
+        model_config.drop_empty_assistant_messages = true;
+        let normalized =
+            drop_empty_assistant_messages(conversation_with_empty_assistant(), &model_config);
+        assert_eq!(normalized.messages.len(), 6);
+        assert!(matches!(normalized.messages[1], request::Message::User(_)));
+        match &normalized.messages[2] {
+            request::Message::Assistant(msg) => assert!(msg.tool_calls.is_some()),
+            _ => panic!("Expected Assistant message"),
+        }
+
+        let reasoning_request = build_reasoning_request(normalized, &model_config);
+        match reasoning_request.messages.last().unwrap() {
+            request::Message::Assistant(msg) => {
+                assert_eq!(msg.content, Some(crate::consts::THINK_START.to_string()));
+            }
+            _ => panic!("Expected Assistant message"),
+        }
+    }
+
+    fn assistant_content(request: &request::ChatCompletionCreate, index: usize) -> String {
+        match &request.messages[index] {
+            request::Message::Assistant(msg) => msg.content.clone().unwrap_or_default(),
+            _ => panic!("Expected Assistant message"),
+        }
+    }
+
+    #[test]
+    fn test_strip_prior_reasoning() {
+        let user = |text: &str| {
+            request::Message::User(MessageSystemUser {
+                content: MessageContent::String(text.to_string()),
+            })
+        };
+        let turn = |reasoning: &str, answer: &str| {
+            request::Message::Assistant(MessageAssistant::new(
+                reasoning.to_string(),
+                answer.to_string(),
+                None,
+                false,
+                (crate::consts::THINK_START, crate::consts::THINK_END),
+            ))
+        };
+        let conversation = request::ChatCompletionCreate {
+            model: "test".to_string(),
+            messages: vec![
+                user("First"),
+                turn("First thoughts", "First answer"),
+                user("Second"),
+                request::Message::Assistant(MessageAssistant {
+                    reasoning_content: Some("Native thoughts".to_string()),
+                    content: Some("Second answer".to_string()),
+                    tool_calls: None,
+                    refusal: None,
+                }),
+                user("Third"),
+                turn("Third thoughts", "Third answer"),
+                user("Fourth"),
+            ],
+            ..Default::default()
+        };
+        let mut model_config = config::ModelConfig {
+            model_name: "upstream-model".to_string(),
+            reasoning_budget: 100,
+            ..Default::default()
+        };
+
+        let untouched = strip_prior_reasoning(conversation.clone(), &model_config);
+        assert!(assistant_content(&untouched, 1).contains("First thoughts"));
+
+        model_config.max_retained_reasoning_turns = Some(1);
+        let stripped = strip_prior_reasoning(conversation.clone(), &model_config);
+        assert_eq!(stripped.messages.len(), 7);
+        assert_eq!(assistant_content(&stripped, 1), "First answer");
+        match &stripped.messages[3] {
+            request::Message::Assistant(msg) => {
+                assert_eq!(msg.reasoning_content, None);
+                assert_eq!(msg.content.as_deref(), Some("Second answer"));
+            }
+            _ => panic!("Expected Assistant message"),
+        }
+        assert!(assistant_content(&stripped, 5).contains("Third thoughts"));
+
+        model_config.max_retained_reasoning_turns = Some(0);
+        let stripped = strip_prior_reasoning(conversation, &model_config);
+        assert_eq!(assistant_content(&stripped, 5), "Third answer");
+    }
+
+    #[test]
+    fn test_strip_prior_reasoning_uses_configured_delimiters() {
+        let conversation = request::ChatCompletionCreate {
+            model: "test".to_string(),
+            messages: vec![
+                request::Message::Assistant(MessageAssistant {
+                    reasoning_content: None,
+                    content: Some("```thinking\nEarlier thoughts\n```\nEarlier answer".to_string()),
+                    tool_calls: None,
+                    refusal: None,
+                }),
+                request::Message::User(MessageSystemUser {
+                    content: MessageContent::String("Next".to_string()),
+                }),
+            ],
+            ..Default::default()
+        };
+        let model_config = config::ModelConfig {
+            model_name: "upstream-model".to_string(),
+            reasoning_budget: 100,
+            reasoning_delimiter_style: config::ReasoningDelimiterStyle::MarkdownFence,
+            max_retained_reasoning_turns: Some(0),
+            ..Default::default()
+        };
+
+        let stripped = strip_prior_reasoning(conversation, &model_config);
+        assert_eq!(assistant_content(&stripped, 0), "Earlier answer");
+    }
+
+    #[test]
+    fn test_calculate_remaining_tokens_with_max_tokens() {
+        let result = calculate_remaining_tokens(Some(1000), 200);
+        assert_eq!(result, 800);
+    }
 
     #[test]
     fn test_calculate_remaining_tokens_with_none_max_tokens() {
@@ -173,6 +1179,202 @@ mod tests {
         assert_eq!(result, -50);
     }
 
+    #[test]
+    fn test_calculate_remaining_tokens_saturates_at_bounds() {
+        assert_eq!(calculate_remaining_tokens(Some(i32::MIN), 1), i32::MIN);
+        assert_eq!(calculate_remaining_tokens(Some(i32::MAX), -1), i32::MAX);
+        assert_eq!(calculate_remaining_tokens(Some(i32::MAX), i32::MAX), 0);
+        assert_eq!(
+            calculate_remaining_tokens(None, crate::consts::MAX_REASONING_BUDGET),
+            crate::consts::DEFAULT_MAX_TOKENS - crate::consts::MAX_REASONING_BUDGET
+        );
+    }
+
+    #[test]
+    fn test_resolve_reasoning_budget_leaves_room_for_answer() {
+        for (max_tokens, default_max_tokens, min_answer_tokens, expected_budget) in [
+            (Some(1000), None, 0, 500),
+            (Some(1000), None, 700, 300),
+            (Some(100), None, 0, 100),
+            (Some(100), None, 200, 0),
+            (None, Some(800), 400, 400),
+            (None, None, 400, 500),
+        ] {
+            let request = request::ChatCompletionCreate {
+                max_tokens,
+                ..Default::default()
+            };
+            let model_config = config::ModelConfig {
+                reasoning_budget: 500,
+                default_max_tokens,
+                min_answer_tokens,
+                ..Default::default()
+            };
+
+            assert_eq!(
+                resolve_reasoning_budget(&request, &model_config),
+                expected_budget
+            );
+        }
+    }
+
+    #[test]
+    fn test_request_reasoning_budget_overrides_config() {
+        let model_config = config::ModelConfig {
+            reasoning_budget: 500,
+            ..Default::default()
+        };
+        for (reasoning_budget, max_tokens, expected_budget) in [
+            (None, Some(1000), 500),
+            (Some(200), Some(1000), 200),
+            (Some(800), Some(1000), 800),
+            (Some(800), Some(600), 600),
+            (Some(0), Some(1000), 0),
+        ] {
+            let request = request::ChatCompletionCreate {
+                max_tokens,
+                reasoning_budget,
+                ..Default::default()
+            };
+
+            assert_eq!(
+                resolve_reasoning_budget(&request, &model_config),
+                expected_budget
+            );
+            assert_eq!(
+                build_reasoning_request(request, &model_config).max_tokens,
+                Some(expected_budget)
+            );
+        }
+    }
+
+    #[test]
+    fn test_reasoning_effort_scales_config_budget() {
+        let model_config = config::ModelConfig {
+            reasoning_budget: 500,
+            ..Default::default()
+        };
+        for (reasoning_effort, expected_budget) in [
+            (None, 500),
+            (Some(request::ReasoningEffort::Low), 250),
+            (Some(request::ReasoningEffort::Medium), 500),
+            (Some(request::ReasoningEffort::High), 1000),
+        ] {
+            let request = request::ChatCompletionCreate {
+                max_tokens: Some(5000),
+                reasoning_effort,
+                ..Default::default()
+            };
+
+            assert_eq!(
+                build_reasoning_request(request, &model_config).max_tokens,
+                Some(expected_budget)
+            );
+        }
+    }
+
+    #[test]
+    fn test_reasoning_effort_uses_configured_multipliers() {
+        let model_config = config::ModelConfig {
+            reasoning_budget: 500,
+            reasoning_effort_multipliers: config::ReasoningEffortMultipliers {
+                low: 0.1,
+                medium: 1.5,
+                high: 3.0,
+            },
+            ..Default::default()
+        };
+        let request = request::ChatCompletionCreate {
+            max_tokens: Some(5000),
+            reasoning_effort: Some(request::ReasoningEffort::Low),
+            ..Default::default()
+        };
+
+        assert_eq!(resolve_reasoning_budget(&request, &model_config), 50);
+    }
+
+    #[test]
+    fn test_explicit_reasoning_budget_wins_over_reasoning_effort() {
+        let model_config = config::ModelConfig {
+            reasoning_budget: 500,
+            ..Default::default()
+        };
+        let request = request::ChatCompletionCreate {
+            max_tokens: Some(5000),
+            reasoning_budget: Some(300),
+            reasoning_effort: Some(request::ReasoningEffort::High),
+            ..Default::default()
+        };
+
+        assert_eq!(resolve_reasoning_budget(&request, &model_config), 300);
+    }
+
+    #[test]
+    fn test_reasoning_effort_stays_within_header_budget() {
+        let model_config = config::ModelConfig {
+            reasoning_budget: 500,
+            header_reasoning_budget: Some(200),
+            ..Default::default()
+        };
+        for (reasoning_effort, expected_budget) in [
+            (Some(request::ReasoningEffort::Low), 100),
+            (Some(request::ReasoningEffort::High), 200),
+        ] {
+            let request = request::ChatCompletionCreate {
+                max_tokens: Some(5000),
+                reasoning_effort,
+                ..Default::default()
+            };
+
+            assert_eq!(
+                resolve_reasoning_budget(&request, &model_config),
+                expected_budget
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_chat_request_reasoning_budget() {
+        let mut request = request::ChatCompletionCreate {
+            model: "test".to_string(),
+            messages: vec![request::Message::User(MessageSystemUser {
+                content: MessageContent::String("Hello".to_string()),
+            })],
+            ..Default::default()
+        };
+        for budget in [0, 1000, crate::consts::MAX_REASONING_BUDGET] {
+            request.reasoning_budget = Some(budget);
+            assert!(validate_chat_request(&request).is_ok(), "{budget}");
+        }
+        for budget in [-1, crate::consts::MAX_REASONING_BUDGET + 1, i32::MAX] {
+            request.reasoning_budget = Some(budget);
+            match validate_chat_request(&request) {
+                Err(ReasonerError::ValidationError { message: msg, .. }) => {
+                    assert!(msg.contains("reasoning_budget"));
+                }
+                other => panic!("Expected ValidationError for {budget}, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_omitted_max_tokens_uses_model_default_total() {
+        let request = request::ChatCompletionCreate::default();
+        let model_config = config::ModelConfig {
+            reasoning_budget: 500,
+            default_max_tokens: Some(800),
+            min_answer_tokens: 400,
+            ..Default::default()
+        };
+
+        let reasoning_request = build_reasoning_request(request.clone(), &model_config);
+        assert_eq!(reasoning_request.max_tokens, Some(400));
+
+        let max_tokens = resolve_max_tokens(&request, &model_config);
+        assert_eq!(max_tokens, Some(800));
+        assert_eq!(calculate_remaining_tokens(max_tokens, 400), 400);
+    }
+
     #[test]
     fn test_build_reasoning_request() {
         let original_request = request::ChatCompletionCreate {
@@ -183,12 +1385,7 @@ mod tests {
                 }),
             ],
             max_tokens: Some(1000),
-            stop: None,
-            stream: None,
-            stream_options: None,
-            tools: None,
-            tool_choice: None,
-            extra: Default::default(),
+            ..Default::default()
         };
 
         let model_config = config::ModelConfig {
@@ -196,7 +1393,7 @@ mod tests {
             api_url: "http://test.com".to_string(),
             api_key: "test-key".to_string(),
             reasoning_budget: 100,
-            extra: None,
+            ..Default::default()
         };
 
         let reasoning_request = build_reasoning_request(original_request, &model_config);
@@ -213,6 +1410,232 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_build_reasoning_request_without_think_priming() {
+        let original_request = request::ChatCompletionCreate {
+            messages: vec![request::Message::User(MessageSystemUser {
+                content: MessageContent::String("Hello".to_string()),
+            })],
+            ..Default::default()
+        };
+        let model_config = config::ModelConfig {
+            prime_think_content: Some(false),
+            ..Default::default()
+        };
+
+        let reasoning_request = build_reasoning_request(original_request, &model_config);
+
+        assert_eq!(reasoning_request.messages.len(), 1);
+        assert!(matches!(
+            reasoning_request.messages[0],
+            request::Message::User(_)
+        ));
+        assert_eq!(
+            reasoning_request.stop,
+            Some(vec![crate::consts::THINK_END.to_string()])
+        );
+    }
+
+    #[test]
+    fn test_requests_use_configured_think_tags() {
+        let model_config = config::ModelConfig {
+            think_start: Some("<thought>".to_string()),
+            think_end: Some("</thought>".to_string()),
+            ..Default::default()
+        };
+
+        let reasoning_request =
+            build_reasoning_request(request::ChatCompletionCreate::default(), &model_config);
+        assert_eq!(reasoning_request.stop, Some(vec!["</thought>".to_string()]));
+        match reasoning_request.messages.last() {
+            Some(request::Message::Assistant(msg)) => {
+                assert_eq!(msg.content.as_deref(), Some("<thought>"));
+            }
+            _ => panic!("Expected Assistant message"),
+        }
+
+        let answer_request = build_answer_request(
+            request::ChatCompletionCreate::default(),
+            &model_config,
+            "Hmm",
+            100,
+        );
+        match answer_request.messages.last() {
+            Some(request::Message::Assistant(msg)) => {
+                assert_eq!(msg.content.as_deref(), Some("<thought>Hmm</thought>"));
+            }
+            _ => panic!("Expected Assistant message"),
+        }
+    }
+
+    #[test]
+    fn test_requests_use_markdown_fence_delimiters() {
+        let model_config = config::ModelConfig {
+            reasoning_delimiter_style: config::ReasoningDelimiterStyle::MarkdownFence,
+            ..Default::default()
+        };
+
+        let reasoning_request =
+            build_reasoning_request(request::ChatCompletionCreate::default(), &model_config);
+        assert_eq!(reasoning_request.stop, Some(vec!["\n```".to_string()]));
+        match reasoning_request.messages.last() {
+            Some(request::Message::Assistant(msg)) => {
+                assert_eq!(msg.content.as_deref(), Some("```thinking\n"));
+            }
+            _ => panic!("Expected Assistant message"),
+        }
+
+        let answer_request = build_answer_request(
+            request::ChatCompletionCreate::default(),
+            &model_config,
+            "Hmm",
+            100,
+        );
+        match answer_request.messages.last() {
+            Some(request::Message::Assistant(msg)) => {
+                assert_eq!(msg.content.as_deref(), Some("```thinking\nHmm\n```"));
+            }
+            _ => panic!("Expected Assistant message"),
+        }
+    }
+
+    #[test]
+    fn test_markdown_fence_reasoning_extraction() {
+        let model_config = config::ModelConfig {
+            reasoning_delimiter_style: config::ReasoningDelimiterStyle::MarkdownFence,
+            ..Default::default()
+        };
+        let (think_start, think_end) = resolve_think_tags(&model_config);
+
+        assert_eq!(
+            strip_echoed_think_start("```thinking\nLet me think", think_start),
+            "Let me think"
+        );
+        assert_eq!(
+            split_reasoning_and_answer("Let me think\n```\n\nThe answer is 4.", think_end),
+            ("Let me think".to_string(), "The answer is 4.".to_string())
+        );
+        assert_eq!(
+            strip_think_tags(
+                "```thinking\nSee:\n```rust\nfn main() {}\n```",
+                &resolve_leaked_think_tags(&model_config)
+            ),
+            "See:\n```rust\nfn main() {}\n```"
+        );
+    }
+
+    #[test]
+    fn test_reasoning_request_caps_stop_sequences_keeping_think_end() {
+        let original_request = request::ChatCompletionCreate {
+            stop: Some((1..=5).map(|i| format!("STOP{i}")).collect()),
+            ..Default::default()
+        };
+        let model_config = config::ModelConfig::default();
+
+        let reasoning_request = build_reasoning_request(original_request, &model_config);
+
+        assert_eq!(
+            reasoning_request.stop,
+            Some(vec![
+                crate::consts::THINK_END.to_string(),
+                "STOP1".to_string(),
+                "STOP2".to_string(),
+                "STOP3".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_answer_request_caps_stop_sequences() {
+        let original_request = request::ChatCompletionCreate {
+            stop: Some(vec!["A".to_string(), "B".to_string()]),
+            ..Default::default()
+        };
+        let model_config = config::ModelConfig {
+            answer_stop: Some(vec!["C".to_string()]),
+            max_stop_sequences: Some(2),
+            ..Default::default()
+        };
+
+        let answer_request = build_answer_request(original_request, &model_config, "", 100);
+
+        assert_eq!(
+            answer_request.stop,
+            Some(vec!["A".to_string(), "B".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_request_builders_preserve_conversation() {
+        let original_request = request::ChatCompletionCreate {
+            model: "test".to_string(),
+            messages: vec![
+                request::Message::System(MessageSystemUser {
+                    content: MessageContent::String("Be brief".to_string()),
+                }),
+                request::Message::User(MessageSystemUser {
+                    content: MessageContent::Array(vec![
+                        request::MessageContentPart::Text {
+                            text: "What is this?".to_string(),
+                        },
+                        request::MessageContentPart::ImageUrl {
+                            image_url: request::ImageUrl {
+                                url: "data:image/png;base64,aGVsbG8=".to_string(),
+                                detail: None,
+                            },
+                        },
+                    ]),
+                }),
+                request::Message::Assistant(MessageAssistant {
+                    reasoning_content: None,
+                    content: Some("A greeting".to_string()),
+                    tool_calls: None,
+                    refusal: None,
+                }),
+                request::Message::User(MessageSystemUser {
+                    content: MessageContent::String("Thanks".to_string()),
+                }),
+            ],
+            max_tokens: Some(1000),
+            temperature: Some(0.5),
+            ..Default::default()
+        };
+        let model_config = config::ModelConfig {
+            model_name: "upstream-model".to_string(),
+            reasoning_budget: 100,
+            ..Default::default()
+        };
+        let original = serde_json::to_value(&original_request).unwrap();
+        let with_messages = |extra: serde_json::Value, overrides: serde_json::Value| {
+            let mut expected = original.clone();
+            expected["model"] = "upstream-model".into();
+            expected["messages"].as_array_mut().unwrap().push(extra);
+            for (key, value) in overrides.as_object().unwrap() {
+                expected[key] = value.clone();
+            }
+            expected
+        };
+
+        let reasoning_request = build_reasoning_request(original_request.clone(), &model_config);
+        assert_eq!(
+            serde_json::to_value(&reasoning_request).unwrap(),
+            with_messages(
+                serde_json::json!({"role": "assistant", "content": "<think>"}),
+                serde_json::json!({"stop": ["</think>"], "max_tokens": 100}),
+            )
+        );
+
+        let answer_request =
+            build_answer_request(original_request, &model_config, "Let me think", 500);
+        assert_eq!(
+            serde_json::to_value(&answer_request).unwrap(),
+            with_messages(
+                serde_json::json!({"role": "assistant", "content": "<think>Let me think</think>"}),
+                serde_json::json!({"max_tokens": 500}),
+            )
+        );
+    }
+
     #[test]
     fn test_build_answer_request() {
         let original_request = request::ChatCompletionCreate {
@@ -223,12 +1646,7 @@ mod tests {
                 }),
             ],
             max_tokens: Some(1000),
-            stop: None,
-            stream: None,
-            stream_options: None,
-            tools: None,
-            tool_choice: None,
-            extra: Default::default(),
+            ..Default::default()
         };
 
         let model_config = config::ModelConfig {
@@ -236,7 +1654,7 @@ mod tests {
             api_url: "http://test.com".to_string(),
             api_key: "test-key".to_string(),
             reasoning_budget: 100,
-            extra: None,
+            ..Default::default()
         };
 
         let reasoning_text = "Let me think about this";
@@ -257,5 +1675,857 @@ mod tests {
             }
             _ => panic!("Expected Assistant message"),
         }
+        assert_eq!(answer_request.stop, None);
+    }
+
+    #[test]
+    fn test_build_answer_request_collapses_whitespace_only_reasoning() {
+        let original_request = request::ChatCompletionCreate {
+            model: "test".to_string(),
+            messages: vec![request::Message::User(MessageSystemUser {
+                content: MessageContent::String("Hello".to_string()),
+            })],
+            ..Default::default()
+        };
+        let model_config = config::ModelConfig::default();
+
+        let answer_request = build_answer_request(original_request, &model_config, "\n \n\t", 500);
+
+        match &answer_request.messages[1] {
+            request::Message::Assistant(msg) => {
+                assert_eq!(msg.content.as_deref(), Some("<think></think>"));
+            }
+            _ => panic!("Expected Assistant message"),
+        }
+    }
+
+    #[test]
+    fn test_is_blank_reasoning() {
+        let reasoning_response = |message: serde_json::Value| -> ChatCompletion {
+            serde_json::from_value(serde_json::json!({
+                "id": "chatcmpl-test",
+                "object": "chat.completion",
+                "created": 0,
+                "model": "test",
+                "choices": [{"index": 0, "message": message, "finish_reason": "stop"}],
+                "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2}
+            }))
+            .unwrap()
+        };
+        let model_config = config::ModelConfig::default();
+
+        assert!(is_blank_reasoning(
+            &reasoning_response(serde_json::json!({"content": "\n\n  "})),
+            &model_config
+        ));
+        assert!(is_blank_reasoning(
+            &reasoning_response(serde_json::json!({"content": "<think>\n"})),
+            &model_config
+        ));
+        assert!(!is_blank_reasoning(
+            &reasoning_response(serde_json::json!({"content": "Let me think"})),
+            &model_config
+        ));
+        assert!(!is_blank_reasoning(
+            &reasoning_response(serde_json::json!({"content": " ", "refusal": "No"})),
+            &model_config
+        ));
+    }
+
+    #[test]
+    fn test_is_empty_answer() {
+        let answer_response = |message: serde_json::Value| -> ChatCompletion {
+            serde_json::from_value(serde_json::json!({
+                "id": "chatcmpl-test",
+                "object": "chat.completion",
+                "created": 0,
+                "model": "test",
+                "choices": [{"index": 0, "message": message, "finish_reason": "stop"}],
+                "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2}
+            }))
+            .unwrap()
+        };
+
+        assert!(is_empty_answer(&answer_response(serde_json::json!({}))));
+        assert!(is_empty_answer(&answer_response(
+            serde_json::json!({"content": " \n"})
+        )));
+        assert!(!is_empty_answer(&answer_response(
+            serde_json::json!({"content": "Hello"})
+        )));
+        assert!(!is_empty_answer(&answer_response(serde_json::json!({
+            "content": null,
+            "tool_calls": [{
+                "id": "call_1",
+                "type": "function",
+                "function": {"name": "lookup", "arguments": "{}"}
+            }]
+        }))));
+    }
+
+    #[test]
+    fn test_reasoning_confirms_answer() {
+        let answer_response: ChatCompletion = serde_json::from_value(serde_json::json!({
+            "id": "chatcmpl-test",
+            "object": "chat.completion",
+            "created": 0,
+            "model": "test",
+            "choices": [{"index": 0, "message": {"content": "The answer is 42."}, "finish_reason": "stop"}],
+            "usage": {"prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2}
+        }))
+        .unwrap();
+
+        assert!(reasoning_confirms_answer(" \n", &answer_response));
+        assert!(reasoning_confirms_answer(
+            "Six times seven, so\nthe answer  is 42.",
+            &answer_response
+        ));
+        assert!(!reasoning_confirms_answer(
+            "Six times nine, so the answer is 54.",
+            &answer_response
+        ));
+    }
+
+    #[test]
+    fn test_reasoning_prompt_augment_applies_to_reasoning_phase_only() {
+        let original_request = request::ChatCompletionCreate {
+            model: "test".to_string(),
+            messages: vec![
+                request::Message::User(MessageSystemUser {
+                    content: MessageContent::String("First".to_string()),
+                }),
+                request::Message::Assistant(MessageAssistant {
+                    reasoning_content: None,
+                    content: Some("Reply".to_string()),
+                    tool_calls: None,
+                    refusal: None,
+                }),
+                request::Message::User(MessageSystemUser {
+                    content: MessageContent::String("What is 2+2?".to_string()),
+                }),
+            ],
+            ..Default::default()
+        };
+        let model_config = config::ModelConfig {
+            reasoning_budget: 100,
+            reasoning_prompt_augment: Some("Let's think step by step.".to_string()),
+            ..Default::default()
+        };
+        let user_text = |request: &request::ChatCompletionCreate, index: usize| match &request
+            .messages[index]
+        {
+            request::Message::User(MessageSystemUser {
+                content: MessageContent::String(text),
+            }) => text.clone(),
+            _ => panic!("Expected User message"),
+        };
+
+        let reasoning_request = build_reasoning_request(original_request.clone(), &model_config);
+        assert_eq!(user_text(&reasoning_request, 0), "First");
+        assert_eq!(
+            user_text(&reasoning_request, 2),
+            "What is 2+2?\n\nLet's think step by step."
+        );
+
+        let retry_request =
+            build_reasoning_retry_request(original_request.clone(), &model_config, "Hmm", 50);
+        assert!(user_text(&retry_request, 2).ends_with("Let's think step by step."));
+
+        let answer_request = build_answer_request(original_request, &model_config, "Four", 100);
+        assert_eq!(user_text(&answer_request, 2), "What is 2+2?");
+    }
+
+    #[test]
+    fn test_build_answer_request_appends_answer_stop() {
+        let original_request = request::ChatCompletionCreate {
+            model: "test".to_string(),
+            messages: vec![request::Message::User(MessageSystemUser {
+                content: MessageContent::String("Hello".to_string()),
+            })],
+            stop: Some(vec!["END".to_string()]),
+            ..Default::default()
+        };
+        let model_config = config::ModelConfig {
+            answer_stop: Some(vec![crate::consts::THINK_START.to_string()]),
+            ..Default::default()
+        };
+
+        let answer_request = build_answer_request(original_request, &model_config, "", 500);
+
+        assert_eq!(
+            answer_request.stop,
+            Some(vec![
+                "END".to_string(),
+                crate::consts::THINK_START.to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_truncate_to_chars_within_limit() {
+        assert_eq!(truncate_to_chars("Hello", 5), ("Hello", false));
+        assert_eq!(truncate_to_chars("Hello", 10), ("Hello", false));
+    }
+
+    #[test]
+    fn test_truncate_to_chars_exceeding_limit() {
+        assert_eq!(truncate_to_chars("Hello, world", 5), ("Hello", true));
+        assert_eq!(truncate_to_chars("Hello", 0), ("", true));
+    }
+
+    #[test]
+    fn test_truncate_to_chars_multi_byte() {
+        assert_eq!(truncate_to_chars("héllo wörld", 7), ("héllo w", true));
+        assert_eq!(truncate_to_chars("日本語テキスト", 3), ("日本語", true));
+        assert_eq!(truncate_to_chars("🦀🦀🦀", 2), ("🦀🦀", true));
+    }
+
+    #[test]
+    fn test_apply_max_answer_chars_truncates_and_sets_length() {
+        let (text, finish_reason) =
+            apply_max_answer_chars("Привет, мир".to_string(), FinishReason::Stop, Some(6));
+        assert_eq!(text, "Привет");
+        assert_eq!(finish_reason, FinishReason::Length);
+    }
+
+    #[test]
+    fn test_apply_max_answer_chars_keeps_short_answer() {
+        let (text, finish_reason) =
+            apply_max_answer_chars("Hi".to_string(), FinishReason::ToolCalls, Some(6));
+        assert_eq!(text, "Hi");
+        assert_eq!(finish_reason, FinishReason::ToolCalls);
+
+        let (text, finish_reason) =
+            apply_max_answer_chars("Hello, world".to_string(), FinishReason::Stop, None);
+        assert_eq!(text, "Hello, world");
+        assert_eq!(finish_reason, FinishReason::Stop);
+    }
+
+    #[test]
+    fn test_resolve_max_answer_chars_request_overrides_model() {
+        let mut request = request::ChatCompletionCreate::default();
+        let mut model_config = config::ModelConfig::default();
+        assert_eq!(resolve_max_answer_chars(&request, &model_config), None);
+
+        model_config.max_answer_chars = Some(100);
+        assert_eq!(resolve_max_answer_chars(&request, &model_config), Some(100));
+
+        request.max_answer_chars = Some(10);
+        assert_eq!(resolve_max_answer_chars(&request, &model_config), Some(10));
+    }
+
+    #[test]
+    fn test_logprobs_requested_only_for_answer_phase() {
+        let original_request = request::ChatCompletionCreate {
+            model: "test".to_string(),
+            messages: vec![request::Message::User(MessageSystemUser {
+                content: MessageContent::String("Hello".to_string()),
+            })],
+            logprobs: Some(true),
+            top_logprobs: Some(3),
+            ..Default::default()
+        };
+        let model_config = config::ModelConfig {
+            model_name: "upstream-model".to_string(),
+            reasoning_budget: 100,
+            ..Default::default()
+        };
+
+        let reasoning_request = build_reasoning_request(original_request.clone(), &model_config);
+        assert_eq!(reasoning_request.logprobs, None);
+        assert_eq!(reasoning_request.top_logprobs, None);
+
+        let answer_request = build_answer_request(original_request, &model_config, "", 500);
+        assert_eq!(answer_request.logprobs, Some(true));
+        assert_eq!(answer_request.top_logprobs, Some(3));
+    }
+
+    #[test]
+    fn test_choice_count_requested_only_for_answer_phase() {
+        let original_request = request::ChatCompletionCreate {
+            model: "test".to_string(),
+            messages: vec![request::Message::User(MessageSystemUser {
+                content: MessageContent::String("Hello".to_string()),
+            })],
+            n: Some(3),
+            ..Default::default()
+        };
+        let model_config = config::ModelConfig {
+            model_name: "upstream-model".to_string(),
+            reasoning_budget: 100,
+            ..Default::default()
+        };
+
+        assert_eq!(resolve_choice_count(&original_request), 3);
+
+        let reasoning_request = build_reasoning_request(original_request.clone(), &model_config);
+        assert_eq!(reasoning_request.n, None);
+        assert_eq!(resolve_choice_count(&reasoning_request), 1);
+
+        let answer_request = build_answer_request(original_request, &model_config, "", 500);
+        assert_eq!(answer_request.n, Some(3));
+    }
+
+    #[test]
+    fn test_reasoning_param_filter() {
+        let original_request = request::ChatCompletionCreate {
+            model: "test".to_string(),
+            messages: vec![request::Message::User(MessageSystemUser {
+                content: MessageContent::String("Hello".to_string()),
+            })],
+            temperature: Some(0.7),
+            top_p: Some(0.9),
+            n: Some(2),
+            best_of: Some(4),
+            seed: Some(42),
+            ..Default::default()
+        };
+        let mut model_config = config::ModelConfig {
+            model_name: "upstream-model".to_string(),
+            reasoning_budget: 100,
+            ..Default::default()
+        };
+
+        let reasoning_request = build_reasoning_request(original_request.clone(), &model_config);
+        assert_eq!(reasoning_request.temperature, Some(0.7));
+        assert_eq!(reasoning_request.top_p, Some(0.9));
+        assert_eq!(reasoning_request.n, None);
+        assert_eq!(reasoning_request.best_of, None);
+        assert_eq!(reasoning_request.seed, Some(42));
+
+        model_config.reasoning_param_filter = Some(vec![config::SamplingParam::N]);
+        let reasoning_request = build_reasoning_request(original_request.clone(), &model_config);
+        assert_eq!(reasoning_request.temperature, None);
+        assert_eq!(reasoning_request.top_p, None);
+        assert_eq!(reasoning_request.n, Some(2));
+        assert_eq!(reasoning_request.best_of, None);
+        assert_eq!(reasoning_request.seed, None);
+
+        let answer_request = build_answer_request(original_request, &model_config, "", 500);
+        assert_eq!(answer_request.temperature, Some(0.7));
+        assert_eq!(answer_request.best_of, Some(4));
+    }
+
+    #[test]
+    fn test_sampling_params_forwarded_to_both_phases() {
+        let original_request: request::ChatCompletionCreate =
+            serde_json::from_value(serde_json::json!({
+                "model": "test",
+                "messages": [{"role": "user", "content": "Hello"}],
+                "temperature": 0.2,
+                "top_p": 0.8,
+                "seed": 7,
+                "presence_penalty": 0.5,
+                "frequency_penalty": -0.5,
+            }))
+            .unwrap();
+        let model_config = config::ModelConfig::default();
+
+        let reasoning_request = build_reasoning_request(original_request.clone(), &model_config);
+        let answer_request = build_answer_request(original_request, &model_config, "", 500);
+        for phase_request in [reasoning_request, answer_request] {
+            assert_eq!(phase_request.temperature, Some(0.2));
+            assert_eq!(phase_request.top_p, Some(0.8));
+            assert_eq!(phase_request.seed, Some(7));
+            assert_eq!(phase_request.presence_penalty, Some(0.5));
+            assert_eq!(phase_request.frequency_penalty, Some(-0.5));
+        }
+    }
+
+    #[test]
+    fn test_default_temperatures_apply_per_phase() {
+        let mut original_request = request::ChatCompletionCreate {
+            model: "test".to_string(),
+            messages: vec![request::Message::User(MessageSystemUser {
+                content: MessageContent::String("Hello".to_string()),
+            })],
+            ..Default::default()
+        };
+        let model_config = config::ModelConfig {
+            reasoning_default_temperature: Some(0.6),
+            answer_default_temperature: Some(0.2),
+            ..Default::default()
+        };
+
+        let reasoning_request = build_reasoning_request(original_request.clone(), &model_config);
+        assert_eq!(reasoning_request.temperature, Some(0.6));
+        let answer_request = build_answer_request(original_request.clone(), &model_config, "", 500);
+        assert_eq!(answer_request.temperature, Some(0.2));
+
+        original_request.temperature = Some(1.0);
+        let reasoning_request = build_reasoning_request(original_request.clone(), &model_config);
+        assert_eq!(reasoning_request.temperature, Some(1.0));
+        let answer_request = build_answer_request(original_request, &model_config, "", 500);
+        assert_eq!(answer_request.temperature, Some(1.0));
+    }
+
+    #[test]
+    fn test_resolve_reasoning_retry_budget() {
+        let mut model_config = config::ModelConfig {
+            reasoning_budget: 100,
+            ..Default::default()
+        };
+        assert_eq!(resolve_reasoning_retry_budget(&model_config, 100), None);
+
+        model_config.reasoning_budget_exceeded_behavior =
+            config::ReasoningBudgetExceededBehavior::RetryWithLargerBudget { max_budget: 250 };
+        assert_eq!(
+            resolve_reasoning_retry_budget(&model_config, 100),
+            Some(150)
+        );
+        assert_eq!(resolve_reasoning_retry_budget(&model_config, 250), None);
+        assert_eq!(resolve_reasoning_retry_budget(&model_config, 300), None);
+    }
+
+    #[test]
+    fn test_resolve_deterministic_id() {
+        let mut original_request = request::ChatCompletionCreate {
+            model: "test".to_string(),
+            messages: vec![request::Message::User(MessageSystemUser {
+                content: MessageContent::String("Hello".to_string()),
+            })],
+            seed: Some(42),
+            ..Default::default()
+        };
+        let mut model_config = config::ModelConfig::default();
+        assert_eq!(
+            resolve_deterministic_id(&original_request, &model_config),
+            None
+        );
+
+        model_config.deterministic_ids = true;
+        let id = resolve_deterministic_id(&original_request, &model_config).unwrap();
+        assert!(id.starts_with("chatcmpl-"));
+        assert_eq!(
+            resolve_deterministic_id(&original_request, &model_config),
+            Some(id.clone())
+        );
+
+        original_request.seed = Some(43);
+        assert_ne!(
+            resolve_deterministic_id(&original_request, &model_config),
+            Some(id)
+        );
+
+        original_request.seed = None;
+        assert_eq!(
+            resolve_deterministic_id(&original_request, &model_config),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resolve_reasoning_cache_key() {
+        let mut original_request = request::ChatCompletionCreate {
+            model: "test".to_string(),
+            messages: vec![request::Message::User(MessageSystemUser {
+                content: MessageContent::String("Hello".to_string()),
+            })],
+            ..Default::default()
+        };
+        let mut model_config = config::ModelConfig {
+            model_name: "upstream-model".to_string(),
+            reasoning_budget: 100,
+            ..Default::default()
+        };
+        assert_eq!(
+            resolve_reasoning_cache_key(&original_request, &model_config, false),
+            None
+        );
+
+        original_request.seed = Some(7);
+        let key = resolve_reasoning_cache_key(&original_request, &model_config, false).unwrap();
+
+        let mut streaming_request = original_request.clone();
+        streaming_request.stream = Some(true);
+        streaming_request.max_tokens = Some(500);
+        assert_eq!(
+            resolve_reasoning_cache_key(&streaming_request, &model_config, false),
+            Some(key)
+        );
+
+        model_config.reasoning_budget = 200;
+        assert_ne!(
+            resolve_reasoning_cache_key(&original_request, &model_config, false),
+            Some(key)
+        );
+
+        original_request.seed = None;
+        assert!(resolve_reasoning_cache_key(&original_request, &model_config, true).is_some());
+    }
+
+    #[test]
+    fn test_resolve_finish_reason_prefers_answer_phase() {
+        assert_eq!(
+            resolve_finish_reason(Some(FinishReason::Stop)),
+            FinishReason::Stop
+        );
+        assert_eq!(
+            resolve_finish_reason(Some(FinishReason::Length)),
+            FinishReason::Length
+        );
+        assert_eq!(
+            resolve_finish_reason(Some(FinishReason::ToolCalls)),
+            FinishReason::ToolCalls
+        );
+        assert_eq!(resolve_finish_reason(None), FinishReason::Length);
+    }
+
+    #[test]
+    fn test_extract_client_reasoning() {
+        let user_message =
+            |content: MessageContent| request::Message::User(MessageSystemUser { content });
+        let mut original_request = request::ChatCompletionCreate {
+            model: "test".to_string(),
+            messages: vec![user_message(MessageContent::String(
+                "<think>old</think> Use this: <think> 2 + 2 = 4 </think> Answer?".to_string(),
+            ))],
+            ..Default::default()
+        };
+        let mut model_config = config::ModelConfig::default();
+        assert_eq!(
+            extract_client_reasoning(&original_request, &model_config),
+            None
+        );
+
+        model_config.reuse_client_reasoning = true;
+        assert_eq!(
+            extract_client_reasoning(&original_request, &model_config),
+            Some("2 + 2 = 4".to_string())
+        );
+
+        original_request.messages = vec![user_message(MessageContent::Array(vec![
+            request::MessageContentPart::Text {
+                text: "<think>from parts".to_string(),
+            },
+            request::MessageContentPart::Text {
+                text: "</think>".to_string(),
+            },
+        ]))];
+        assert_eq!(
+            extract_client_reasoning(&original_request, &model_config),
+            Some("from parts".to_string())
+        );
+
+        for content in ["<think>unfinished", "<think> </think>", "no reasoning"] {
+            original_request.messages =
+                vec![user_message(MessageContent::String(content.to_string()))];
+            assert_eq!(
+                extract_client_reasoning(&original_request, &model_config),
+                None
+            );
+        }
+
+        original_request.messages = vec![
+            user_message(MessageContent::String("<think>done</think>".to_string())),
+            request::Message::System(MessageSystemUser {
+                content: MessageContent::String("Be brief".to_string()),
+            }),
+        ];
+        assert_eq!(
+            extract_client_reasoning(&original_request, &model_config),
+            None
+        );
+
+        model_config.think_start = Some("<reasoning>".to_string());
+        model_config.think_end = Some("</reasoning>".to_string());
+        original_request.messages = vec![user_message(MessageContent::String(
+            "<think>ignored</think> <reasoning> custom </reasoning>".to_string(),
+        ))];
+        assert_eq!(
+            extract_client_reasoning(&original_request, &model_config),
+            Some("custom".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolve_request_timeout_clamps_to_server_max() {
+        let mut original_request = request::ChatCompletionCreate::default();
+        assert_eq!(resolve_request_timeout(&original_request), None);
+
+        original_request.timeout_secs = Some(5);
+        assert_eq!(
+            resolve_request_timeout(&original_request),
+            Some(Duration::from_secs(5))
+        );
+
+        original_request.timeout_secs = Some(u64::MAX);
+        assert_eq!(
+            resolve_request_timeout(&original_request),
+            Some(Duration::from_secs(crate::consts::MAX_REQUEST_TIMEOUT_SECS))
+        );
+    }
+
+    #[test]
+    fn test_redact_tool_arguments() {
+        let original_request = request::ChatCompletionCreate {
+            model: "test".to_string(),
+            messages: vec![
+                request::Message::User(MessageSystemUser {
+                    content: MessageContent::String("Find my orders".to_string()),
+                }),
+                request::Message::Assistant(MessageAssistant {
+                    reasoning_content: None,
+                    content: None,
+                    tool_calls: Some(vec![serde_json::json!({
+                        "id": "call_1",
+                        "type": "function",
+                        "function": {
+                            "name": "run_sql",
+                            "arguments": "{\"query\": \"SELECT * FROM orders WHERE email = 'a@b.c'\"}"
+                        }
+                    })]),
+                    refusal: None,
+                }),
+            ],
+            ..Default::default()
+        };
+
+        let redacted = redact_tool_arguments(&original_request);
+        let logged = format!("{:?}", redacted);
+        assert!(!logged.contains("a@b.c"));
+        assert!(logged.contains("run_sql"));
+        assert!(logged.contains("[redacted:"));
+        assert_eq!(
+            format!("{:?}", redact_tool_arguments(&original_request)),
+            logged
+        );
+
+        let original = serde_json::to_string(&original_request).unwrap();
+        assert!(original.contains("a@b.c"));
+    }
+
+    #[test]
+    fn test_calculate_tokens_per_second() {
+        assert_eq!(
+            calculate_tokens_per_second(50, Duration::from_millis(500)),
+            100.0
+        );
+        assert_eq!(calculate_tokens_per_second(10, Duration::ZERO), 0.0);
+    }
+
+    #[test]
+    fn test_average_logprob() {
+        let logprobs = |values: &[f32]| LogProbs {
+            content: Some(
+                values
+                    .iter()
+                    .map(|logprob| crate::models::TokenLogProb {
+                        token: "t".to_string(),
+                        logprob: *logprob,
+                        bytes: None,
+                        top_logprobs: None,
+                    })
+                    .collect(),
+            ),
+        };
+
+        assert_eq!(average_logprob(None), None);
+        assert_eq!(average_logprob(Some(&logprobs(&[]))), None);
+        assert_eq!(average_logprob(Some(&logprobs(&[-0.5, -1.5]))), Some(-1.0));
+    }
+
+    #[test]
+    fn test_estimate_cost() {
+        let mut model_config = config::ModelConfig::default();
+        assert_eq!(estimate_cost(&model_config, 1000, 1000), None);
+
+        model_config.input_price_per_1k = Some(0.5);
+        model_config.output_price_per_1k = Some(2.0);
+        assert_eq!(estimate_cost(&model_config, 2000, 500), Some(2.0));
+
+        model_config.input_price_per_1k = None;
+        assert_eq!(estimate_cost(&model_config, 2000, 500), Some(1.0));
+    }
+
+    #[test]
+    fn test_build_single_pass_request_primes_without_stop() {
+        let original_request = request::ChatCompletionCreate {
+            model: "test".to_string(),
+            messages: vec![request::Message::User(MessageSystemUser {
+                content: MessageContent::String("Hello".to_string()),
+            })],
+            max_tokens: Some(1000),
+            ..Default::default()
+        };
+        let model_config = config::ModelConfig {
+            model_name: "upstream-model".to_string(),
+            reasoning_budget: 100,
+            ..Default::default()
+        };
+
+        let single_pass_request = build_single_pass_request(original_request, &model_config);
+
+        assert_eq!(single_pass_request.model, "upstream-model");
+        assert_eq!(single_pass_request.max_tokens, Some(1000));
+        assert_eq!(single_pass_request.stop, None);
+        match &single_pass_request.messages[1] {
+            request::Message::Assistant(msg) => {
+                assert_eq!(msg.content, Some(crate::consts::THINK_START.to_string()));
+            }
+            _ => panic!("Expected Assistant message"),
+        }
+    }
+
+    #[test]
+    fn test_answer_system_prompt_placement() {
+        let system = |text: &str| {
+            request::Message::System(MessageSystemUser {
+                content: MessageContent::String(text.to_string()),
+            })
+        };
+        let user = request::Message::User(MessageSystemUser {
+            content: MessageContent::String("Hello".to_string()),
+        });
+        let system_text = |request: &request::ChatCompletionCreate| match &request.messages[0] {
+            request::Message::System(MessageSystemUser {
+                content: MessageContent::String(text),
+            }) => text.clone(),
+            _ => panic!("Expected System message"),
+        };
+
+        let mut model_config = config::ModelConfig {
+            answer_system_prompt: Some("Be concise.".to_string()),
+            ..Default::default()
+        };
+        let with_client_system = request::ChatCompletionCreate {
+            messages: vec![system("You are a pirate."), user.clone()],
+            ..Default::default()
+        };
+
+        let prepended = build_answer_request(with_client_system.clone(), &model_config, "", 100);
+        assert_eq!(system_text(&prepended), "Be concise.\n\nYou are a pirate.");
+
+        model_config.answer_system_prompt_placement = config::SystemPromptPlacement::Append;
+        let appended = build_answer_request(with_client_system, &model_config, "", 100);
+        assert_eq!(system_text(&appended), "You are a pirate.\n\nBe concise.");
+
+        let without_client_system = request::ChatCompletionCreate {
+            messages: vec![user],
+            ..Default::default()
+        };
+        let inserted = build_answer_request(without_client_system, &model_config, "", 100);
+        assert_eq!(system_text(&inserted), "Be concise.");
+        assert_eq!(inserted.messages.len(), 3);
+    }
+
+    #[test]
+    fn test_resolve_reasoning_cutoff_stub() {
+        let mut model_config = config::ModelConfig::default();
+        assert_eq!(
+            resolve_reasoning_cutoff_stub(&model_config),
+            crate::consts::REASONING_CUTOFF_STUB
+        );
+
+        model_config.reasoning_cutoff_stub = Some("Time to answer.".to_string());
+        assert_eq!(
+            resolve_reasoning_cutoff_stub(&model_config),
+            "Time to answer."
+        );
+    }
+
+    #[test]
+    fn test_build_passthrough_request_keeps_messages_untouched() {
+        let original_request = request::ChatCompletionCreate {
+            model: "test".to_string(),
+            messages: vec![request::Message::User(MessageSystemUser {
+                content: MessageContent::String("Hello".to_string()),
+            })],
+            max_tokens: Some(1000),
+            ..Default::default()
+        };
+        let model_config = config::ModelConfig {
+            model_name: "upstream-model".to_string(),
+            answer_default_temperature: Some(0.3),
+            ..Default::default()
+        };
+
+        let passthrough_request = build_passthrough_request(original_request, &model_config);
+
+        assert_eq!(passthrough_request.model, "upstream-model");
+        assert_eq!(passthrough_request.max_tokens, Some(1000));
+        assert_eq!(passthrough_request.temperature, Some(0.3));
+        assert_eq!(passthrough_request.stop, None);
+        assert_eq!(passthrough_request.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_strip_echoed_think_start() {
+        assert_eq!(
+            strip_echoed_think_start("<think>Let me think", crate::consts::THINK_START),
+            "Let me think"
+        );
+        assert_eq!(
+            strip_echoed_think_start("\n<think>Let me think", crate::consts::THINK_START),
+            "Let me think"
+        );
+        assert_eq!(
+            strip_echoed_think_start("Let me think", crate::consts::THINK_START),
+            "Let me think"
+        );
+        assert_eq!(
+            strip_echoed_think_start("Think about <think> tags", crate::consts::THINK_START),
+            "Think about <think> tags"
+        );
+    }
+
+    #[test]
+    fn test_estimate_truncated_answer_tokens() {
+        assert_eq!(estimate_truncated_answer_tokens(2, 0), 2);
+        assert_eq!(estimate_truncated_answer_tokens(2, 8), 2);
+        assert_eq!(estimate_truncated_answer_tokens(5, 3), 3);
+    }
+
+    #[test]
+    fn test_strip_trailing_stop() {
+        let stops = vec!["<END>".to_string(), "###".to_string()];
+
+        assert_eq!(strip_trailing_stop("It's 4.<END>", &stops), "It's 4.");
+        assert_eq!(strip_trailing_stop("It's 4.###", &stops), "It's 4.");
+        assert_eq!(
+            strip_trailing_stop("<END> It's 4.", &stops),
+            "<END> It's 4."
+        );
+        assert_eq!(strip_trailing_stop("It's 4.<END>", &[]), "It's 4.<END>");
+    }
+
+    #[test]
+    fn test_synthesize_answer_uses_reasoning_tail() {
+        let mut model_config = config::ModelConfig::default();
+        let reasoning = "2 + 2 is basic arithmetic.\n\nSo the answer is 4.\n\n";
+
+        assert_eq!(
+            synthesize_answer(&model_config, reasoning, FinishReason::Stop),
+            None
+        );
+
+        model_config.synthesize_answer_from_reasoning = true;
+        assert_eq!(
+            synthesize_answer(&model_config, reasoning, FinishReason::Stop),
+            Some("So the answer is 4.".to_string())
+        );
+        assert_eq!(
+            synthesize_answer(&model_config, reasoning, FinishReason::Length),
+            None
+        );
+        assert_eq!(
+            synthesize_answer(&model_config, " \n\n ", FinishReason::Stop),
+            None
+        );
+    }
+
+    #[test]
+    fn test_split_reasoning_and_answer() {
+        assert_eq!(
+            split_reasoning_and_answer(
+                "\nHmm, let me see.\n</think>\n\nIt's 4.",
+                crate::consts::THINK_END
+            ),
+            ("Hmm, let me see.".to_string(), "It's 4.".to_string())
+        );
+        assert_eq!(
+            split_reasoning_and_answer("Still thinking", crate::consts::THINK_END),
+            ("Still thinking".to_string(), "".to_string())
+        );
     }
 }
@@ -0,0 +1,120 @@
+use std::future::Future;
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+use crate::consts;
+use crate::errors::ReasonerError;
+
+tokio::task_local! {
+    static REQUEST_LOG_LEVEL: LevelFilter;
+}
+
+/// Runs `future` with its log records let through down to `level`, on top of
+/// whatever the global filter already allows.
+pub async fn scope<F: Future>(level: Option<LevelFilter>, future: F) -> F::Output {
+    REQUEST_LOG_LEVEL
+        .scope(level.unwrap_or(LevelFilter::Off), future)
+        .await
+}
+
+/// Level elevated for the current request, to carry over into spawned tasks.
+pub fn current() -> Option<LevelFilter> {
+    REQUEST_LOG_LEVEL
+        .try_with(|level| *level)
+        .ok()
+        .filter(|level| *level != LevelFilter::Off)
+}
+
+pub fn parse_level(header: &str) -> Result<LevelFilter, ReasonerError> {
+    let header = header.trim();
+    header
+        .parse()
+        .ok()
+        .filter(|level| consts::REQUEST_LOG_LEVELS.contains(level))
+        .ok_or_else(|| {
+            ReasonerError::validation(format!("error: request log level not allowed: {header}"))
+        })
+}
+
+/// Logger that passes records the global `filter` accepts, plus records at or
+/// above the level elevated for the request being processed, on to `sink`.
+pub struct RequestScopedLogger<F, S> {
+    filter: F,
+    sink: S,
+}
+
+impl<F: Log, S: Log> RequestScopedLogger<F, S> {
+    pub fn new(filter: F, sink: S) -> Self {
+        Self { filter, sink }
+    }
+}
+
+impl<F: Log, S: Log> Log for RequestScopedLogger<F, S> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.filter.enabled(metadata) || current().is_some_and(|level| metadata.level() <= level)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            self.sink.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.sink.flush();
+    }
+}
+
+/// Installs the logger configured from the environment, falling back to
+/// `default_filter` when `RUST_LOG` is unset.
+pub fn init(default_filter: &str) {
+    let filter =
+        env_logger::Builder::from_env(env_logger::Env::new().default_filter_or(default_filter))
+            .build();
+    let max_level = filter.filter();
+    let sink = env_logger::Builder::new()
+        .filter_level(LevelFilter::Trace)
+        .build();
+
+    log::set_boxed_logger(Box::new(RequestScopedLogger::new(filter, sink)))
+        .expect("Failed to install logger");
+    log::set_max_level(max_level);
+}
+
+/// Raises the global max level so records elevated for a request are not
+/// discarded before they reach the logger.
+pub fn allow_request_levels() {
+    let max_request_level = consts::REQUEST_LOG_LEVELS
+        .iter()
+        .copied()
+        .max()
+        .unwrap_or(LevelFilter::Off);
+    log::set_max_level(log::max_level().max(max_request_level));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_level_allows_listed_levels_only() {
+        assert_eq!(parse_level("debug").unwrap(), LevelFilter::Debug);
+        assert_eq!(parse_level(" TRACE ").unwrap(), LevelFilter::Trace);
+        for header in ["info", "off", "verbose"] {
+            assert!(matches!(
+                parse_level(header),
+                Err(ReasonerError::ValidationError { .. })
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scope_sets_current_level() {
+        assert_eq!(current(), None);
+        assert_eq!(
+            scope(Some(LevelFilter::Debug), async { current() }).await,
+            Some(LevelFilter::Debug)
+        );
+        assert_eq!(scope(None, async { current() }).await, None);
+    }
+}
@@ -1,11 +1,14 @@
 mod app;
+mod budget;
 mod config;
 mod consts;
 mod errors;
 mod handlers;
 mod llm_client;
 mod llm_request;
+mod log_context;
 mod models;
+mod openapi;
 mod service;
 
 use std::sync::Arc;
@@ -16,10 +19,14 @@ use crate::app::create_app;
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv::dotenv().ok();
-    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
+    log_context::init("info");
     log::info!("Initializing Adaptive Reasoner service...");
 
     let model_config = config::load_config().expect("Failed to load config");
+    let bind_address = config::load_bind_address().map_err(std::io::Error::other)?;
+    if model_config.server.allow_request_log_level {
+        log_context::allow_request_levels();
+    }
 
     let http_client = reqwest::Client::builder()
         .connect_timeout(Duration::new(consts::CONNECT_TIMEOUT_SECS, 0))
@@ -27,12 +34,106 @@ async fn main() -> std::io::Result<()> {
         .build()
         .unwrap();
 
-    let reasoning_service = Arc::new(service::ReasoningService::new(http_client));
+    let reasoning_service = Arc::new(
+        service::ReasoningService::builder(http_client)
+            .max_concurrent_streams(model_config.server.max_concurrent_streams)
+            .reasoning_progress(model_config.server.stream_reasoning_progress)
+            .stream_diagnostics(model_config.server.stream_diagnostics)
+            .sse_done_sentinel(model_config.server.sse_done_sentinel.clone())
+            .allowed_upstream_hosts(model_config.server.allowed_upstream_hosts.clone())
+            .stream_idle_timeout(
+                model_config
+                    .server
+                    .stream_idle_timeout_secs
+                    .map(Duration::from_secs),
+            )
+            .max_stream_duration(
+                model_config
+                    .server
+                    .max_stream_duration_secs
+                    .map(Duration::from_secs),
+            )
+            .stream_keep_alive(
+                model_config
+                    .server
+                    .stream_keep_alive_secs
+                    .map(Duration::from_secs),
+            )
+            .reasoning_cache(
+                model_config.server.reasoning_cache_capacity,
+                Duration::from_secs(
+                    model_config
+                        .server
+                        .reasoning_cache_ttl_secs
+                        .unwrap_or(consts::DEFAULT_REASONING_CACHE_TTL_SECS),
+                ),
+                model_config.server.reasoning_cache_include_unseeded,
+            )
+            .single_flight(model_config.server.single_flight)
+            .fallback_models(model_config.models.clone())
+            .fresh_completion_ids(!model_config.server.preserve_upstream_ids)
+            .build(),
+    );
+    if model_config.server.discover_upstream_models {
+        reasoning_service
+            .discover_upstream_models(&model_config.models)
+            .await;
+    }
+    let shutdown_grace_period = Duration::from_secs(
+        model_config
+            .server
+            .shutdown_grace_period_secs
+            .unwrap_or(consts::DEFAULT_SHUTDOWN_GRACE_PERIOD_SECS),
+    );
     let config = Arc::new(model_config);
 
+    let shutdown_service = reasoning_service.clone();
     let app_factory = move || create_app(reasoning_service.clone(), config.clone());
 
-    let server = actix_web::HttpServer::new(app_factory);
+    let server = actix_web::HttpServer::new(app_factory)
+        .disable_signals()
+        .shutdown_timeout(shutdown_grace_period.as_secs())
+        .bind(bind_address)
+        .map_err(|e| {
+            std::io::Error::other(
+                errors::ReasonerError::config(format!("error: cannot bind to {bind_address}: {e}"))
+                    .with_source(e),
+            )
+        })?;
+    log::info!("Listening on {bind_address}");
 
-    server.bind(("0.0.0.0", consts::SERVER_PORT))?.run().await
+    let server = server.run();
+    let server_handle = server.handle();
+    actix_web::rt::spawn(async move {
+        shutdown_signal().await;
+        log::info!(
+            "Shutting down, draining {} active streams",
+            shutdown_service.active_streams()
+        );
+        server_handle.pause().await;
+        if !shutdown_service.drain_streams(shutdown_grace_period).await {
+            log::warn!(
+                "Shutdown grace period elapsed with {} streams still active",
+                shutdown_service.active_streams()
+            );
+        }
+        server_handle.stop(true).await;
+    });
+
+    server.await
+}
+
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        let mut terminate =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+                .expect("Failed to install SIGTERM handler");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
 }
@@ -4,6 +4,7 @@ pub mod response_direct;
 pub mod response_stream;
 
 use serde::{self, Deserialize, Serialize};
+use serde_json::Value;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "lowercase")]
@@ -26,9 +27,50 @@ pub struct Usage {
     pub prompt_tokens: i32,
     pub completion_tokens: i32,
     pub total_tokens: i32,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub prompt_tokens_details: Option<PromptTokensDetails>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub completion_tokens_details: Option<CompletionTokensDetails>,
+}
+
+/// Breakdown of completion tokens, reporting how many went into the reasoning phase.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct CompletionTokensDetails {
+    #[serde(default)]
+    pub reasoning_tokens: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct PromptTokensDetails {
+    #[serde(default)]
+    pub cached_tokens: i32,
+}
+
+impl PromptTokensDetails {
+    /// Sums the details of two upstream calls, staying absent if neither reported any.
+    pub(crate) fn combine(first: Option<&Self>, second: Option<&Self>) -> Option<Self> {
+        if first.is_none() && second.is_none() {
+            return None;
+        }
+        Some(Self {
+            cached_tokens: first.map_or(0, |details| details.cached_tokens)
+                + second.map_or(0, |details| details.cached_tokens),
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TokenLogProb {
+    pub token: String,
+    pub logprob: f32,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub bytes: Option<Vec<u8>>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub top_logprobs: Option<Vec<Value>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LogProbs {
-    pub tokens: Vec<f32>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub content: Option<Vec<TokenLogProb>>,
 }
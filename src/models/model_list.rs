@@ -1,4 +1,5 @@
 use serde::{self, Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "lowercase")]
@@ -23,4 +24,7 @@ pub struct Model {
     pub object: ObjectType,
     pub created: i64,
     pub owned_by: Owner,
+    /// Metadata the upstream reported for the model, when discovery is enabled.
+    #[serde(flatten, default)]
+    pub upstream: Map<String, Value>,
 }
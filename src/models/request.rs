@@ -37,38 +37,47 @@ pub struct MessageAssistant {
     pub content: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub tool_calls: Option<Vec<Value>>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub refusal: Option<String>,
 }
 
 impl MessageAssistant {
-    #[cfg(reasoning)]
+    /// Inlines the reasoning between the given tags unless it is emitted as
+    /// `reasoning_content`.
     pub(crate) fn new(
         reasoning_content: String,
         content: String,
         tool_calls: Option<Vec<Value>>,
+        emit_reasoning_content: bool,
+        (think_start, think_end): (&str, &str),
     ) -> MessageAssistant {
-        MessageAssistant {
-            reasoning_content: Some(reasoning_content),
-            content: Some(content),
-            tool_calls: tool_calls,
+        if emit_reasoning_content {
+            return MessageAssistant {
+                reasoning_content: Some(reasoning_content),
+                content: Some(content),
+                tool_calls,
+                refusal: None,
+            };
+        }
+        if reasoning_content.trim().is_empty() && content.is_empty() {
+            return MessageAssistant {
+                reasoning_content: None,
+                content: Some(content),
+                tool_calls,
+                refusal: None,
+            };
         }
-    }
-
-    #[cfg(not(reasoning))]
-    pub(crate) fn new(
-        reasoning_content: String,
-        content: String,
-        tool_calls: Option<Vec<serde_json::Value>>,
-    ) -> MessageAssistant {
         MessageAssistant {
             reasoning_content: None,
             content: Some(format!(
                 "{}\n{}\n{}\n{}",
-                crate::consts::THINK_START,
+                think_start,
                 reasoning_content.trim(),
-                crate::consts::THINK_END,
+                think_end,
                 content
             )),
-            tool_calls: tool_calls,
+            tool_calls,
+            refusal: None,
         }
     }
 }
@@ -88,13 +97,34 @@ pub enum Message {
     Tool(MessageTool),
 }
 
+pub fn normalize_message_roles(body: &mut Value) {
+    let Some(messages) = body.get_mut("messages").and_then(Value::as_array_mut) else {
+        return;
+    };
+
+    for message in messages {
+        let Some(role) = message.get_mut("role") else {
+            continue;
+        };
+        let Some(name) = role.as_str() else {
+            continue;
+        };
+        let normalized = match name.to_ascii_lowercase().as_str() {
+            "human" => "user".to_string(),
+            "ai" => "assistant".to_string(),
+            other => other.to_string(),
+        };
+        *role = Value::String(normalized);
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct StreamOptions {
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub include_usage: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct ChatCompletionCreate {
     pub model: String,
     pub messages: Vec<Message>,
@@ -110,10 +140,46 @@ pub struct ChatCompletionCreate {
     pub tools: Option<Vec<Value>>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub tool_choice: Option<ToolChoice>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub n: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub best_of: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub seed: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub presence_penalty: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub frequency_penalty: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub logprobs: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub top_logprobs: Option<i32>,
+    #[serde(skip_serializing, default)]
+    pub max_answer_chars: Option<usize>,
+    #[serde(skip_serializing, default)]
+    pub single_pass: Option<bool>,
+    #[serde(skip_serializing, default)]
+    pub timeout_secs: Option<u64>,
+    #[serde(skip_serializing, default)]
+    pub reasoning_budget: Option<i32>,
+    #[serde(skip_serializing, default)]
+    pub reasoning_effort: Option<ReasoningEffort>,
     #[serde(flatten, skip_deserializing, default)]
     pub extra: HashMap<String, Value>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReasoningEffort {
+    Low,
+    Medium,
+    High,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "snake_case")]
 pub enum ToolChoice {
@@ -121,3 +187,58 @@ pub enum ToolChoice {
     None,
     Required,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn deserialize_role(role: &str) -> Message {
+        let mut body = json!({
+            "model": "test",
+            "messages": [{"role": role, "content": "Hello"}]
+        });
+        normalize_message_roles(&mut body);
+        let request: ChatCompletionCreate = serde_json::from_value(body).unwrap();
+        request.messages.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn test_normalize_message_roles_aliases() {
+        for role in ["human", "Human", "HUMAN", "user", "User"] {
+            assert!(matches!(deserialize_role(role), Message::User(_)), "{role}");
+        }
+        for role in ["ai", "AI", "Assistant", "ASSISTANT"] {
+            assert!(
+                matches!(deserialize_role(role), Message::Assistant(_)),
+                "{role}"
+            );
+        }
+        for role in ["System", "SYSTEM", "system"] {
+            assert!(
+                matches!(deserialize_role(role), Message::System(_)),
+                "{role}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_strict_roles_reject_aliases() {
+        let body = json!({
+            "model": "test",
+            "messages": [{"role": "human", "content": "Hello"}]
+        });
+        assert!(serde_json::from_value::<ChatCompletionCreate>(body).is_err());
+    }
+
+    #[test]
+    fn test_normalize_message_roles_ignores_malformed_body() {
+        let mut body = json!({"model": "test", "messages": [{"content": "Hello"}, {"role": 1}]});
+        normalize_message_roles(&mut body);
+        assert_eq!(body["messages"][1]["role"], 1);
+
+        let mut body = json!({"model": "test"});
+        normalize_message_roles(&mut body);
+        assert_eq!(body, json!({"model": "test"}));
+    }
+}
@@ -3,7 +3,7 @@ use crate::models::request::MessageAssistant;
 use super::{FinishReason, LogProbs, Usage};
 use serde::{self, Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Choice {
     pub index: i32,
     pub message: MessageAssistant,
@@ -12,7 +12,7 @@ pub struct Choice {
     pub finish_reason: FinishReason,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ChatCompletion {
     pub id: String,
     pub object: String,
@@ -20,4 +20,47 @@ pub struct ChatCompletion {
     pub model: String,
     pub choices: Vec<Choice>,
     pub usage: Usage,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub diagnostics: Option<CompletionDiagnostics>,
+}
+
+/// Extended response details for clients that render the full turn structure.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CompletionDiagnostics {
+    pub messages: Vec<TurnMessage>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TurnRole {
+    Reasoning,
+    Assistant,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TurnMessage {
+    pub role: TurnRole,
+    pub content: String,
+}
+
+impl CompletionDiagnostics {
+    /// Each choice as a reasoning message followed by its answer message.
+    pub(crate) fn from_turns(turns: Vec<(String, String)>) -> Self {
+        let messages = turns
+            .into_iter()
+            .flat_map(|(reasoning, answer)| {
+                [
+                    TurnMessage {
+                        role: TurnRole::Reasoning,
+                        content: reasoning,
+                    },
+                    TurnMessage {
+                        role: TurnRole::Assistant,
+                        content: answer,
+                    },
+                ]
+            })
+            .collect();
+        Self { messages }
+    }
 }
@@ -6,29 +6,24 @@ use serde_json::Value;
 pub struct ChunkChoiceDelta {
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub role: Option<Role>,
-    #[cfg(reasoning)]
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub reasoning_content: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub content: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub tool_calls: Option<Vec<Value>>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub refusal: Option<String>,
 }
 
 impl ChunkChoiceDelta {
-    #[cfg(reasoning)]
-    pub(crate) fn chunk_choice_delta_opening() -> ChunkChoiceDelta {
+    pub(crate) fn chunk_choice_delta_opening(
+        emit_reasoning_content: bool,
+        think_start: &str,
+    ) -> ChunkChoiceDelta {
         ChunkChoiceDelta {
             role: Some(Role::Assistant),
-            ..Default::default()
-        }
-    }
-
-    #[cfg(not(reasoning))]
-    pub(crate) fn chunk_choice_delta_opening() -> ChunkChoiceDelta {
-        ChunkChoiceDelta {
-            role: Some(Role::Assistant),
-            content: Some(crate::consts::THINK_START.to_string()),
+            content: (!emit_reasoning_content).then(|| think_start.to_string()),
             ..Default::default()
         }
     }
@@ -40,16 +35,16 @@ impl ChunkChoiceDelta {
         }
     }
 
-    #[cfg(reasoning)]
-    pub(crate) fn chunk_choice_delta_reasoning(reasoning_content: String) -> ChunkChoiceDelta {
-        ChunkChoiceDelta {
-            reasoning_content: Some(reasoning_content),
-            ..Default::default()
+    pub(crate) fn chunk_choice_delta_reasoning(
+        reasoning_content: String,
+        emit_reasoning_content: bool,
+    ) -> ChunkChoiceDelta {
+        if emit_reasoning_content {
+            return ChunkChoiceDelta {
+                reasoning_content: Some(reasoning_content),
+                ..Default::default()
+            };
         }
-    }
-
-    #[cfg(not(reasoning))]
-    pub(crate) fn chunk_choice_delta_reasoning(reasoning_content: String) -> ChunkChoiceDelta {
         ChunkChoiceDelta {
             content: Some(reasoning_content),
             ..Default::default()
@@ -0,0 +1,326 @@
+use serde_json::{Value, json};
+
+// Hand-maintained mirror of the serde models in `crate::models`. Keep in sync
+// when fields are added to `ChatCompletionCreate` or the response structs.
+pub fn openapi_document() -> Value {
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Adaptive Reasoner",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": {
+            "/v1/models": {
+                "get": {
+                    "summary": "List served models",
+                    "responses": {
+                        "200": json_response("#/components/schemas/ModelList"),
+                    },
+                },
+            },
+            "/v1/chat/completions": {
+                "post": {
+                    "summary": "Create a chat completion with a bounded reasoning phase",
+                    "requestBody": {
+                        "required": true,
+                        "content": {
+                            "application/json": {
+                                "schema": {"$ref": "#/components/schemas/ChatCompletionCreate"},
+                            },
+                        },
+                    },
+                    "responses": {
+                        "200": {
+                            "description": "Completion, or an SSE stream of chunks when `stream` is true",
+                            "content": {
+                                "application/json": {
+                                    "schema": {"$ref": "#/components/schemas/ChatCompletion"},
+                                },
+                                "text/event-stream": {
+                                    "schema": {"$ref": "#/components/schemas/ChatCompletionChunk"},
+                                },
+                            },
+                        },
+                        "400": {"description": "Invalid request"},
+                        "502": {"description": "Upstream API failure"},
+                        "503": {"description": "Concurrent stream limit reached"},
+                        "504": {"description": "Request timeout exceeded"},
+                    },
+                },
+            },
+        },
+        "components": {
+            "schemas": {
+                "ChatCompletionCreate": chat_completion_create_schema(),
+                "Message": message_schema(),
+                "ChatCompletion": chat_completion_schema(),
+                "ChatCompletionChunk": chat_completion_chunk_schema(),
+                "Usage": usage_schema(),
+                "FinishReason": {
+                    "type": "string",
+                    "enum": ["stop", "length", "tool_calls"],
+                },
+                "ModelList": {
+                    "type": "object",
+                    "required": ["data"],
+                    "properties": {
+                        "data": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "required": ["id", "object", "created", "owned_by"],
+                                "properties": {
+                                    "id": {"type": "string"},
+                                    "object": {"type": "string", "enum": ["model"]},
+                                    "created": {"type": "integer"},
+                                    "owned_by": {"type": "string"},
+                                },
+                            },
+                        },
+                    },
+                },
+            },
+        },
+    })
+}
+
+fn json_response(schema_ref: &str) -> Value {
+    json!({
+        "description": "OK",
+        "content": {
+            "application/json": {"schema": {"$ref": schema_ref}},
+        },
+    })
+}
+
+fn chat_completion_create_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["model", "messages"],
+        "properties": {
+            "model": {"type": "string"},
+            "messages": {
+                "type": "array",
+                "minItems": 1,
+                "items": {"$ref": "#/components/schemas/Message"},
+            },
+            "max_tokens": {"type": "integer"},
+            "stop": {"type": "array", "items": {"type": "string"}},
+            "stream": {"type": "boolean"},
+            "stream_options": {
+                "type": "object",
+                "properties": {"include_usage": {"type": "boolean"}},
+            },
+            "tools": {"type": "array", "items": {"type": "object"}},
+            "tool_choice": {"type": "string", "enum": ["auto", "none", "required"]},
+            "temperature": {"type": "number"},
+            "top_p": {"type": "number"},
+            "n": {"type": "integer", "minimum": 1},
+            "best_of": {"type": "integer"},
+            "seed": {"type": "integer"},
+            "presence_penalty": {"type": "number"},
+            "frequency_penalty": {"type": "number"},
+            "logprobs": {"type": "boolean"},
+            "top_logprobs": {"type": "integer"},
+            "max_answer_chars": {
+                "type": "integer",
+                "minimum": 0,
+                "description": "Proxy extension: truncate the answer to this many characters",
+            },
+            "single_pass": {
+                "type": "boolean",
+                "description": "Proxy extension: run reasoning and answer in one upstream call",
+            },
+            "timeout_secs": {
+                "type": "integer",
+                "minimum": 0,
+                "description": "Proxy extension: bound the whole completion, in seconds",
+            },
+            "reasoning_budget": {
+                "oneOf": [
+                    {"type": "integer", "minimum": 0},
+                    {"type": "string", "enum": ["auto"]},
+                ],
+                "description": "Proxy extension: override the model's reasoning budget, in tokens or auto",
+            },
+            "reasoning_effort": {
+                "type": "string",
+                "enum": ["low", "medium", "high"],
+                "description": "Scale the model's reasoning budget, ignored when reasoning_budget is set",
+            },
+        },
+    })
+}
+
+fn message_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["role"],
+        "properties": {
+            "role": {"type": "string", "enum": ["system", "user", "assistant", "tool"]},
+            "content": {
+                "oneOf": [
+                    {"type": "string"},
+                    {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["type"],
+                            "properties": {
+                                "type": {"type": "string", "enum": ["text", "image_url"]},
+                                "text": {"type": "string"},
+                                "image_url": {
+                                    "type": "object",
+                                    "required": ["url"],
+                                    "properties": {
+                                        "url": {"type": "string"},
+                                        "detail": {"type": "string"},
+                                    },
+                                },
+                            },
+                        },
+                    },
+                ],
+            },
+            "reasoning_content": {"type": "string"},
+            "tool_calls": {"type": "array", "items": {"type": "object"}},
+            "tool_call_id": {"type": "string"},
+            "refusal": {"type": "string"},
+        },
+    })
+}
+
+fn chat_completion_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["id", "object", "created", "model", "choices", "usage"],
+        "properties": {
+            "id": {"type": "string"},
+            "object": {"type": "string"},
+            "created": {"type": "integer"},
+            "model": {"type": "string"},
+            "choices": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["index", "message", "finish_reason"],
+                    "properties": {
+                        "index": {"type": "integer"},
+                        "message": {"$ref": "#/components/schemas/Message"},
+                        "logprobs": {"type": "object"},
+                        "finish_reason": {"$ref": "#/components/schemas/FinishReason"},
+                    },
+                },
+            },
+            "usage": {"$ref": "#/components/schemas/Usage"},
+            "diagnostics": {
+                "type": "object",
+                "properties": {
+                    "messages": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["role", "content"],
+                            "properties": {
+                                "role": {"type": "string", "enum": ["reasoning", "assistant"]},
+                                "content": {"type": "string"},
+                            },
+                        },
+                    },
+                },
+            },
+        },
+    })
+}
+
+fn chat_completion_chunk_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["id", "object", "created", "model", "choices"],
+        "properties": {
+            "id": {"type": "string"},
+            "object": {"type": "string"},
+            "created": {"type": "integer"},
+            "model": {"type": "string"},
+            "choices": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["index", "delta"],
+                    "properties": {
+                        "index": {"type": "integer"},
+                        "delta": {
+                            "type": "object",
+                            "properties": {
+                                "role": {"type": "string"},
+                                "content": {"type": "string"},
+                                "reasoning_content": {"type": "string"},
+                                "tool_calls": {"type": "array", "items": {"type": "object"}},
+                                "refusal": {"type": "string"},
+                            },
+                        },
+                        "logprobs": {"type": "object"},
+                        "finish_reason": {"$ref": "#/components/schemas/FinishReason"},
+                    },
+                },
+            },
+            "usage": {"$ref": "#/components/schemas/Usage"},
+        },
+    })
+}
+
+fn usage_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["prompt_tokens", "completion_tokens", "total_tokens"],
+        "properties": {
+            "prompt_tokens": {"type": "integer"},
+            "completion_tokens": {"type": "integer"},
+            "total_tokens": {"type": "integer"},
+            "prompt_tokens_details": {
+                "type": "object",
+                "properties": {"cached_tokens": {"type": "integer"}},
+            },
+            "completion_tokens_details": {
+                "type": "object",
+                "properties": {"reasoning_tokens": {"type": "integer"}},
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_schema_lists_every_serialized_field() {
+        let request = crate::models::request::ChatCompletionCreate {
+            model: "test".to_string(),
+            max_tokens: Some(1),
+            stop: Some(vec![]),
+            stream: Some(true),
+            stream_options: Some(Default::default()),
+            tools: Some(vec![]),
+            tool_choice: Some(crate::models::request::ToolChoice::Auto),
+            temperature: Some(0.0),
+            top_p: Some(0.0),
+            n: Some(1),
+            best_of: Some(1),
+            seed: Some(1),
+            logprobs: Some(true),
+            top_logprobs: Some(1),
+            ..Default::default()
+        };
+        let serialized = serde_json::to_value(request).unwrap();
+        let document = openapi_document();
+        let properties = &document["components"]["schemas"]["ChatCompletionCreate"]["properties"];
+
+        for field in serialized.as_object().unwrap().keys() {
+            assert!(
+                properties.get(field).is_some(),
+                "{field} missing from schema"
+            );
+        }
+    }
+}
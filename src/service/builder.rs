@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::ReasoningService;
+use super::health_tracker::HealthTracker;
+use super::reasoning_cache::ReasoningCache;
+use super::single_flight::SingleFlight;
+use super::stream_limiter::StreamLimiter;
+use super::usage_tracker::UsageTracker;
+use crate::config::ModelConfig;
+use crate::consts;
+use crate::llm_client::Upstream;
+
+pub struct ReasoningServiceBuilder {
+    http_client: reqwest::Client,
+    max_concurrent_streams: Option<usize>,
+    stream_reasoning_progress: bool,
+    stream_diagnostics: bool,
+    sse_done_sentinel: Option<String>,
+    allowed_upstream_hosts: Option<Vec<String>>,
+    stream_idle_timeout: Option<Duration>,
+    max_stream_duration: Option<Duration>,
+    stream_keep_alive: Option<Duration>,
+    reasoning_cache: Option<(usize, Duration)>,
+    reasoning_cache_include_unseeded: bool,
+    single_flight: bool,
+    fallback_models: HashMap<String, ModelConfig>,
+    fresh_completion_ids: bool,
+    upstream: Option<Arc<dyn Upstream>>,
+}
+
+impl ReasoningServiceBuilder {
+    pub fn new(http_client: reqwest::Client) -> Self {
+        Self {
+            http_client,
+            max_concurrent_streams: None,
+            stream_reasoning_progress: false,
+            stream_diagnostics: false,
+            sse_done_sentinel: Some(consts::SSE_DONE_SENTINEL.to_string()),
+            allowed_upstream_hosts: None,
+            stream_idle_timeout: None,
+            max_stream_duration: None,
+            stream_keep_alive: Some(Duration::from_secs(consts::DEFAULT_STREAM_KEEP_ALIVE_SECS)),
+            reasoning_cache: None,
+            reasoning_cache_include_unseeded: false,
+            single_flight: false,
+            fallback_models: HashMap::new(),
+            fresh_completion_ids: true,
+            upstream: None,
+        }
+    }
+
+    pub fn max_concurrent_streams(mut self, max_concurrent_streams: Option<usize>) -> Self {
+        self.max_concurrent_streams = max_concurrent_streams;
+        self
+    }
+
+    pub fn reasoning_progress(mut self, stream_reasoning_progress: bool) -> Self {
+        self.stream_reasoning_progress = stream_reasoning_progress;
+        self
+    }
+
+    pub fn stream_diagnostics(mut self, stream_diagnostics: bool) -> Self {
+        self.stream_diagnostics = stream_diagnostics;
+        self
+    }
+
+    pub fn sse_done_sentinel(mut self, sse_done_sentinel: Option<String>) -> Self {
+        self.sse_done_sentinel = sse_done_sentinel;
+        self
+    }
+
+    pub fn allowed_upstream_hosts(mut self, allowed_upstream_hosts: Option<Vec<String>>) -> Self {
+        self.allowed_upstream_hosts = allowed_upstream_hosts;
+        self
+    }
+
+    pub fn stream_idle_timeout(mut self, stream_idle_timeout: Option<Duration>) -> Self {
+        self.stream_idle_timeout = stream_idle_timeout;
+        self
+    }
+
+    /// Upper bound on how long a streamed completion may run across both phases.
+    pub fn max_stream_duration(mut self, max_stream_duration: Option<Duration>) -> Self {
+        self.max_stream_duration = max_stream_duration;
+        self
+    }
+
+    pub fn stream_keep_alive(mut self, stream_keep_alive: Option<Duration>) -> Self {
+        self.stream_keep_alive = stream_keep_alive;
+        self
+    }
+
+    pub fn reasoning_cache(
+        mut self,
+        capacity: usize,
+        ttl: Duration,
+        include_unseeded: bool,
+    ) -> Self {
+        self.reasoning_cache = (capacity > 0).then_some((capacity, ttl));
+        self.reasoning_cache_include_unseeded = include_unseeded;
+        self
+    }
+
+    pub fn single_flight(mut self, single_flight: bool) -> Self {
+        self.single_flight = single_flight;
+        self
+    }
+
+    /// Models that `fallbacks` entries of a model configuration refer to by name.
+    pub fn fallback_models(mut self, fallback_models: HashMap<String, ModelConfig>) -> Self {
+        self.fallback_models = fallback_models;
+        self
+    }
+
+    /// Mints a new id and timestamp for merged responses instead of reusing the
+    /// reasoning-phase response's.
+    pub fn fresh_completion_ids(mut self, fresh_completion_ids: bool) -> Self {
+        self.fresh_completion_ids = fresh_completion_ids;
+        self
+    }
+
+    /// Serves every model from the given upstream instead of its configured HTTP endpoint.
+    #[allow(dead_code)]
+    pub fn upstream(mut self, upstream: Arc<dyn Upstream>) -> Self {
+        self.upstream = Some(upstream);
+        self
+    }
+
+    pub fn build(self) -> ReasoningService {
+        ReasoningService {
+            http_client: self.http_client,
+            stream_limiter: StreamLimiter::new(self.max_concurrent_streams),
+            stream_reasoning_progress: self.stream_reasoning_progress,
+            stream_diagnostics: self.stream_diagnostics,
+            sse_done_sentinel: self.sse_done_sentinel,
+            allowed_upstream_hosts: self.allowed_upstream_hosts,
+            stream_idle_timeout: self.stream_idle_timeout,
+            max_stream_duration: self.max_stream_duration,
+            stream_keep_alive: self.stream_keep_alive,
+            reasoning_cache: self
+                .reasoning_cache
+                .map(|(capacity, ttl)| Arc::new(ReasoningCache::new(capacity, ttl))),
+            reasoning_cache_include_unseeded: self.reasoning_cache_include_unseeded,
+            single_flight: self
+                .single_flight
+                .then(|| Arc::new(SingleFlight::default())),
+            fallback_models: Arc::new(self.fallback_models),
+            fresh_completion_ids: self.fresh_completion_ids,
+            discovered_models: Arc::default(),
+            upstream: self.upstream,
+            usage_tracker: Arc::new(UsageTracker::default()),
+            health_tracker: Arc::new(HealthTracker::default()),
+            started_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs() as i64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_builds_minimal_service() {
+        let service = ReasoningService::new(reqwest::Client::new());
+
+        assert!(service.reasoning_cache.is_none());
+        assert!(!service.stream_reasoning_progress);
+        assert!(!service.stream_diagnostics);
+        assert_eq!(service.sse_done_sentinel.as_deref(), Some("[DONE]"));
+        let permits: Vec<_> = (0..8).map(|_| service.try_acquire_stream()).collect();
+        assert!(permits.iter().all(Option::is_some));
+    }
+
+    #[test]
+    fn test_builder_applies_components() {
+        let service = ReasoningService::builder(reqwest::Client::new())
+            .max_concurrent_streams(Some(1))
+            .reasoning_cache(4, Duration::from_secs(60), true)
+            .reasoning_progress(true)
+            .build();
+
+        let permit = service.try_acquire_stream();
+        assert!(permit.is_some());
+        assert!(service.try_acquire_stream().is_none());
+        assert_eq!(service.active_streams(), 1);
+
+        assert!(service.reasoning_cache.is_some());
+        assert!(service.reasoning_cache_include_unseeded);
+        assert!(service.stream_reasoning_progress);
+    }
+
+    #[test]
+    fn test_zero_capacity_disables_reasoning_cache() {
+        let service = ReasoningServiceBuilder::new(reqwest::Client::new())
+            .reasoning_cache(0, Duration::from_secs(60), false)
+            .build();
+
+        assert!(service.reasoning_cache.is_none());
+    }
+}
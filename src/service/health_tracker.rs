@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::consts;
+
+/// Counts consecutive upstream failures per served model, so a model whose
+/// upstream keeps failing can be reported unhealthy until a request succeeds.
+#[derive(Default)]
+pub(crate) struct HealthTracker {
+    failures: Mutex<HashMap<String, u32>>,
+}
+
+impl HealthTracker {
+    pub(crate) fn record_success(&self, model: &str) {
+        self.failures.lock().unwrap().remove(model);
+    }
+
+    pub(crate) fn record_failure(&self, model: &str) {
+        *self
+            .failures
+            .lock()
+            .unwrap()
+            .entry(model.to_string())
+            .or_default() += 1;
+    }
+
+    pub(crate) fn is_healthy(&self, model: &str) -> bool {
+        self.failures
+            .lock()
+            .unwrap()
+            .get(model)
+            .is_none_or(|failures| *failures < consts::UNHEALTHY_MODEL_FAILURES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_tracker_recovers_after_success() {
+        let tracker = HealthTracker::default();
+
+        for _ in 0..consts::UNHEALTHY_MODEL_FAILURES {
+            assert!(tracker.is_healthy("model-a"));
+            tracker.record_failure("model-a");
+        }
+        assert!(!tracker.is_healthy("model-a"));
+        assert!(tracker.is_healthy("model-b"));
+
+        tracker.record_success("model-a");
+        assert!(tracker.is_healthy("model-a"));
+    }
+}
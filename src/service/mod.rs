@@ -1,18 +1,32 @@
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use actix_web::mime;
 use actix_web::web::Bytes;
-use tokio::sync::mpsc::Sender;
+use tokio::sync::mpsc::{self, Sender};
 
 use crate::config;
 use crate::consts;
 use crate::errors::ReasonerError;
-use crate::llm_client::{LLMClient, LLMClientTrait};
+use crate::llm_client::{LLMClient, RetryBudget, Upstream, UpstreamStream, check_allowed_host};
 use crate::llm_request::{
-    build_answer_request, build_reasoning_request, calculate_remaining_tokens,
-    validate_chat_request,
+    apply_max_answer_chars, build_answer_request, build_passthrough_request,
+    build_reasoning_request, build_reasoning_retry_request, build_single_pass_request,
+    calculate_remaining_tokens, calculate_tokens_per_second, drop_empty_assistant_messages,
+    estimate_cost, estimate_truncated_answer_tokens, extract_client_reasoning, is_blank_reasoning,
+    is_empty_answer, is_low_confidence, reasoning_confirms_answer, resolve_answer_stops,
+    resolve_choice_count, resolve_deterministic_id, resolve_finish_reason,
+    resolve_leaked_think_tags, resolve_max_answer_chars, resolve_max_tokens,
+    resolve_reasoning_budget, resolve_reasoning_cache_key, resolve_reasoning_cutoff_stub,
+    resolve_reasoning_retry_budget, resolve_request_timeout, resolve_single_flight_key,
+    resolve_single_pass, resolve_think_tags, resolve_upstream_stream_options,
+    split_reasoning_and_answer, strip_echoed_think_start, strip_prior_reasoning, strip_think_tags,
+    strip_trailing_stop, synthesize_answer, truncate_to_chars, validate_chat_request,
 };
+use crate::log_context;
+use crate::models::CompletionTokensDetails;
 use crate::models::FinishReason;
+use crate::models::PromptTokensDetails;
 use crate::models::Usage;
 use crate::models::request;
 use crate::models::response_direct;
@@ -21,55 +35,1644 @@ use crate::models::response_stream;
 use crate::models::response_stream::ChatCompletionChunk;
 use crate::models::response_stream::ChunkChoiceDelta;
 
+mod builder;
+mod health_tracker;
+mod reasoning_cache;
+mod single_flight;
+mod sse_buffer;
+mod stop_trimmer;
+mod stream_limiter;
+mod think_tag_stripper;
+mod usage_tracker;
+
+pub use builder::ReasoningServiceBuilder;
+
+use health_tracker::HealthTracker;
+use reasoning_cache::{CachedReasoning, ReasoningCache};
+use single_flight::SingleFlight;
+use sse_buffer::SseBuffer;
+use stop_trimmer::StopTrimmer;
+use stream_limiter::StreamLimiter;
+pub use stream_limiter::StreamPermit;
+use think_tag_stripper::ThinkTagStripper;
+pub use usage_tracker::ModelUsage;
+use usage_tracker::UsageTracker;
+
+struct ReasoningPhase {
+    id: String,
+    object: String,
+    created: i64,
+    prompt_tokens: i32,
+    reasoning_tokens: i32,
+    finish_reason: FinishReason,
+    text: String,
+    tool_calls: Option<Vec<serde_json::Value>>,
+    refusal: Option<String>,
+    prompt_tokens_details: Option<PromptTokensDetails>,
+}
+
+impl ReasoningPhase {
+    fn prefilled(text: String, finish_reason: FinishReason, reasoning_tokens: i32) -> Self {
+        Self {
+            id: "".to_string(),
+            object: "chat.completion".to_string(),
+            created: 0,
+            prompt_tokens: 0,
+            reasoning_tokens,
+            finish_reason,
+            text,
+            tool_calls: None,
+            refusal: None,
+            prompt_tokens_details: None,
+        }
+    }
+}
+
+enum Speculation {
+    /// Reasoning finished first and changes the answer, which has to be requested
+    /// again. Carries the completion tokens of the discarded speculative answer.
+    Reasoning(ReasoningPhase, i32),
+    /// Reasoning finished first but leaves the speculative answer standing.
+    Confirmed(ReasoningPhase, ChatCompletion),
+    /// The speculative answer finished first and the reasoning was cancelled.
+    Answer(ChatCompletion),
+}
+
 #[derive(Clone)]
 pub struct ReasoningService {
     http_client: reqwest::Client,
+    stream_limiter: StreamLimiter,
+    stream_reasoning_progress: bool,
+    stream_diagnostics: bool,
+    sse_done_sentinel: Option<String>,
+    allowed_upstream_hosts: Option<Vec<String>>,
+    stream_idle_timeout: Option<Duration>,
+    max_stream_duration: Option<Duration>,
+    stream_keep_alive: Option<Duration>,
+    reasoning_cache: Option<Arc<ReasoningCache>>,
+    reasoning_cache_include_unseeded: bool,
+    single_flight: Option<Arc<SingleFlight>>,
+    fallback_models: Arc<HashMap<String, config::ModelConfig>>,
+    fresh_completion_ids: bool,
+    discovered_models: Arc<RwLock<HashMap<String, serde_json::Map<String, serde_json::Value>>>>,
+    upstream: Option<Arc<dyn Upstream>>,
+    usage_tracker: Arc<UsageTracker>,
+    health_tracker: Arc<HealthTracker>,
+    started_at: i64,
 }
 
-impl ReasoningService {
-    pub fn new(http_client: reqwest::Client) -> Self {
-        Self { http_client }
+impl ReasoningService {
+    #[allow(dead_code)]
+    pub fn new(http_client: reqwest::Client) -> Self {
+        Self::builder(http_client).build()
+    }
+
+    pub fn builder(http_client: reqwest::Client) -> ReasoningServiceBuilder {
+        ReasoningServiceBuilder::new(http_client)
+    }
+
+    pub fn try_acquire_stream(&self) -> Option<StreamPermit> {
+        self.stream_limiter.try_acquire()
+    }
+
+    pub fn active_streams(&self) -> usize {
+        self.stream_limiter.active()
+    }
+
+    /// Waits up to `grace_period` for active streams to finish, returning whether they all did.
+    pub async fn drain_streams(&self, grace_period: Duration) -> bool {
+        tokio::time::timeout(grace_period, self.stream_limiter.idle())
+            .await
+            .is_ok()
+    }
+
+    pub fn started_at(&self) -> i64 {
+        self.started_at
+    }
+
+    /// Id and creation time of a response merged from several upstream calls: freshly
+    /// minted unless ids are deterministic or the upstream ones are to be preserved.
+    fn merged_identity(
+        &self,
+        request: &request::ChatCompletionCreate,
+        model_config: &config::ModelConfig,
+        upstream_id: String,
+        upstream_created: i64,
+    ) -> (String, i64) {
+        if let Some(deterministic_id) = resolve_deterministic_id(request, model_config) {
+            return (deterministic_id, upstream_created);
+        }
+        if !self.fresh_completion_ids {
+            return (upstream_id, upstream_created);
+        }
+        let created = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(upstream_created, |elapsed| elapsed.as_secs() as i64);
+        let id = format!("chatcmpl-{}", uuid::Uuid::new_v4().simple());
+        (id, created)
+    }
+
+    async fn send_done(
+        &self,
+        sender: &Sender<Result<Bytes, ReasonerError>>,
+    ) -> Result<(), ReasonerError> {
+        match &self.sse_done_sentinel {
+            Some(sentinel) => send_data(sender, sentinel.clone()).await,
+            None => Ok(()),
+        }
+    }
+
+    fn chunk_stream(
+        &self,
+        response: Box<dyn UpstreamStream>,
+        sender: &Sender<Result<Bytes, ReasonerError>>,
+    ) -> ChunkStream {
+        ChunkStream {
+            response,
+            buffer: SseBuffer::default(),
+            pending: VecDeque::new(),
+            finished: false,
+            idle_timeout: self.stream_idle_timeout,
+            keep_alive: self
+                .stream_keep_alive
+                .map(|interval| (interval, sender.clone())),
+        }
+    }
+
+    /// Keeps the client connection warm while upstream has not started streaming yet.
+    async fn open_stream(
+        &self,
+        upstream: &dyn Upstream,
+        request: request::ChatCompletionCreate,
+        sender: &Sender<Result<Bytes, ReasonerError>>,
+    ) -> Result<Box<dyn UpstreamStream>, ReasonerError> {
+        let stream = upstream.stream(request);
+        tokio::pin!(stream);
+        loop {
+            tokio::select! {
+                stream = &mut stream => return stream,
+                _ = sleep_for(self.stream_keep_alive) => {
+                    send_comment(sender, consts::SSE_KEEP_ALIVE_COMMENT.to_string()).await?;
+                }
+            }
+        }
+    }
+
+    /// Probes `api_url`, counting server errors and hosts outside the allow-list
+    /// as unreachable.
+    pub async fn upstream_reachable(&self, api_url: &str) -> bool {
+        if let Err(e) = check_allowed_host(self.allowed_upstream_hosts.as_deref(), api_url) {
+            log::warn!("Upstream {api_url} is not probed: {e}");
+            return false;
+        }
+        let probe = self
+            .http_client
+            .head(api_url)
+            .timeout(Duration::from_secs(consts::READINESS_PROBE_TIMEOUT_SECS))
+            .send()
+            .await;
+        match probe {
+            Ok(response) if response.status().is_server_error() => {
+                log::warn!(
+                    "Upstream {api_url} is unavailable: status {}",
+                    response.status()
+                );
+                false
+            }
+            Ok(_) => true,
+            Err(e) => {
+                log::warn!("Upstream {api_url} is unreachable: {e}");
+                false
+            }
+        }
+    }
+
+    /// Fetches each distinct upstream's model listing once and caches the entry
+    /// matching every configured model, warning about models the upstream lacks.
+    pub async fn discover_upstream_models(&self, models: &HashMap<String, config::ModelConfig>) {
+        let mut listings: HashMap<&str, Vec<serde_json::Value>> = HashMap::new();
+        let mut discovered = HashMap::new();
+        for (model_name, model_config) in models {
+            let api_url = model_config.api_url.as_str();
+            if !listings.contains_key(api_url) {
+                let client = LLMClient::new(self.http_client.clone(), model_config)
+                    .with_allowed_hosts(self.allowed_upstream_hosts.clone());
+                let listing = client.list_models().await.unwrap_or_else(|e| {
+                    log::warn!("Could not list models of upstream {api_url}: {e}");
+                    vec![]
+                });
+                listings.insert(api_url, listing);
+            }
+
+            let entry = listings[api_url]
+                .iter()
+                .find(|entry| entry["id"] == model_config.model_name.as_str());
+            match entry {
+                Some(serde_json::Value::Object(entry)) => {
+                    discovered.insert(model_name.clone(), entry.clone());
+                }
+                _ => log::warn!(
+                    "Model {model_name}: upstream {api_url} does not list {}",
+                    model_config.model_name
+                ),
+            }
+        }
+        // The map is replaced wholesale, so a poisoned lock holds no partial update
+        *self
+            .discovered_models
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = discovered;
+    }
+
+    pub fn discovered_model(
+        &self,
+        model_name: &str,
+    ) -> Option<serde_json::Map<String, serde_json::Value>> {
+        self.discovered_models
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(model_name)
+            .cloned()
+    }
+
+    pub fn usage(&self) -> BTreeMap<String, ModelUsage> {
+        self.usage_tracker.snapshot()
+    }
+
+    /// False once the model's upstream failed several requests in a row, until one succeeds.
+    pub fn model_healthy(&self, model: &str) -> bool {
+        self.health_tracker.is_healthy(model)
+    }
+
+    fn record_health<T>(&self, model: &str, result: &Result<T, ReasonerError>) {
+        match result {
+            Ok(_) => self.health_tracker.record_success(model),
+            Err(error) if error.is_retryable() => self.health_tracker.record_failure(model),
+            Err(_) => {}
+        }
+    }
+
+    fn record_usage(
+        &self,
+        model: &str,
+        model_config: &config::ModelConfig,
+        prompt_tokens: i32,
+        completion_tokens: i32,
+    ) -> Option<f64> {
+        let estimated_cost = estimate_cost(model_config, prompt_tokens, completion_tokens);
+        self.usage_tracker
+            .record(model, prompt_tokens, completion_tokens, estimated_cost);
+        estimated_cost
+    }
+
+    fn reasoning_cache_key(
+        &self,
+        request: &request::ChatCompletionCreate,
+        model_config: &config::ModelConfig,
+    ) -> Option<u64> {
+        self.reasoning_cache.as_ref()?;
+        resolve_reasoning_cache_key(request, model_config, self.reasoning_cache_include_unseeded)
+    }
+
+    fn cache_reasoning(&self, cache_key: Option<u64>, reasoning: CachedReasoning) {
+        if let (Some(reasoning_cache), Some(cache_key)) = (&self.reasoning_cache, cache_key) {
+            reasoning_cache.insert(cache_key, reasoning);
+        }
+    }
+
+    fn cached_reasoning(&self, cache_key: Option<u64>) -> Option<CachedReasoning> {
+        let cached = self.reasoning_cache.as_ref()?.get(cache_key?);
+        if cached.is_some() {
+            log::debug!("Reasoning cache hit, skipping the reasoning phase");
+        }
+        cached
+    }
+
+    pub(crate) fn create_upstream(&self, model_config: &config::ModelConfig) -> Arc<dyn Upstream> {
+        self.create_upstream_with_budget(
+            model_config,
+            RetryBudget::new(model_config.max_total_retries),
+        )
+    }
+
+    fn create_upstream_with_budget(
+        &self,
+        model_config: &config::ModelConfig,
+        retry_budget: RetryBudget,
+    ) -> Arc<dyn Upstream> {
+        if let Some(upstream) = &self.upstream {
+            return upstream.clone();
+        }
+        Arc::new(
+            LLMClient::new(self.http_client.clone(), model_config)
+                .with_retry_budget(retry_budget)
+                .with_allowed_hosts(self.allowed_upstream_hosts.clone()),
+        )
+    }
+
+    /// Upstream for the answer phase, billed to `answer_api_key` when the model sets one.
+    /// Both phases draw retries from the same `retry_budget`.
+    fn answer_upstream(
+        &self,
+        upstream: &Arc<dyn Upstream>,
+        model_config: &config::ModelConfig,
+        retry_budget: &RetryBudget,
+    ) -> Arc<dyn Upstream> {
+        match &model_config.answer_api_key {
+            Some(answer_api_key) if self.upstream.is_none() => {
+                let answer_config = config::ModelConfig {
+                    api_key: answer_api_key.clone(),
+                    ..model_config.clone()
+                };
+                self.create_upstream_with_budget(&answer_config, retry_budget.clone())
+            }
+            _ => upstream.clone(),
+        }
+    }
+
+    pub async fn create_completion(
+        &self,
+        request: request::ChatCompletionCreate,
+        model_config: &config::ModelConfig,
+    ) -> Result<ChatCompletion, ReasonerError> {
+        // A follower would log under the leader's level, so requests that elevate
+        // their own level always run on their own
+        let single_flight_key = self
+            .single_flight
+            .as_ref()
+            .filter(|_| log_context::current().is_none())
+            .and_then(|_| resolve_single_flight_key(&request, model_config));
+        let model = request.model.clone();
+        let result = match (&self.single_flight, single_flight_key) {
+            (Some(single_flight), Some(key)) => {
+                single_flight
+                    .run(key, self.timed_completion(request, model_config))
+                    .await
+            }
+            _ => self.timed_completion(request, model_config).await,
+        };
+        self.record_health(&model, &result);
+        result
+    }
+
+    async fn timed_completion(
+        &self,
+        request: request::ChatCompletionCreate,
+        model_config: &config::ModelConfig,
+    ) -> Result<ChatCompletion, ReasonerError> {
+        let Some(timeout) = resolve_request_timeout(&request) else {
+            return self.complete_with_fallbacks(request, model_config).await;
+        };
+
+        tokio::time::timeout(timeout, self.complete_with_fallbacks(request, model_config))
+            .await
+            .map_err(|_| timeout_error(timeout))?
+    }
+
+    pub async fn stream_completion(
+        &self,
+        request: request::ChatCompletionCreate,
+        model_config: &config::ModelConfig,
+        sender: Sender<Result<Bytes, ReasonerError>>,
+    ) -> Result<(), ReasonerError> {
+        let model = request.model.clone();
+        let request_timeout = resolve_request_timeout(&request);
+        let mut lifetime_exceeded = false;
+        let result = tokio::select! {
+            result = self.stream_with_fallbacks(request, model_config, sender.clone()) => result,
+            _ = sleep_for(request_timeout) => Err(timeout_error(request_timeout.unwrap_or_default())),
+            _ = sleep_for(self.max_stream_duration) => {
+                lifetime_exceeded = true;
+                Err(ReasonerError::timeout(format!(
+                    "error: stream exceeded its maximum lifetime of {:?}",
+                    self.max_stream_duration.unwrap_or_default()
+                )))
+            }
+        };
+
+        // Lets clients tell a failed stream from a clean end, the way OpenAI does
+        if let Err(error) = &result
+            && send_error(&sender, error).await.is_err()
+        {
+            log::debug!("Client went away before the stream error could be delivered");
+        }
+        // The upstream may still be streaming, so close the client stream for it
+        if lifetime_exceeded && self.send_done(&sender).await.is_err() {
+            log::debug!("Client went away before the stream could be closed");
+        }
+        self.record_health(&model, &result);
+        result
+    }
+
+    fn fallback_configs<'a>(
+        &'a self,
+        model_config: &'a config::ModelConfig,
+    ) -> impl Iterator<Item = (&'a str, &'a config::ModelConfig)> {
+        model_config.fallbacks.iter().flatten().filter_map(|name| {
+            match self.fallback_models.get(name) {
+                Some(fallback) => Some((name.as_str(), fallback)),
+                None => {
+                    log::warn!("Fallback model {name} is not configured, skipping it");
+                    None
+                }
+            }
+        })
+    }
+
+    async fn complete_with_fallbacks(
+        &self,
+        request: request::ChatCompletionCreate,
+        model_config: &config::ModelConfig,
+    ) -> Result<ChatCompletion, ReasonerError> {
+        let mut result = self.run_completion(request.clone(), model_config).await;
+        for (name, fallback) in self.fallback_configs(model_config) {
+            match &result {
+                Err(error) if error.is_retryable() => {
+                    log::warn!("Upstream failed ({error}), falling back to model {name}");
+                }
+                _ => break,
+            }
+            result = self.run_completion(request.clone(), fallback).await;
+        }
+        result
+    }
+
+    /// Falls back only while nothing but comments has reached the client, since
+    /// a stream cannot be restarted once content was delivered.
+    async fn stream_with_fallbacks(
+        &self,
+        request: request::ChatCompletionCreate,
+        model_config: &config::ModelConfig,
+        sender: Sender<Result<Bytes, ReasonerError>>,
+    ) -> Result<(), ReasonerError> {
+        if model_config.fallbacks.as_ref().is_none_or(Vec::is_empty) {
+            return self
+                .run_stream_completion(request, model_config, sender)
+                .await;
+        }
+
+        let candidates = std::iter::once(("primary", model_config))
+            .chain(self.fallback_configs(model_config))
+            .collect::<Vec<_>>();
+        let mut result = Ok(());
+        for (attempt, (name, candidate)) in candidates.into_iter().enumerate() {
+            if attempt > 0 {
+                log::warn!("Upstream stream failed, falling back to model {name}");
+            }
+            let (attempt_sender, attempt_receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+            let (attempt_result, delivered_data) = tokio::join!(
+                self.run_stream_completion(request.clone(), candidate, attempt_sender),
+                forward_stream(attempt_receiver, &sender),
+            );
+            result = attempt_result;
+            match &result {
+                Err(error) if !delivered_data && error.is_retryable() => {}
+                _ => break,
+            }
+        }
+        result
+    }
+
+    async fn run_completion(
+        &self,
+        request: request::ChatCompletionCreate,
+        model_config: &config::ModelConfig,
+    ) -> Result<ChatCompletion, ReasonerError> {
+        let request = drop_empty_assistant_messages(request, model_config);
+        let request = strip_prior_reasoning(request, model_config);
+        validate_chat_request(&request)?;
+
+        if skips_reasoning(&request, model_config) {
+            return self
+                .create_passthrough_completion(request, model_config)
+                .await;
+        }
+        if model_config.native_reasoning {
+            return self
+                .create_native_reasoning_completion(request, model_config)
+                .await;
+        }
+        if resolve_single_pass(&request, model_config) {
+            return self
+                .create_single_pass_completion(request, model_config)
+                .await;
+        }
+
+        let retry_budget = RetryBudget::new(model_config.max_total_retries);
+        let upstream = self.create_upstream_with_budget(model_config, retry_budget.clone());
+        let answer_upstream = self.answer_upstream(&upstream, model_config, &retry_budget);
+
+        let cache_key = self.reasoning_cache_key(&request, model_config);
+        let mut speculative_answer = None;
+        let mut discarded_answer_tokens = 0;
+        let mut reasoning_cancelled = false;
+        let mut reasoning = if let Some(text) = extract_client_reasoning(&request, model_config) {
+            log::debug!("Reusing client reasoning, skipping the reasoning phase");
+            ReasoningPhase::prefilled(text, FinishReason::Stop, 0)
+        } else if let Some(cached) = self.cached_reasoning(cache_key) {
+            ReasoningPhase::prefilled(cached.text, cached.finish_reason, cached.reasoning_tokens)
+        } else {
+            let reasoning = if model_config.speculative_answer {
+                match self
+                    .race_speculative_answer(
+                        upstream.as_ref(),
+                        answer_upstream.as_ref(),
+                        &request,
+                        model_config,
+                    )
+                    .await?
+                {
+                    Speculation::Reasoning(reasoning, discarded_tokens) => {
+                        discarded_answer_tokens = discarded_tokens;
+                        reasoning
+                    }
+                    Speculation::Confirmed(reasoning, answer_response) => {
+                        speculative_answer = Some(answer_response);
+                        reasoning
+                    }
+                    Speculation::Answer(answer_response) => {
+                        speculative_answer = Some(answer_response);
+                        reasoning_cancelled = true;
+                        ReasoningPhase::prefilled("".to_string(), FinishReason::Stop, 0)
+                    }
+                }
+            } else {
+                self.request_reasoning(upstream.as_ref(), &request, model_config)
+                    .await?
+            };
+            if reasoning.tool_calls.is_some()
+                && model_config.reasoning_tool_calls == config::ReasoningToolCallBehavior::Surface
+            {
+                log::debug!(
+                    "Completion {} reasoning returned only tool calls, surfacing them",
+                    reasoning.id
+                );
+                let message = request::MessageAssistant {
+                    reasoning_content: None,
+                    content: None,
+                    tool_calls: reasoning.tool_calls.clone(),
+                    refusal: None,
+                };
+                return Ok(self.surface_reasoning(
+                    &request,
+                    model_config,
+                    reasoning,
+                    message,
+                    FinishReason::ToolCalls,
+                ));
+            }
+            if reasoning.refusal.is_some() && model_config.short_circuit_reasoning_refusal {
+                log::debug!(
+                    "Completion {} reasoning was refused, skipping the answer",
+                    reasoning.id
+                );
+                let message = request::MessageAssistant {
+                    reasoning_content: None,
+                    content: None,
+                    tool_calls: None,
+                    refusal: reasoning.refusal.clone(),
+                };
+                return Ok(self.surface_reasoning(
+                    &request,
+                    model_config,
+                    reasoning,
+                    message,
+                    FinishReason::Stop,
+                ));
+            }
+            if !reasoning_cancelled {
+                self.cache_reasoning(
+                    cache_key,
+                    CachedReasoning {
+                        text: reasoning.text.clone(),
+                        finish_reason: reasoning.finish_reason,
+                        reasoning_tokens: reasoning.reasoning_tokens,
+                    },
+                );
+            }
+            reasoning
+        };
+
+        let max_answer_chars = resolve_max_answer_chars(&request, model_config);
+        let answer_stops = resolve_answer_stops(&request, model_config);
+        let leaked_think_tags = resolve_leaked_think_tags(model_config);
+        let mut choices = vec![];
+        let mut turns = vec![];
+        let answer_tokens: i32;
+        let mut answer_prompt_tokens_details = None;
+        let reasoning_prompt_tokens = reasoning.prompt_tokens;
+        let mut answer_prompt_tokens = 0;
+        let remaining_tokens = calculate_remaining_tokens(
+            resolve_max_tokens(&request, model_config),
+            reasoning.reasoning_tokens,
+        );
+        if remaining_tokens > 0 && !should_return_length(reasoning.finish_reason, model_config) {
+            let synthesized_answer =
+                synthesize_answer(model_config, &reasoning.text, reasoning.finish_reason);
+            if let FinishReason::Length = reasoning.finish_reason {
+                reasoning.text = format!(
+                    "{}...\n\n{}\n",
+                    reasoning.text,
+                    resolve_reasoning_cutoff_stub(model_config)
+                );
+            }
+
+            let answer_response = match speculative_answer {
+                Some(answer_response) => answer_response,
+                None => {
+                    self.request_gated_answer(
+                        answer_upstream.as_ref(),
+                        &request,
+                        model_config,
+                        &reasoning.text,
+                        remaining_tokens,
+                    )
+                    .await?
+                }
+            };
+
+            if reasoning.id.is_empty() {
+                reasoning.id = answer_response.id.clone();
+                reasoning.created = answer_response.created;
+                reasoning.prompt_tokens = answer_response.usage.prompt_tokens;
+            }
+            answer_prompt_tokens = answer_response.usage.prompt_tokens;
+            answer_prompt_tokens_details = answer_response.usage.prompt_tokens_details;
+            answer_tokens = answer_response.usage.completion_tokens + discarded_answer_tokens;
+            for answer_choice in answer_response.choices {
+                let mut answer_text = match &answer_choice.message.content {
+                    Some(content) => {
+                        let content = strip_think_tags(content, &leaked_think_tags);
+                        strip_trailing_stop(content.trim(), &answer_stops)
+                            .trim()
+                            .to_string()
+                    }
+                    None => "".to_string(),
+                };
+                if answer_text.is_empty()
+                    && answer_choice.message.tool_calls.is_none()
+                    && let Some(synthesized_answer) = &synthesized_answer
+                {
+                    log::debug!(
+                        "Completion {} answer {} is empty, synthesizing it from reasoning",
+                        reasoning.id,
+                        answer_choice.index
+                    );
+                    answer_text = synthesized_answer.clone();
+                }
+
+                log::debug!(
+                    "Completion {} answer {} text: {}",
+                    reasoning.id,
+                    answer_choice.index,
+                    answer_text
+                );
+
+                let (answer_text, finish_reason) = apply_max_answer_chars(
+                    answer_text,
+                    resolve_finish_reason(Some(answer_choice.finish_reason)),
+                    max_answer_chars,
+                );
+                if model_config.reasoning_turn_messages {
+                    turns.push((reasoning.text.trim().to_string(), answer_text.clone()));
+                }
+                let mut message = request::MessageAssistant::new(
+                    reasoning.text.clone(),
+                    answer_text,
+                    answer_choice.message.tool_calls,
+                    model_config.emit_reasoning_content,
+                    resolve_think_tags(model_config),
+                );
+                message.refusal = answer_choice.message.refusal;
+                choices.push(response_direct::Choice {
+                    index: answer_choice.index,
+                    message,
+                    logprobs: answer_choice.logprobs,
+                    finish_reason,
+                });
+            }
+
+            log::debug!(
+                "Completion {} answer usage: answer_tokens: {}",
+                reasoning.id,
+                answer_tokens
+            );
+        } else {
+            answer_tokens = 0;
+            for index in 0..resolve_choice_count(&request) {
+                if model_config.reasoning_turn_messages {
+                    turns.push((reasoning.text.trim().to_string(), "".to_string()));
+                }
+                choices.push(response_direct::Choice {
+                    index,
+                    message: request::MessageAssistant::new(
+                        reasoning.text.clone(),
+                        "".to_string(),
+                        None,
+                        model_config.emit_reasoning_content,
+                        resolve_think_tags(model_config),
+                    ),
+                    logprobs: None,
+                    finish_reason: resolve_finish_reason(None),
+                });
+            }
+            log::debug!(
+                "Completion {} reasoning length exceeded, finishing without an answer.",
+                reasoning.id
+            );
+        }
+
+        self.record_usage(
+            &request.model,
+            model_config,
+            reasoning_prompt_tokens + answer_prompt_tokens,
+            reasoning.reasoning_tokens + answer_tokens,
+        );
+
+        let (id, created) =
+            self.merged_identity(&request, model_config, reasoning.id, reasoning.created);
+        Ok(ChatCompletion {
+            id,
+            object: reasoning.object,
+            created,
+            model: request.model.clone(),
+            choices,
+            usage: Usage {
+                prompt_tokens: reasoning.prompt_tokens,
+                completion_tokens: reasoning.reasoning_tokens + answer_tokens,
+                total_tokens: reasoning.prompt_tokens + reasoning.reasoning_tokens + answer_tokens,
+                prompt_tokens_details: PromptTokensDetails::combine(
+                    reasoning.prompt_tokens_details.as_ref(),
+                    answer_prompt_tokens_details.as_ref(),
+                ),
+                completion_tokens_details: Some(CompletionTokensDetails {
+                    reasoning_tokens: reasoning.reasoning_tokens,
+                }),
+            },
+            diagnostics: model_config
+                .reasoning_turn_messages
+                .then(|| response_direct::CompletionDiagnostics::from_turns(turns)),
+        })
+    }
+
+    /// Answers every choice with `message` straight from the reasoning phase.
+    fn surface_reasoning(
+        &self,
+        request: &request::ChatCompletionCreate,
+        model_config: &config::ModelConfig,
+        reasoning: ReasoningPhase,
+        message: request::MessageAssistant,
+        finish_reason: FinishReason,
+    ) -> ChatCompletion {
+        self.record_usage(
+            &request.model,
+            model_config,
+            reasoning.prompt_tokens,
+            reasoning.reasoning_tokens,
+        );
+
+        let (id, created) =
+            self.merged_identity(request, model_config, reasoning.id, reasoning.created);
+        ChatCompletion {
+            id,
+            object: reasoning.object,
+            created,
+            model: request.model.clone(),
+            choices: (0..resolve_choice_count(request))
+                .map(|index| response_direct::Choice {
+                    index,
+                    message: message.clone(),
+                    logprobs: None,
+                    finish_reason,
+                })
+                .collect(),
+            usage: Usage {
+                prompt_tokens: reasoning.prompt_tokens,
+                completion_tokens: reasoning.reasoning_tokens,
+                total_tokens: reasoning.prompt_tokens + reasoning.reasoning_tokens,
+                prompt_tokens_details: reasoning.prompt_tokens_details,
+                completion_tokens_details: Some(CompletionTokensDetails {
+                    reasoning_tokens: reasoning.reasoning_tokens,
+                }),
+            },
+            diagnostics: None,
+        }
+    }
+
+    async fn run_stream_completion(
+        &self,
+        request: request::ChatCompletionCreate,
+        model_config: &config::ModelConfig,
+        sender: Sender<Result<Bytes, ReasonerError>>,
+    ) -> Result<(), ReasonerError> {
+        let request = drop_empty_assistant_messages(request, model_config);
+        let request = strip_prior_reasoning(request, model_config);
+        validate_chat_request(&request)?;
+
+        if skips_reasoning(&request, model_config) {
+            return self
+                .stream_passthrough_completion(request, model_config, sender)
+                .await;
+        }
+        if model_config.native_reasoning {
+            return self
+                .stream_native_reasoning_completion(request, model_config, sender)
+                .await;
+        }
+        if resolve_single_pass(&request, model_config) {
+            return self
+                .stream_single_pass_completion(request, model_config, sender)
+                .await;
+        }
+
+        let retry_budget = RetryBudget::new(model_config.max_total_retries);
+        let upstream = self.create_upstream_with_budget(model_config, retry_budget.clone());
+
+        let upstream_stream_options = resolve_upstream_stream_options(model_config);
+        let estimate_usage = upstream_stream_options.is_none();
+        let mut reasoning_request = build_reasoning_request(request.clone(), model_config);
+        reasoning_request.stream_options = upstream_stream_options.clone();
+
+        let mut reasoning_text = "".to_string();
+        let mut prompt_tokens = 0;
+        let mut reasoning_tokens = 0;
+        let mut answer_tokens = 0;
+        let mut prompt_tokens_details = None;
+        let mut reasoning_finish_reason = FinishReason::Stop;
+        let emit_reasoning_content = model_config.emit_reasoning_content;
+        let think_tags = resolve_think_tags(model_config);
+        let choice_count = resolve_choice_count(&request);
+
+        let mut outgoing_chunk = response_stream::ChatCompletionChunk {
+            id: "".to_string(),
+            object: "chat.completion.chunk".to_string(),
+            created: 0,
+            model: request.model.clone(),
+            choices: vec![],
+            usage: None,
+        };
+
+        let cache_key = self.reasoning_cache_key(&request, model_config);
+        let prefilled_reasoning = match extract_client_reasoning(&request, model_config) {
+            Some(text) => {
+                log::debug!("Reusing client reasoning, skipping the reasoning phase");
+                Some(CachedReasoning {
+                    text,
+                    finish_reason: FinishReason::Stop,
+                    reasoning_tokens: 0,
+                })
+            }
+            None => self.cached_reasoning(cache_key),
+        };
+        if let Some(prefilled) = &prefilled_reasoning {
+            reasoning_text = prefilled.text.clone();
+            reasoning_finish_reason = prefilled.finish_reason;
+            reasoning_tokens = prefilled.reasoning_tokens;
+        }
+
+        // Reasoning stream
+        let mut reasoning_tool_call_deltas = vec![];
+        let mut reasoning_refusal = String::new();
+        let mut first_chunk = true;
+        let mut streamed_reasoning_tokens = 0;
+        let mut retried = false;
+        while prefilled_reasoning.is_none() {
+            let response = self
+                .open_stream(upstream.as_ref(), reasoning_request, &sender)
+                .await?;
+
+            let mut attempt_tokens = 0;
+            let mut attempt_content_deltas = 0;
+            let mut chunk_stream = self.chunk_stream(response, &sender);
+            while let Some(chunk) = chunk_stream.next().await? {
+                if outgoing_chunk.id.is_empty() {
+                    (outgoing_chunk.id, outgoing_chunk.created) =
+                        self.merged_identity(&request, model_config, chunk.id, chunk.created);
+                }
+
+                if let Some(usage) = chunk.usage {
+                    if !retried {
+                        prompt_tokens = usage.prompt_tokens;
+                        prompt_tokens_details = usage.prompt_tokens_details;
+                    }
+                    attempt_tokens = usage.completion_tokens;
+                }
+
+                let reasoning_choice = match chunk.choices.first() {
+                    Some(choice) => choice,
+                    None => continue,
+                };
+
+                if let Some(finisg_reason) = reasoning_choice.finish_reason {
+                    reasoning_finish_reason = finisg_reason;
+                }
+
+                if first_chunk {
+                    send_delta(
+                        &sender,
+                        outgoing_chunk.clone(),
+                        ChunkChoiceDelta::chunk_choice_delta_opening(
+                            emit_reasoning_content,
+                            think_tags.0,
+                        ),
+                        choice_count,
+                    )
+                    .await?;
+                    first_chunk = false;
+                }
+
+                if let Some(tool_calls) = &reasoning_choice.delta.tool_calls {
+                    reasoning_tool_call_deltas.push(tool_calls.clone());
+                }
+                if let Some(refusal) = &reasoning_choice.delta.refusal {
+                    reasoning_refusal.push_str(refusal);
+                }
+
+                if let Some(mut content) = reasoning_choice.delta.content.clone() {
+                    attempt_content_deltas += 1;
+                    if reasoning_text.is_empty() {
+                        let (think_start, _) = resolve_think_tags(model_config);
+                        content = strip_echoed_think_start(&content, think_start).to_string();
+                    }
+                    reasoning_text = format!("{}{}", reasoning_text, content);
+                    log::debug!(
+                        "Completion {} reasoning content delta: {:?}",
+                        outgoing_chunk.id,
+                        content
+                    );
+
+                    send_delta(
+                        &sender,
+                        outgoing_chunk.clone(),
+                        ChunkChoiceDelta::chunk_choice_delta_reasoning(
+                            content,
+                            emit_reasoning_content,
+                        ),
+                        choice_count,
+                    )
+                    .await?;
+
+                    if self.stream_reasoning_progress {
+                        streamed_reasoning_tokens += 1;
+                        send_comment(
+                            &sender,
+                            format!("reasoning_tokens={}", streamed_reasoning_tokens),
+                        )
+                        .await?;
+                    }
+                }
+            }
+            if estimate_usage && attempt_tokens == 0 {
+                attempt_tokens = attempt_content_deltas;
+            }
+            reasoning_tokens += attempt_tokens;
+
+            if retried || reasoning_finish_reason != FinishReason::Length {
+                break;
+            }
+            let Some(retry_budget) = resolve_reasoning_retry_budget(model_config, reasoning_tokens)
+            else {
+                break;
+            };
+
+            log::debug!(
+                "Completion {} reasoning length exceeded, retrying with {} more tokens",
+                outgoing_chunk.id,
+                retry_budget
+            );
+            reasoning_request = build_reasoning_retry_request(
+                request.clone(),
+                model_config,
+                &reasoning_text,
+                retry_budget,
+            );
+            reasoning_request.stream_options = upstream_stream_options.clone();
+            reasoning_finish_reason = FinishReason::Stop;
+            retried = true;
+        }
+
+        if prefilled_reasoning.is_none() && first_chunk {
+            send_delta(
+                &sender,
+                outgoing_chunk.clone(),
+                ChunkChoiceDelta::chunk_choice_delta_opening(emit_reasoning_content, think_tags.0),
+                choice_count,
+            )
+            .await?;
+        }
+
+        let surface_tool_calls = model_config.reasoning_tool_calls
+            == config::ReasoningToolCallBehavior::Surface
+            && !reasoning_tool_call_deltas.is_empty()
+            && reasoning_text.trim().is_empty();
+        let short_circuit_refusal =
+            !reasoning_refusal.is_empty() && model_config.short_circuit_reasoning_refusal;
+
+        if prefilled_reasoning.is_none() && !surface_tool_calls && !short_circuit_refusal {
+            self.cache_reasoning(
+                cache_key,
+                CachedReasoning {
+                    text: reasoning_text.clone(),
+                    finish_reason: reasoning_finish_reason,
+                    reasoning_tokens,
+                },
+            );
+        }
+
+        log::debug!(
+            "Completion {} reasoning usage: prompt_tokens: {}, reasoning_tokens: {}",
+            outgoing_chunk.id,
+            prompt_tokens,
+            reasoning_tokens
+        );
+
+        let mut billed_prompt_tokens = prompt_tokens;
+
+        // Answer stream
+        let remaining_tokens = calculate_remaining_tokens(
+            resolve_max_tokens(&request, model_config),
+            reasoning_tokens,
+        );
+        if surface_tool_calls {
+            log::debug!(
+                "Completion {} reasoning returned only tool calls, surfacing them",
+                outgoing_chunk.id
+            );
+            send_delta_thinking_end(
+                &sender,
+                &outgoing_chunk,
+                choice_count,
+                emit_reasoning_content,
+                think_tags.1,
+            )
+            .await?;
+            for tool_calls in reasoning_tool_call_deltas {
+                send_delta(
+                    &sender,
+                    outgoing_chunk.clone(),
+                    ChunkChoiceDelta {
+                        tool_calls: Some(tool_calls),
+                        ..Default::default()
+                    },
+                    choice_count,
+                )
+                .await?;
+            }
+
+            outgoing_chunk.choices = (0..choice_count)
+                .map(|index| response_stream::ChunkChoice {
+                    index,
+                    delta: ChunkChoiceDelta::default(),
+                    logprobs: None,
+                    finish_reason: Some(FinishReason::ToolCalls),
+                })
+                .collect();
+            send_chunk(&sender, &outgoing_chunk).await?;
+        } else if short_circuit_refusal {
+            log::debug!(
+                "Completion {} reasoning was refused, skipping the answer",
+                outgoing_chunk.id
+            );
+            send_delta_thinking_end(
+                &sender,
+                &outgoing_chunk,
+                choice_count,
+                emit_reasoning_content,
+                think_tags.1,
+            )
+            .await?;
+            outgoing_chunk.choices = (0..choice_count)
+                .map(|index| response_stream::ChunkChoice {
+                    index,
+                    delta: ChunkChoiceDelta {
+                        refusal: Some(reasoning_refusal.clone()),
+                        ..Default::default()
+                    },
+                    logprobs: None,
+                    finish_reason: Some(FinishReason::Stop),
+                })
+                .collect();
+            send_chunk(&sender, &outgoing_chunk).await?;
+        } else if remaining_tokens > 0
+            && !should_return_length(reasoning_finish_reason, model_config)
+        {
+            let synthesized_answer =
+                synthesize_answer(model_config, &reasoning_text, reasoning_finish_reason);
+            if let FinishReason::Length = reasoning_finish_reason {
+                reasoning_text = format!(
+                    "{}...\n\n{}\n",
+                    reasoning_text,
+                    resolve_reasoning_cutoff_stub(model_config)
+                );
+                send_delta(
+                    &sender,
+                    outgoing_chunk.clone(),
+                    ChunkChoiceDelta::chunk_choice_delta_reasoning(
+                        format!("...\n\n{}\n", resolve_reasoning_cutoff_stub(model_config)),
+                        emit_reasoning_content,
+                    ),
+                    choice_count,
+                )
+                .await?;
+            }
+
+            if prefilled_reasoning.is_none() {
+                send_delta_thinking_end(
+                    &sender,
+                    &outgoing_chunk,
+                    choice_count,
+                    emit_reasoning_content,
+                    think_tags.1,
+                )
+                .await?;
+            }
+
+            let mut answer_request = build_answer_request(
+                request.clone(),
+                model_config,
+                &reasoning_text,
+                remaining_tokens,
+            );
+            answer_request.stream_options = upstream_stream_options.clone();
+
+            let answer_started = Instant::now();
+            let response = self
+                .open_stream(
+                    self.answer_upstream(&upstream, model_config, &retry_budget)
+                        .as_ref(),
+                    answer_request,
+                    &sender,
+                )
+                .await?;
+
+            let max_answer_chars = resolve_max_answer_chars(&request, model_config);
+            let mut stop_trimmer = StopTrimmer::new(resolve_answer_stops(&request, model_config));
+            let mut think_tag_stripper =
+                ThinkTagStripper::new(resolve_leaked_think_tags(model_config));
+            let mut answer_chars: HashMap<i32, usize> = HashMap::new();
+            let mut truncated_choices: HashSet<i32> = HashSet::new();
+
+            let mut first_answer_chunk = true;
+            let mut finished_choices: HashSet<i32> = HashSet::new();
+            let mut answered_choices: HashSet<i32> = HashSet::new();
+            let mut answer_content_deltas = 0;
+            let mut forwarded_answer_deltas = 0;
+            let mut chunk_stream = self.chunk_stream(response, &sender);
+            while let Some(chunk) = chunk_stream.next().await? {
+                if let Some(usage) = chunk.usage {
+                    if prefilled_reasoning.is_some() {
+                        prompt_tokens = usage.prompt_tokens;
+                    }
+                    billed_prompt_tokens += usage.prompt_tokens;
+                    answer_tokens = usage.completion_tokens;
+                    prompt_tokens_details = PromptTokensDetails::combine(
+                        prompt_tokens_details.as_ref(),
+                        usage.prompt_tokens_details.as_ref(),
+                    );
+                }
+
+                if first_answer_chunk && prefilled_reasoning.is_some() {
+                    (outgoing_chunk.id, outgoing_chunk.created) = self.merged_identity(
+                        &request,
+                        model_config,
+                        chunk.id.clone(),
+                        chunk.created,
+                    );
+                    send_prefilled_reasoning(
+                        &sender,
+                        &outgoing_chunk,
+                        &reasoning_text,
+                        choice_count,
+                        emit_reasoning_content,
+                        think_tags,
+                    )
+                    .await?;
+                }
+                first_answer_chunk = false;
+
+                let mut answer_choices = vec![];
+                for mut answer_choice in chunk.choices {
+                    if truncated_choices.contains(&answer_choice.index) {
+                        continue;
+                    }
+                    if answer_choice.finish_reason.is_some() {
+                        finished_choices.insert(answer_choice.index);
+                    }
+                    think_tag_stripper.apply(&mut answer_choice);
+                    if let Some(stop_trimmer) = stop_trimmer.as_mut() {
+                        stop_trimmer.apply(&mut answer_choice);
+                    }
+                    if answer_choice
+                        .delta
+                        .content
+                        .as_ref()
+                        .is_some_and(|content| !content.trim().is_empty())
+                        || answer_choice.delta.tool_calls.is_some()
+                        || answer_choice.delta.refusal.is_some()
+                    {
+                        answered_choices.insert(answer_choice.index);
+                    }
+                    if answer_choice.finish_reason.is_some()
+                        && !answered_choices.contains(&answer_choice.index)
+                        && let Some(synthesized_answer) = &synthesized_answer
+                    {
+                        answer_choice.delta.content = Some(synthesized_answer.clone());
+                    }
+
+                    if let Some(content) = answer_choice.delta.content.as_mut() {
+                        answer_content_deltas += 1;
+                        log::debug!(
+                            "Completion {} answer {} content delta: {:?}",
+                            outgoing_chunk.id,
+                            answer_choice.index,
+                            content
+                        );
+
+                        if let Some(max_chars) = max_answer_chars {
+                            let choice_chars = answer_chars.entry(answer_choice.index).or_default();
+                            let (truncated, was_truncated) =
+                                truncate_to_chars(content, max_chars.saturating_sub(*choice_chars));
+                            if was_truncated {
+                                *content = truncated.to_string();
+                                answer_choice.finish_reason = Some(FinishReason::Length);
+                                truncated_choices.insert(answer_choice.index);
+                                log::debug!(
+                                    "Completion {} answer {} truncated at {} chars",
+                                    outgoing_chunk.id,
+                                    answer_choice.index,
+                                    max_chars
+                                );
+                            }
+                            *choice_chars += content.chars().count();
+                        }
+                        if !content.is_empty() {
+                            forwarded_answer_deltas += 1;
+                        }
+                    }
+                    answer_choices.push(answer_choice);
+                }
+
+                if answer_choices.is_empty() {
+                    continue;
+                }
+                outgoing_chunk.choices = answer_choices;
+                send_chunk(&sender, &outgoing_chunk).await?;
+
+                if truncated_choices.len() >= choice_count as usize {
+                    break;
+                }
+            }
+
+            if first_answer_chunk && prefilled_reasoning.is_some() {
+                (outgoing_chunk.id, outgoing_chunk.created) =
+                    self.merged_identity(&request, model_config, "".to_string(), 0);
+                send_prefilled_reasoning(
+                    &sender,
+                    &outgoing_chunk,
+                    &reasoning_text,
+                    choice_count,
+                    emit_reasoning_content,
+                    think_tags,
+                )
+                .await?;
+            }
+
+            // Upstream may end the stream without content or finish reasons
+            let unfinished_choices: Vec<_> = (0..choice_count)
+                .filter(|index| {
+                    !finished_choices.contains(index) && !truncated_choices.contains(index)
+                })
+                .map(|index| {
+                    let mut content = stop_trimmer
+                        .as_mut()
+                        .map(|stop_trimmer| stop_trimmer.finish(index))
+                        .unwrap_or_default();
+                    content.push_str(&think_tag_stripper.finish(index));
+                    if content.trim().is_empty()
+                        && !answered_choices.contains(&index)
+                        && let Some(synthesized_answer) = &synthesized_answer
+                    {
+                        content = synthesized_answer.clone();
+                    }
+                    response_stream::ChunkChoice {
+                        index,
+                        delta: ChunkChoiceDelta {
+                            content: Some(content),
+                            ..Default::default()
+                        },
+                        logprobs: None,
+                        finish_reason: Some(FinishReason::Stop),
+                    }
+                })
+                .collect();
+            if !unfinished_choices.is_empty() {
+                outgoing_chunk.choices = unfinished_choices;
+                send_chunk(&sender, &outgoing_chunk).await?;
+            }
+
+            if estimate_usage && answer_tokens == 0 {
+                answer_tokens = answer_content_deltas;
+            }
+            if !truncated_choices.is_empty() {
+                answer_tokens =
+                    estimate_truncated_answer_tokens(forwarded_answer_deltas, answer_tokens);
+            }
+
+            log::debug!(
+                "Completion {} answer usage: answer_tokens: {}",
+                outgoing_chunk.id,
+                answer_tokens
+            );
+
+            let throughput_tokens = if answer_tokens > 0 {
+                answer_tokens
+            } else {
+                answer_content_deltas
+            };
+            let elapsed = answer_started.elapsed();
+            let tokens_per_second = calculate_tokens_per_second(throughput_tokens, elapsed);
+            log::debug!(
+                "Completion {} answer throughput: {:.1} tokens/s over {}ms",
+                outgoing_chunk.id,
+                tokens_per_second,
+                elapsed.as_millis()
+            );
+            if self.stream_diagnostics {
+                let diagnostics = serde_json::json!({
+                    "id": outgoing_chunk.id,
+                    "object": "chat.completion.diagnostics",
+                    "diagnostics": {
+                        "answer_tokens": throughput_tokens,
+                        "answer_elapsed_ms": elapsed.as_millis() as u64,
+                        "tokens_per_second": tokens_per_second,
+                        "estimated_cost": estimate_cost(
+                            model_config,
+                            billed_prompt_tokens,
+                            reasoning_tokens + answer_tokens,
+                        ),
+                    },
+                });
+                send_data(&sender, diagnostics.to_string()).await?;
+            }
+        } else {
+            send_delta_thinking_end(
+                &sender,
+                &outgoing_chunk,
+                choice_count,
+                emit_reasoning_content,
+                think_tags.1,
+            )
+            .await?;
+
+            outgoing_chunk.choices = (0..choice_count)
+                .map(|index| response_stream::ChunkChoice {
+                    index,
+                    delta: ChunkChoiceDelta::chunk_choice_delta_empty(),
+                    logprobs: None,
+                    finish_reason: Some(resolve_finish_reason(None)),
+                })
+                .collect();
+            log::debug!(
+                "Completion {} reasoning length exceeded, finishing without an answer.",
+                outgoing_chunk.id
+            );
+            send_chunk(&sender, &outgoing_chunk).await?;
+        }
+
+        self.record_usage(
+            &request.model,
+            model_config,
+            billed_prompt_tokens,
+            reasoning_tokens + answer_tokens,
+        );
+
+        if let Some(stream_options) = request.stream_options
+            && stream_options.include_usage.unwrap_or(false)
+        {
+            outgoing_chunk.choices = vec![];
+            outgoing_chunk.usage = Some(Usage {
+                prompt_tokens,
+                completion_tokens: reasoning_tokens + answer_tokens,
+                total_tokens: prompt_tokens + reasoning_tokens + answer_tokens,
+                prompt_tokens_details,
+                completion_tokens_details: Some(CompletionTokensDetails { reasoning_tokens }),
+            });
+            send_chunk(&sender, &outgoing_chunk).await?;
+        }
+
+        self.send_done(&sender).await?;
+
+        Ok(())
     }
 
-    pub(crate) fn create_llm_client(&self, model_config: &config::ModelConfig) -> Box<dyn LLMClientTrait> {
-        Box::new(LLMClient::new(
-            self.http_client.clone(),
-            &model_config.api_url,
-            &model_config.api_key,
-            &model_config.extra,
-        ))
+    async fn request_answer(
+        &self,
+        upstream: &dyn Upstream,
+        request: &request::ChatCompletionCreate,
+        model_config: &config::ModelConfig,
+        reasoning_text: &str,
+        max_tokens: i32,
+    ) -> Result<ChatCompletion, ReasonerError> {
+        let answer_request =
+            build_answer_request(request.clone(), model_config, reasoning_text, max_tokens);
+
+        let answer_response = upstream.complete(answer_request).await?;
+        if answer_response.choices.is_empty() {
+            return Err(ReasonerError::api("error: no answer response".to_string()));
+        }
+        Ok(answer_response)
     }
 
-    pub async fn create_completion(
+    // Retries empty answers and answers whose average token logprob falls below
+    // the configured gate. Both only apply to non-streaming completions, and the
+    // confidence gate only to those that requested logprobs.
+    async fn request_gated_answer(
         &self,
-        request: request::ChatCompletionCreate,
+        upstream: &dyn Upstream,
+        request: &request::ChatCompletionCreate,
         model_config: &config::ModelConfig,
+        reasoning_text: &str,
+        max_tokens: i32,
     ) -> Result<ChatCompletion, ReasonerError> {
-        validate_chat_request(&request)?;
+        let mut answer_response = self
+            .request_answer(upstream, request, model_config, reasoning_text, max_tokens)
+            .await?;
+
+        let mut discarded_tokens = 0;
+        for retry in 1..=model_config.retry_empty_answer {
+            if !is_empty_answer(&answer_response) {
+                break;
+            }
+            log::debug!(
+                "Completion {} answer is empty, retry {} of {}",
+                answer_response.id,
+                retry,
+                model_config.retry_empty_answer
+            );
+            discarded_tokens += answer_response.usage.completion_tokens;
+            answer_response = self
+                .request_answer(upstream, request, model_config, reasoning_text, max_tokens)
+                .await?;
+        }
 
-        let client = self.create_llm_client(model_config);
+        if let Some(gate) = &model_config.confidence_gate
+            && request.logprobs.unwrap_or(false)
+        {
+            for retry in 1..=gate.max_retries {
+                if !is_low_confidence(&answer_response, gate) {
+                    break;
+                }
+                log::debug!(
+                    "Completion {} answer below confidence gate, retry {} of {}",
+                    answer_response.id,
+                    retry,
+                    gate.max_retries
+                );
+                discarded_tokens += answer_response.usage.completion_tokens;
+                answer_response = self
+                    .request_answer(upstream, request, model_config, reasoning_text, max_tokens)
+                    .await?;
+            }
+        }
+        answer_response.usage.completion_tokens += discarded_tokens;
+        answer_response.usage.total_tokens += discarded_tokens;
 
-        let reasoning_request = build_reasoning_request(request.clone(), model_config);
+        Ok(answer_response)
+    }
 
-        let response = client
-            .request_chat_completion(reasoning_request, mime::APPLICATION_JSON)
-            .await?;
+    // Races the reasoning phase against an answer with empty reasoning. Reasoning
+    // that completes first replaces the speculative answer only when it changes
+    // it. A speculative answer that completes first cancels the reasoning, whose
+    // tokens are then never reported by the upstream.
+    async fn race_speculative_answer(
+        &self,
+        upstream: &dyn Upstream,
+        answer_upstream: &dyn Upstream,
+        request: &request::ChatCompletionCreate,
+        model_config: &config::ModelConfig,
+    ) -> Result<Speculation, ReasonerError> {
+        let reasoning = self.request_reasoning(upstream, request, model_config);
+        let speculative_answer = self.request_answer(
+            answer_upstream,
+            request,
+            model_config,
+            "",
+            calculate_remaining_tokens(resolve_max_tokens(request, model_config), 0),
+        );
+        tokio::pin!(reasoning, speculative_answer);
+
+        tokio::select! {
+            biased;
+            reasoning = &mut reasoning => {
+                let reasoning = reasoning?;
+                match speculative_answer.await {
+                    Ok(answer_response) if reasoning_confirms_answer(&reasoning.text, &answer_response) => {
+                        log::debug!("Reasoning finished first and confirms the speculative answer");
+                        Ok(Speculation::Confirmed(reasoning, answer_response))
+                    }
+                    Ok(answer_response) => {
+                        log::debug!("Reasoning finished first and changes the speculative answer");
+                        Ok(Speculation::Reasoning(reasoning, answer_response.usage.completion_tokens))
+                    }
+                    Err(e) => {
+                        log::warn!("Speculative answer failed: {e}");
+                        Ok(Speculation::Reasoning(reasoning, 0))
+                    }
+                }
+            }
+            answer = &mut speculative_answer => match answer {
+                Ok(answer_response) => {
+                    log::debug!("Speculative answer finished first, cancelling reasoning");
+                    Ok(Speculation::Answer(answer_response))
+                }
+                Err(e) => {
+                    log::warn!("Speculative answer failed, waiting for reasoning: {e}");
+                    Ok(Speculation::Reasoning(reasoning.await?, 0))
+                }
+            },
+        }
+    }
+
+    async fn request_reasoning(
+        &self,
+        upstream: &dyn Upstream,
+        request: &request::ChatCompletionCreate,
+        model_config: &config::ModelConfig,
+    ) -> Result<ReasoningPhase, ReasonerError> {
+        let reasoning_request = build_reasoning_request(request.clone(), model_config);
 
-        let reasoning_response = response.json::<response_direct::ChatCompletion>().await?;
+        let mut reasoning_response = upstream.complete(reasoning_request.clone()).await?;
+        if model_config.retry_blank_reasoning
+            && is_blank_reasoning(&reasoning_response, model_config)
+        {
+            log::debug!(
+                "Completion {} reasoning is blank, retrying once",
+                reasoning_response.id
+            );
+            // The upstream bills the discarded attempt too
+            let discarded_usage = reasoning_response.usage;
+            reasoning_response = upstream.complete(reasoning_request).await?;
+            reasoning_response.usage.prompt_tokens += discarded_usage.prompt_tokens;
+            reasoning_response.usage.completion_tokens += discarded_usage.completion_tokens;
+            reasoning_response.usage.total_tokens += discarded_usage.total_tokens;
+        }
         let reasoning_choice = match reasoning_response.choices.first() {
             Some(choice) => choice,
             None => {
-                return Err(ReasonerError::ApiError(
+                return Err(ReasonerError::api(
                     "error: no reasoning response".to_string(),
                 ));
             }
         };
         let prompt_tokens = reasoning_response.usage.prompt_tokens;
-        let reasoning_tokens = reasoning_response.usage.completion_tokens;
+        let prompt_tokens_details = reasoning_response.usage.prompt_tokens_details.clone();
+        let mut reasoning_tokens = reasoning_response.usage.completion_tokens;
+        let mut reasoning_finish_reason = reasoning_choice.finish_reason;
         let mut reasoning_text: String = match &reasoning_choice.message.content {
-            Some(content) => content.trim().to_string(),
+            Some(content) => {
+                strip_echoed_think_start(content, resolve_think_tags(model_config).0).to_string()
+            }
             None => "".to_string(),
         };
+        let tool_calls = reasoning_choice
+            .message
+            .tool_calls
+            .clone()
+            .filter(|tool_calls| !tool_calls.is_empty() && reasoning_text.trim().is_empty());
+        let refusal = reasoning_choice.message.refusal.clone();
+
+        if let FinishReason::Length = reasoning_finish_reason
+            && let Some(retry_budget) =
+                resolve_reasoning_retry_budget(model_config, reasoning_tokens)
+        {
+            log::debug!(
+                "Completion {} reasoning length exceeded, retrying with {} more tokens",
+                reasoning_response.id,
+                retry_budget
+            );
+            let retry_request = build_reasoning_retry_request(
+                request.clone(),
+                model_config,
+                &reasoning_text,
+                retry_budget,
+            );
+
+            let retry_response = upstream.complete(retry_request).await?;
+            let retry_choice = match retry_response.choices.first() {
+                Some(choice) => choice,
+                None => {
+                    return Err(ReasonerError::api(
+                        "error: no reasoning retry response".to_string(),
+                    ));
+                }
+            };
+            if let Some(content) = &retry_choice.message.content {
+                reasoning_text.push_str(content);
+            }
+            reasoning_tokens += retry_response.usage.completion_tokens;
+            reasoning_finish_reason = retry_choice.finish_reason;
+        }
+        let reasoning_text = reasoning_text.trim().to_string();
 
         log::debug!(
             "Completion {} reasoning text: {}",
@@ -83,114 +1686,174 @@ impl ReasoningService {
             reasoning_tokens
         );
 
-        let answer_text: String;
-        let answer_tool_calls: Option<Vec<serde_json::Value>>;
-        let answer_tokens: i32;
-        let finish_reason: FinishReason;
-        let remaining_tokens = calculate_remaining_tokens(request.max_tokens, reasoning_tokens);
-        if remaining_tokens > 0 {
-            if let FinishReason::Length = reasoning_choice.finish_reason {
-                reasoning_text = format!(
-                    "{}...\n\n{}\n",
-                    reasoning_text,
-                    consts::REASONING_CUTOFF_STUB
-                );
+        Ok(ReasoningPhase {
+            id: reasoning_response.id,
+            object: reasoning_response.object,
+            created: reasoning_response.created,
+            prompt_tokens,
+            reasoning_tokens,
+            finish_reason: reasoning_finish_reason,
+            text: reasoning_text,
+            tool_calls,
+            refusal,
+            prompt_tokens_details,
+        })
+    }
+
+    async fn create_passthrough_completion(
+        &self,
+        request: request::ChatCompletionCreate,
+        model_config: &config::ModelConfig,
+    ) -> Result<ChatCompletion, ReasonerError> {
+        let upstream = self.create_upstream(model_config);
+
+        let passthrough_request = build_passthrough_request(request.clone(), model_config);
+        let mut completion = upstream.complete(passthrough_request).await?;
+        completion.model = request.model.clone();
+        if let Some(deterministic_id) = resolve_deterministic_id(&request, model_config) {
+            completion.id = deterministic_id;
+        }
+
+        log::debug!(
+            "Completion {} passthrough usage: prompt_tokens: {}, completion_tokens: {}",
+            completion.id,
+            completion.usage.prompt_tokens,
+            completion.usage.completion_tokens
+        );
+        self.record_usage(
+            &request.model,
+            model_config,
+            completion.usage.prompt_tokens,
+            completion.usage.completion_tokens,
+        );
+
+        Ok(completion)
+    }
+
+    async fn stream_passthrough_completion(
+        &self,
+        request: request::ChatCompletionCreate,
+        model_config: &config::ModelConfig,
+        sender: Sender<Result<Bytes, ReasonerError>>,
+    ) -> Result<(), ReasonerError> {
+        let upstream = self.create_upstream(model_config);
+
+        let mut passthrough_request = build_passthrough_request(request.clone(), model_config);
+        passthrough_request.stream_options = resolve_upstream_stream_options(model_config);
+
+        let include_usage = request
+            .stream_options
+            .as_ref()
+            .and_then(|stream_options| stream_options.include_usage)
+            .unwrap_or(false);
+        let mut identity = None;
+        let mut usage = None;
+
+        let response = self
+            .open_stream(upstream.as_ref(), passthrough_request, &sender)
+            .await?;
+        let mut chunk_stream = self.chunk_stream(response, &sender);
+        while let Some(mut chunk) = chunk_stream.next().await? {
+            if chunk.usage.is_some() {
+                usage = chunk.usage.clone();
+            }
+            if !include_usage {
+                chunk.usage = None;
+                if chunk.choices.is_empty() {
+                    continue;
+                }
             }
 
-            let answer_request = build_answer_request(
-                request.clone(),
+            let (id, created) = identity.get_or_insert_with(|| {
+                self.merged_identity(&request, model_config, chunk.id.clone(), chunk.created)
+            });
+            chunk.id = id.clone();
+            chunk.created = *created;
+            chunk.model = request.model.clone();
+            send_chunk(&sender, &chunk).await?;
+        }
+
+        if let Some(usage) = &usage {
+            self.record_usage(
+                &request.model,
                 model_config,
-                &reasoning_text,
-                remaining_tokens,
+                usage.prompt_tokens,
+                usage.completion_tokens,
             );
+        }
 
-            let response = client
-                .request_chat_completion(answer_request, mime::APPLICATION_JSON)
-                .await?;
+        self.send_done(&sender).await?;
 
-            let answer_response = response.json::<response_direct::ChatCompletion>().await?;
-            let answer_choice = match answer_response.choices.first() {
-                Some(choice) => choice,
-                None => {
-                    return Err(ReasonerError::ApiError(
-                        "error: no answer response".to_string(),
-                    ));
-                }
-            };
+        Ok(())
+    }
 
-            answer_text = match &answer_choice.message.content {
-                Some(content) => content.trim().to_string(),
-                None => "".to_string(),
-            };
-            answer_tool_calls = answer_choice.message.tool_calls.clone();
-            answer_tokens = answer_response.usage.completion_tokens;
-            finish_reason = answer_choice.finish_reason;
+    async fn create_single_pass_completion(
+        &self,
+        request: request::ChatCompletionCreate,
+        model_config: &config::ModelConfig,
+    ) -> Result<ChatCompletion, ReasonerError> {
+        let upstream = self.create_upstream(model_config);
 
-            log::debug!(
-                "Completion {} answer text: {}",
-                reasoning_response.id,
-                answer_text
-            );
-            log::debug!(
-                "Completion {} answer usage: answer_tokens: {}",
-                reasoning_response.id,
-                answer_tokens
-            );
-        } else {
-            answer_text = "".to_string();
-            answer_tool_calls = None;
-            answer_tokens = 0;
-            finish_reason = FinishReason::Length;
-            log::debug!(
-                "Completion {} reasoning length exceeded, finishing without an answer.",
-                reasoning_response.id
+        let single_pass_request = build_single_pass_request(request.clone(), model_config);
+        let mut completion = upstream.complete(single_pass_request).await?;
+        completion.model = request.model.clone();
+        if let Some(deterministic_id) = resolve_deterministic_id(&request, model_config) {
+            completion.id = deterministic_id;
+        }
+        let (_, think_end) = resolve_think_tags(model_config);
+        let mut turns = vec![];
+        for choice in completion.choices.iter_mut() {
+            let content = choice.message.content.take().unwrap_or_default();
+            let (reasoning_text, answer_text) = split_reasoning_and_answer(&content, think_end);
+            let refusal = choice.message.refusal.take();
+            if model_config.reasoning_turn_messages {
+                turns.push((reasoning_text.trim().to_string(), answer_text.clone()));
+            }
+            choice.message = request::MessageAssistant::new(
+                reasoning_text,
+                answer_text,
+                choice.message.tool_calls.take(),
+                model_config.emit_reasoning_content,
+                resolve_think_tags(model_config),
             );
+            choice.message.refusal = refusal;
+        }
+        if model_config.reasoning_turn_messages {
+            completion.diagnostics =
+                Some(response_direct::CompletionDiagnostics::from_turns(turns));
         }
 
-        Ok(ChatCompletion {
-            id: reasoning_response.id,
-            object: reasoning_response.object,
-            created: reasoning_response.created,
-            model: request.model.clone(),
-            choices: vec![response_direct::Choice {
-                index: 0,
-                message: request::MessageAssistant::new(
-                    reasoning_text,
-                    answer_text,
-                    answer_tool_calls,
-                ),
-                logprobs: None,
-                finish_reason: finish_reason,
-            }],
-            usage: Usage {
-                prompt_tokens: prompt_tokens,
-                completion_tokens: reasoning_tokens + answer_tokens,
-                total_tokens: prompt_tokens + reasoning_tokens + answer_tokens,
-            },
-        })
+        log::debug!(
+            "Completion {} single pass usage: prompt_tokens: {}, completion_tokens: {}",
+            completion.id,
+            completion.usage.prompt_tokens,
+            completion.usage.completion_tokens
+        );
+        self.record_usage(
+            &request.model,
+            model_config,
+            completion.usage.prompt_tokens,
+            completion.usage.completion_tokens,
+        );
+
+        Ok(completion)
     }
 
-    pub async fn stream_completion(
+    async fn stream_single_pass_completion(
         &self,
         request: request::ChatCompletionCreate,
         model_config: &config::ModelConfig,
         sender: Sender<Result<Bytes, ReasonerError>>,
     ) -> Result<(), ReasonerError> {
-        validate_chat_request(&request)?;
-
-        let client = self.create_llm_client(model_config);
+        let upstream = self.create_upstream(model_config);
 
-        let mut reasoning_request = build_reasoning_request(request.clone(), model_config);
-        reasoning_request.stream_options = Some(request::StreamOptions {
-            include_usage: Some(true),
-        });
-
-        let mut reasoning_text = "".to_string();
-        let mut prompt_tokens = 0;
-        let mut reasoning_tokens = 0;
-        let mut answer_tokens = 0;
-        let mut reasoning_finish_reason = FinishReason::Stop;
+        let mut single_pass_request = build_single_pass_request(request.clone(), model_config);
+        single_pass_request.stream_options = resolve_upstream_stream_options(model_config);
+        let estimate_usage = single_pass_request.stream_options.is_none();
 
+        let mut usage = None;
+        let mut content_deltas = 0;
+        let choice_count = resolve_choice_count(&request);
         let mut outgoing_chunk = response_stream::ChatCompletionChunk {
             id: "".to_string(),
             object: "chat.completion.chunk".to_string(),
@@ -200,228 +1863,308 @@ impl ReasoningService {
             usage: None,
         };
 
-        // Reasoning stream
-        let mut response = client
-            .request_chat_completion(reasoning_request, mime::TEXT_EVENT_STREAM)
+        let response = self
+            .open_stream(upstream.as_ref(), single_pass_request, &sender)
             .await?;
 
         let mut first_chunk = true;
-        let mut chunks_to_process: VecDeque<ChatCompletionChunk> = VecDeque::new();
-        loop {
-            if chunks_to_process.len() == 0 {
-                match extract_chunks_from_event(response.chunk().await)? {
-                    Some(chunks) => chunks_to_process.extend(chunks),
-                    None => break,
-                };
+        let mut chunk_stream = self.chunk_stream(response, &sender);
+        while let Some(chunk) = chunk_stream.next().await? {
+            if outgoing_chunk.id.is_empty() {
+                (outgoing_chunk.id, outgoing_chunk.created) =
+                    self.merged_identity(&request, model_config, chunk.id, chunk.created);
             }
-            let chunk = match chunks_to_process.pop_front() {
-                Some(chunk) => chunk,
-                None => continue,
-            };
 
-            outgoing_chunk.id = chunk.id.clone();
-            outgoing_chunk.created = chunk.created;
-
-            if let Some(usage) = chunk.usage {
-                prompt_tokens = usage.prompt_tokens;
-                reasoning_tokens = usage.completion_tokens;
+            if chunk.usage.is_some() {
+                usage = chunk.usage;
             }
 
-            let reasoning_choice = match chunk.choices.first() {
-                Some(choice) => choice,
-                None => continue,
-            };
-
-            if let Some(finisg_reason) = reasoning_choice.finish_reason {
-                reasoning_finish_reason = finisg_reason;
+            if chunk.choices.is_empty() {
+                continue;
             }
+            content_deltas += chunk
+                .choices
+                .iter()
+                .filter(|choice| choice.delta.content.is_some())
+                .count() as i32;
 
             if first_chunk {
                 send_delta(
                     &sender,
                     outgoing_chunk.clone(),
-                    ChunkChoiceDelta::chunk_choice_delta_opening(),
+                    ChunkChoiceDelta::chunk_choice_delta_opening(
+                        false,
+                        resolve_think_tags(model_config).0,
+                    ),
+                    choice_count,
                 )
                 .await?;
                 first_chunk = false;
             }
 
-            if let Some(content) = reasoning_choice.delta.content.clone() {
-                reasoning_text = format!("{}{}", reasoning_text, content);
-                log::debug!(
-                    "Completion {} reasoning content delta: {:?}",
-                    outgoing_chunk.id,
-                    content
-                );
+            outgoing_chunk.choices = chunk.choices;
+            send_chunk(&sender, &outgoing_chunk).await?;
+        }
 
-                send_delta(
-                    &sender,
-                    outgoing_chunk.clone(),
-                    ChunkChoiceDelta::chunk_choice_delta_reasoning(content),
-                )
-                .await?;
-            }
+        if estimate_usage && usage.is_none() {
+            usage = Some(Usage {
+                prompt_tokens: 0,
+                completion_tokens: content_deltas,
+                total_tokens: content_deltas,
+                prompt_tokens_details: None,
+                completion_tokens_details: None,
+            });
         }
 
-        log::debug!(
-            "Completion {} reasoning usage: prompt_tokens: {}, reasoning_tokens: {}",
-            outgoing_chunk.id,
-            prompt_tokens,
-            reasoning_tokens
-        );
+        if let Some(usage) = &usage {
+            self.record_usage(
+                &request.model,
+                model_config,
+                usage.prompt_tokens,
+                usage.completion_tokens,
+            );
+        }
 
-        // Answer stream
-        let remaining_tokens = calculate_remaining_tokens(request.max_tokens, reasoning_tokens);
-        if remaining_tokens > 0 {
-            if let FinishReason::Length = reasoning_finish_reason {
-                reasoning_text = format!(
-                    "{}...\n\n{}\n",
-                    reasoning_text,
-                    consts::REASONING_CUTOFF_STUB
-                );
-                send_delta(
-                    &sender,
-                    outgoing_chunk.clone(),
-                    ChunkChoiceDelta::chunk_choice_delta_reasoning(
-                        format!("...\n\n{}\n", consts::REASONING_CUTOFF_STUB).to_string(),
-                    ),
-                )
-                .await?;
-            }
+        if let Some(stream_options) = request.stream_options
+            && stream_options.include_usage.unwrap_or(false)
+        {
+            outgoing_chunk.choices = vec![];
+            outgoing_chunk.usage = usage;
+            send_chunk(&sender, &outgoing_chunk).await?;
+        }
 
-            send_delta_thinking_end(&sender, &outgoing_chunk).await?;
+        self.send_done(&sender).await?;
 
-            let mut answer_request = build_answer_request(
-                request.clone(),
-                model_config,
-                &reasoning_text,
-                remaining_tokens,
-            );
-            answer_request.stream_options = Some(request::StreamOptions {
-                include_usage: Some(true),
-            });
+        Ok(())
+    }
 
-            let mut response = client
-                .request_chat_completion(answer_request, mime::TEXT_EVENT_STREAM)
-                .await?;
+    async fn create_native_reasoning_completion(
+        &self,
+        request: request::ChatCompletionCreate,
+        model_config: &config::ModelConfig,
+    ) -> Result<ChatCompletion, ReasonerError> {
+        let upstream = self.create_upstream(model_config);
 
-            let mut chunks_to_process: VecDeque<ChatCompletionChunk> = VecDeque::new();
-            loop {
-                if chunks_to_process.len() == 0 {
-                    match extract_chunks_from_event(response.chunk().await)? {
-                        Some(chunks) => chunks_to_process.extend(chunks),
-                        None => break,
-                    };
-                }
-                let chunk = match chunks_to_process.pop_front() {
-                    Some(chunk) => chunk,
-                    None => continue,
-                };
+        let native_request = build_passthrough_request(request.clone(), model_config);
+        let mut completion = upstream.complete(native_request).await?;
+        completion.model = request.model.clone();
+        if let Some(deterministic_id) = resolve_deterministic_id(&request, model_config) {
+            completion.id = deterministic_id;
+        }
+        let think_tags = resolve_think_tags(model_config);
+        let mut turns = vec![];
+        for choice in completion.choices.iter_mut() {
+            let reasoning_text = choice.message.reasoning_content.take().unwrap_or_default();
+            let answer_text = choice.message.content.take().unwrap_or_default();
+            let refusal = choice.message.refusal.take();
+            if model_config.reasoning_turn_messages {
+                turns.push((reasoning_text.trim().to_string(), answer_text.clone()));
+            }
+            choice.message = request::MessageAssistant::new(
+                reasoning_text,
+                answer_text,
+                choice.message.tool_calls.take(),
+                model_config.emit_reasoning_content,
+                think_tags,
+            );
+            choice.message.refusal = refusal;
+        }
+        if model_config.reasoning_turn_messages {
+            completion.diagnostics =
+                Some(response_direct::CompletionDiagnostics::from_turns(turns));
+        }
 
-                if let Some(usage) = chunk.usage {
-                    answer_tokens = usage.completion_tokens;
-                }
+        log::debug!(
+            "Completion {} native reasoning usage: prompt_tokens: {}, completion_tokens: {}",
+            completion.id,
+            completion.usage.prompt_tokens,
+            completion.usage.completion_tokens
+        );
+        self.record_usage(
+            &request.model,
+            model_config,
+            completion.usage.prompt_tokens,
+            completion.usage.completion_tokens,
+        );
 
-                let answer_choice = match chunk.choices.first() {
-                    Some(choice) => choice,
-                    None => continue,
-                };
+        Ok(completion)
+    }
 
-                if let Some(content) = answer_choice.delta.content.clone() {
-                    log::debug!(
-                        "Completion {} answer content delta: {:?}",
-                        outgoing_chunk.id,
-                        content
-                    );
+    async fn stream_native_reasoning_completion(
+        &self,
+        request: request::ChatCompletionCreate,
+        model_config: &config::ModelConfig,
+        sender: Sender<Result<Bytes, ReasonerError>>,
+    ) -> Result<(), ReasonerError> {
+        let upstream = self.create_upstream(model_config);
+
+        let mut native_request = build_passthrough_request(request.clone(), model_config);
+        native_request.stream_options = resolve_upstream_stream_options(model_config);
+
+        let include_usage = request
+            .stream_options
+            .as_ref()
+            .and_then(|stream_options| stream_options.include_usage)
+            .unwrap_or(false);
+        let mut identity = None;
+        let mut usage = None;
+        let mut reasoning_choices: HashSet<i32> = HashSet::new();
+        let think_tags = resolve_think_tags(model_config);
+
+        let response = self
+            .open_stream(upstream.as_ref(), native_request, &sender)
+            .await?;
+        let mut chunk_stream = self.chunk_stream(response, &sender);
+        while let Some(mut chunk) = chunk_stream.next().await? {
+            if chunk.usage.is_some() {
+                usage = chunk.usage.clone();
+            }
+            if !include_usage {
+                chunk.usage = None;
+                if chunk.choices.is_empty() {
+                    continue;
                 }
-                outgoing_chunk.choices = vec![answer_choice.clone()];
-                send_chunk(&sender, &outgoing_chunk).await?;
             }
 
-            log::debug!(
-                "Completion {} answer usage: answer_tokens: {}",
-                outgoing_chunk.id,
-                answer_tokens
-            );
-        } else {
-            outgoing_chunk.choices = vec![response_stream::ChunkChoice {
-                index: 0,
-                delta: ChunkChoiceDelta::chunk_choice_delta_empty(),
-                logprobs: None,
-                finish_reason: Some(FinishReason::Length),
-            }];
-            log::debug!(
-                "Completion {} reasoning length exceeded, finishing without an answer.",
-                outgoing_chunk.id
-            );
-            send_chunk(&sender, &outgoing_chunk).await?;
+            if !model_config.emit_reasoning_content {
+                for choice in chunk.choices.iter_mut() {
+                    inline_native_reasoning(choice, &mut reasoning_choices, think_tags);
+                }
+            }
+            let (id, created) = identity.get_or_insert_with(|| {
+                self.merged_identity(&request, model_config, chunk.id.clone(), chunk.created)
+            });
+            chunk.id = id.clone();
+            chunk.created = *created;
+            chunk.model = request.model.clone();
+            send_chunk(&sender, &chunk).await?;
         }
 
-        if let Some(stream_options) = request.stream_options
-            && stream_options.include_usage.unwrap_or(false)
-        {
-            outgoing_chunk.choices = vec![];
-            outgoing_chunk.usage = Some(Usage {
-                prompt_tokens,
-                completion_tokens: reasoning_tokens + answer_tokens,
-                total_tokens: prompt_tokens + reasoning_tokens + answer_tokens,
-            });
-            send_chunk(&sender, &outgoing_chunk).await?;
+        if let Some(usage) = &usage {
+            self.record_usage(
+                &request.model,
+                model_config,
+                usage.prompt_tokens,
+                usage.completion_tokens,
+            );
         }
 
-        send_data(&sender, "[DONE]".into()).await?;
+        self.send_done(&sender).await?;
 
         Ok(())
     }
 }
 
-fn extract_chunks_from_event(
-    response_event: Result<Option<Bytes>, reqwest::Error>,
-) -> Result<Option<Vec<response_stream::ChatCompletionChunk>>, ReasonerError> {
-    let events = match response_event {
-        Ok(Some(chunk)) => chunk,
-        Ok(None) => {
-            log::debug!("extract_chunks_from_event: No more chunks");
-            return Ok(None);
+struct ChunkStream {
+    response: Box<dyn UpstreamStream>,
+    buffer: SseBuffer,
+    pending: VecDeque<ChatCompletionChunk>,
+    finished: bool,
+    idle_timeout: Option<Duration>,
+    keep_alive: Option<(Duration, Sender<Result<Bytes, ReasonerError>>)>,
+}
+
+impl ChunkStream {
+    async fn next(&mut self) -> Result<Option<ChatCompletionChunk>, ReasonerError> {
+        while self.pending.is_empty() && !self.finished {
+            let events = match self.next_bytes().await? {
+                Some(bytes) => {
+                    self.buffer.push(&bytes);
+                    std::iter::from_fn(|| self.buffer.next_event()).collect::<Vec<_>>()
+                }
+                None => {
+                    log::debug!("ChunkStream: No more chunks");
+                    self.finished = true;
+                    self.buffer.finish().into_iter().collect()
+                }
+            };
+            for event in events {
+                match parse_sse_event(&event)? {
+                    SseEvent::Chunk(chunk) => self.pending.push_back(chunk),
+                    SseEvent::Done => {
+                        self.finished = true;
+                        break;
+                    }
+                    SseEvent::Ignored => {}
+                }
+            }
         }
-        Err(e) => {
-            log::debug!("extract_chunks_from_event: Error reading events: {e}");
-            return Err(ReasonerError::NetworkError(e.to_string()));
+        Ok(self.pending.pop_front())
+    }
+
+    /// Keeps the client connection warm while upstream is silent, and gives up
+    /// once upstream stays silent for longer than the idle timeout.
+    async fn next_bytes(&mut self) -> Result<Option<Bytes>, ReasonerError> {
+        let idle_deadline = self.idle_timeout.map(|timeout| Instant::now() + timeout);
+        let keep_alive_interval = self.keep_alive.as_ref().map(|(interval, _)| *interval);
+
+        let bytes = self.response.next_bytes();
+        tokio::pin!(bytes);
+        loop {
+            let idle_remaining =
+                idle_deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            tokio::select! {
+                bytes = &mut bytes => return bytes,
+                _ = sleep_for(idle_remaining) => {
+                    return Err(ReasonerError::timeout(format!(
+                        "error: upstream stream was idle for more than {:?}",
+                        self.idle_timeout.unwrap_or_default()
+                    )));
+                }
+                _ = sleep_for(keep_alive_interval) => {
+                    if let Some((_, sender)) = &self.keep_alive {
+                        send_comment(sender, consts::SSE_KEEP_ALIVE_COMMENT.to_string()).await?;
+                    }
+                }
+            }
         }
-    };
+    }
+}
+
+async fn sleep_for(duration: Option<Duration>) {
+    match duration {
+        Some(duration) => tokio::time::sleep(duration).await,
+        None => std::future::pending().await,
+    }
+}
+
+enum SseEvent {
+    Chunk(ChatCompletionChunk),
+    Done,
+    Ignored,
+}
 
-    let text = match str::from_utf8(&events) {
-        Ok(text) => text.trim(),
+fn parse_sse_event(event: &[u8]) -> Result<SseEvent, ReasonerError> {
+    let text = match str::from_utf8(event) {
+        Ok(text) => text,
         Err(e) => {
-            log::debug!("extract_chunks_from_event: Error decoding events: {e}");
-            return Err(ReasonerError::ParseError(e.to_string()));
+            log::debug!("parse_sse_event: Error decoding event: {e}");
+            return Err(ReasonerError::parse(e.to_string()).with_source(e));
         }
     };
 
-    let mut chunks = vec![];
-    for text_chunk in text.split("\n\n") {
-        if !text_chunk.starts_with("data: ") {
-            log::debug!("extract_chunks_from_event: Skipping chunk: {text_chunk}");
-            continue;
-        }
+    let data = text
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join("\n");
+    if data.is_empty() {
+        log::debug!("parse_sse_event: Skipping event: {text}");
+        return Ok(SseEvent::Ignored);
+    }
+    if data == consts::SSE_DONE_SENTINEL {
+        log::debug!("parse_sse_event: Final chunk received");
+        return Ok(SseEvent::Done);
+    }
 
-        let text_chunk = &text_chunk["data:".len()..].trim();
-        if text_chunk.contains("[DONE]") {
-            log::debug!("extract_chunks_from_event: Final chunk received");
-            return Ok(None);
+    match serde_json::from_str::<ChatCompletionChunk>(&data) {
+        Ok(chunk) => Ok(SseEvent::Chunk(chunk)),
+        Err(e) => {
+            log::debug!("parse_sse_event: Error parsing chunk: {e}");
+            Err(ReasonerError::parse(e.to_string()).with_source(e))
         }
-
-        let chunk = match serde_json::from_str::<response_stream::ChatCompletionChunk>(text_chunk) {
-            Ok(json) => json,
-            Err(e) => {
-                log::debug!("extract_chunks_from_event: Error parsing chunk: {e}");
-                return Err(ReasonerError::ParseError(e.to_string()));
-            }
-        };
-        chunks.push(chunk);
     }
-
-    Ok(Some(chunks))
 }
 
 async fn send_data(
@@ -431,9 +2174,19 @@ async fn send_data(
     let event_data = format!("data: {}\n\n", data);
     if let Err(e) = sender.send(Ok(event_data.into())).await {
         log::warn!("failed to send message: {:?}", e.0);
-        return Err(ReasonerError::NetworkError(
-            "failed to send message".to_string(),
-        ));
+        return Err(ReasonerError::network("failed to send message".to_string()));
+    }
+    Ok(())
+}
+
+async fn send_comment(
+    sender: &Sender<Result<Bytes, ReasonerError>>,
+    comment: String,
+) -> Result<(), ReasonerError> {
+    let event_comment = format!(": {}\n\n", comment);
+    if let Err(e) = sender.send(Ok(event_comment.into())).await {
+        log::warn!("failed to send comment: {:?}", e.0);
+        return Err(ReasonerError::network("failed to send comment".to_string()));
     }
     Ok(())
 }
@@ -445,40 +2198,175 @@ async fn send_chunk(
     send_data(sender, serde_json::to_string(chunk).unwrap()).await
 }
 
-async fn send_delta(
+/// Relays one stream attempt to the client, reporting whether any data frame
+/// got through.
+async fn forward_stream(
+    mut receiver: mpsc::Receiver<Result<Bytes, ReasonerError>>,
     sender: &Sender<Result<Bytes, ReasonerError>>,
-    mut chunk: response_stream::ChatCompletionChunk,
-    delta: response_stream::ChunkChoiceDelta,
+) -> bool {
+    let mut delivered_data = false;
+    while let Some(message) = receiver.recv().await {
+        if message
+            .as_ref()
+            .is_ok_and(|bytes| bytes.starts_with(b"data:"))
+        {
+            delivered_data = true;
+        }
+        if sender.send(message).await.is_err() {
+            break;
+        }
+    }
+    delivered_data
+}
+
+async fn send_error(
+    sender: &Sender<Result<Bytes, ReasonerError>>,
+    error: &ReasonerError,
 ) -> Result<(), ReasonerError> {
-    chunk.choices = vec![response_stream::ChunkChoice {
-        index: 0,
-        delta: delta,
-        logprobs: None,
-        finish_reason: None,
-    }];
-    send_chunk(&sender, &chunk).await
+    send_data(
+        sender,
+        serde_json::json!({"error": {"message": error.to_string(), "type": error.error_type()}})
+            .to_string(),
+    )
+    .await
 }
 
-#[cfg(reasoning)]
-async fn send_delta_thinking_end(
-    _: &Sender<Result<Bytes, ReasonerError>>,
-    _: &response_stream::ChatCompletionChunk,
+fn timeout_error(timeout: Duration) -> ReasonerError {
+    ReasonerError::timeout(format!(
+        "error: completion did not finish within {}s",
+        timeout.as_secs()
+    ))
+}
+
+/// Whether the request goes straight to the answer model because no reasoning
+/// budget is left. Logs when `min_answer_tokens` takes the whole `max_tokens`,
+/// since reasoning is then skipped without the client asking for it.
+fn skips_reasoning(
+    request: &request::ChatCompletionCreate,
+    model_config: &config::ModelConfig,
+) -> bool {
+    if resolve_reasoning_budget(request, model_config) > 0 {
+        return false;
+    }
+    let max_tokens = resolve_max_tokens(request, model_config);
+    if model_config.min_answer_tokens > 0
+        && calculate_remaining_tokens(max_tokens, model_config.min_answer_tokens) <= 0
+    {
+        log::info!(
+            "Reasoning skipped: max_tokens {} leaves no room beyond min_answer_tokens {}",
+            max_tokens.unwrap_or(crate::consts::DEFAULT_MAX_TOKENS),
+            model_config.min_answer_tokens
+        );
+    }
+    true
+}
+
+fn should_return_length(finish_reason: FinishReason, model_config: &config::ModelConfig) -> bool {
+    finish_reason == FinishReason::Length
+        && model_config.reasoning_budget_exceeded_behavior
+            == config::ReasoningBudgetExceededBehavior::ReturnLength
+}
+
+async fn send_prefilled_reasoning(
+    sender: &Sender<Result<Bytes, ReasonerError>>,
+    chunk: &response_stream::ChatCompletionChunk,
+    reasoning_text: &str,
+    choice_count: i32,
+    emit_reasoning_content: bool,
+    (think_start, think_end): (&str, &str),
 ) -> Result<(), ReasonerError> {
-    Ok(())
+    send_delta(
+        sender,
+        chunk.clone(),
+        ChunkChoiceDelta::chunk_choice_delta_opening(emit_reasoning_content, think_start),
+        choice_count,
+    )
+    .await?;
+    send_delta(
+        sender,
+        chunk.clone(),
+        ChunkChoiceDelta::chunk_choice_delta_reasoning(
+            reasoning_text.to_string(),
+            emit_reasoning_content,
+        ),
+        choice_count,
+    )
+    .await?;
+    send_delta_thinking_end(
+        sender,
+        chunk,
+        choice_count,
+        emit_reasoning_content,
+        think_end,
+    )
+    .await
+}
+
+/// Moves a native `reasoning_content` delta into the content between the model's
+/// think tags, tracking which choices are still inside their reasoning block.
+fn inline_native_reasoning(
+    choice: &mut response_stream::ChunkChoice,
+    reasoning_choices: &mut HashSet<i32>,
+    (think_start, think_end): (&str, &str),
+) {
+    let reasoning = choice.delta.reasoning_content.take().unwrap_or_default();
+    let answer = choice.delta.content.take();
+    let mut content = String::new();
+    if !reasoning.is_empty() {
+        if reasoning_choices.insert(choice.index) {
+            content.push_str(think_start);
+        }
+        content.push_str(&reasoning);
+    }
+    let closes_reasoning = answer.as_deref().is_some_and(|answer| !answer.is_empty())
+        || choice.finish_reason.is_some();
+    if closes_reasoning && reasoning_choices.remove(&choice.index) {
+        content.push_str(think_end);
+    }
+    if let Some(answer) = &answer {
+        content.push_str(answer);
+    }
+    if !content.is_empty() || answer.is_some() {
+        choice.delta.content = Some(content);
+    }
+}
+
+async fn send_delta(
+    sender: &Sender<Result<Bytes, ReasonerError>>,
+    mut chunk: response_stream::ChatCompletionChunk,
+    delta: response_stream::ChunkChoiceDelta,
+    choice_count: i32,
+) -> Result<(), ReasonerError> {
+    chunk.choices = (0..choice_count)
+        .map(|index| response_stream::ChunkChoice {
+            index,
+            delta: delta.clone(),
+            logprobs: None,
+            finish_reason: None,
+        })
+        .collect();
+    send_chunk(sender, &chunk).await
 }
 
-#[cfg(not(reasoning))]
+// A dedicated reasoning field needs no closing tag
 async fn send_delta_thinking_end(
     sender: &Sender<Result<Bytes, ReasonerError>>,
     chunk: &response_stream::ChatCompletionChunk,
+    choice_count: i32,
+    emit_reasoning_content: bool,
+    think_end: &str,
 ) -> Result<(), ReasonerError> {
+    if emit_reasoning_content {
+        return Ok(());
+    }
     send_delta(
         sender,
         chunk.clone(),
         response_stream::ChunkChoiceDelta {
-            content: Some(consts::THINK_END.to_string()),
+            content: Some(think_end.to_string()),
             ..Default::default()
         },
+        choice_count,
     )
     .await
 }
@@ -486,6 +2374,7 @@ async fn send_delta_thinking_end(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::llm_client::{StubReply, StubUpstream};
 
     fn create_model_config() -> config::ModelConfig {
         config::ModelConfig {
@@ -493,7 +2382,7 @@ mod tests {
             api_url: "http://mock-server".to_string(),
             api_key: "test-key".to_string(),
             reasoning_budget: 100,
-            extra: None,
+            ..Default::default()
         }
     }
 
@@ -506,12 +2395,7 @@ mod tests {
                 }),
             ],
             max_tokens: Some(1000),
-            stop: None,
-            stream: None,
-            stream_options: None,
-            tools: None,
-            tool_choice: None,
-            extra: Default::default(),
+            ..Default::default()
         }
     }
 
@@ -528,6 +2412,34 @@ mod tests {
         let _service2 = service1.clone();
     }
 
+    #[test]
+    fn test_skips_reasoning_when_min_answer_tokens_take_max_tokens() {
+        let mut model_config = create_model_config();
+        let mut request = create_request();
+        assert!(!skips_reasoning(&request, &model_config));
+
+        model_config.min_answer_tokens = 1000;
+        assert!(skips_reasoning(&request, &model_config));
+
+        request.max_tokens = Some(1001);
+        assert!(!skips_reasoning(&request, &model_config));
+    }
+
+    #[tokio::test]
+    async fn test_discovered_models_survive_poisoned_lock() {
+        let service = ReasoningService::new(reqwest::Client::new());
+        let discovered_models = service.discovered_models.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = discovered_models.write().unwrap();
+            panic!("poisoning the discovered models lock");
+        })
+        .join();
+        assert!(service.discovered_models.is_poisoned());
+
+        service.discover_upstream_models(&HashMap::new()).await;
+        assert_eq!(service.discovered_model("test-model"), None);
+    }
+
     #[tokio::test]
     async fn test_create_completion_validation_error_empty_messages() {
         let http_client = reqwest::Client::new();
@@ -541,7 +2453,7 @@ mod tests {
 
         assert!(result.is_err());
         match result.unwrap_err() {
-            ReasonerError::ValidationError(msg) => {
+            ReasonerError::ValidationError { message: msg, .. } => {
                 assert!(msg.contains("empty messages"));
             }
             _ => panic!("Expected ValidationError"),
@@ -564,22 +2476,18 @@ mod tests {
                     reasoning_content: None,
                     content: Some("Hi".to_string()),
                     tool_calls: None,
+                    refusal: None,
                 }),
             ],
             max_tokens: Some(1000),
-            stop: None,
-            stream: None,
-            stream_options: None,
-            tools: None,
-            tool_choice: None,
-            extra: Default::default(),
+            ..Default::default()
         };
 
         let result = service.create_completion(request, &model_config).await;
 
         assert!(result.is_err());
         match result.unwrap_err() {
-            ReasonerError::ValidationError(msg) => {
+            ReasonerError::ValidationError { message: msg, .. } => {
                 assert!(msg.contains("cannot process partial assistant"));
             }
             _ => panic!("Expected ValidationError"),
@@ -587,12 +2495,160 @@ mod tests {
     }
 
     #[test]
-    fn test_create_llm_client() {
+    fn test_create_upstream() {
         let http_client = reqwest::Client::new();
         let service = ReasoningService::new(http_client);
 
         let model_config = create_model_config();
-        let _client = service.create_llm_client(&model_config);
+        let _upstream = service.create_upstream(&model_config);
+    }
+
+    fn stub_usage() -> Usage {
+        Usage {
+            prompt_tokens: 10,
+            completion_tokens: 5,
+            total_tokens: 15,
+            prompt_tokens_details: None,
+            completion_tokens_details: None,
+        }
+    }
+
+    fn stub_completion(content: &str) -> ChatCompletion {
+        ChatCompletion {
+            id: "stub-id".to_string(),
+            object: "chat.completion".to_string(),
+            created: 1,
+            model: "upstream-model".to_string(),
+            choices: vec![response_direct::Choice {
+                index: 0,
+                message: request::MessageAssistant {
+                    reasoning_content: None,
+                    content: Some(content.to_string()),
+                    tool_calls: None,
+                    refusal: None,
+                },
+                logprobs: None,
+                finish_reason: FinishReason::Stop,
+            }],
+            usage: stub_usage(),
+            diagnostics: None,
+        }
+    }
+
+    fn stub_chunks(contents: &[&str]) -> Vec<ChatCompletionChunk> {
+        contents
+            .iter()
+            .enumerate()
+            .map(|(i, content)| {
+                let last = i == contents.len() - 1;
+                ChatCompletionChunk {
+                    id: "stub-id".to_string(),
+                    object: "chat.completion.chunk".to_string(),
+                    created: 1,
+                    model: "upstream-model".to_string(),
+                    choices: vec![response_stream::ChunkChoice {
+                        index: 0,
+                        delta: ChunkChoiceDelta {
+                            content: Some(content.to_string()),
+                            ..Default::default()
+                        },
+                        logprobs: None,
+                        finish_reason: last.then_some(FinishReason::Stop),
+                    }],
+                    usage: last.then(stub_usage),
+                }
+            })
+            .collect()
+    }
+
+    fn stub_service(stub: &Arc<StubUpstream>) -> ReasoningService {
+        ReasoningService::builder(reqwest::Client::new())
+            .upstream(stub.clone())
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_create_completion_through_stub_upstream() {
+        let stub = Arc::new(StubUpstream::new(vec![
+            StubReply::Completion(stub_completion("Let me think")),
+            StubReply::Completion(stub_completion("It's 4.")),
+        ]));
+        let service = stub_service(&stub);
+
+        let completion = service
+            .create_completion(create_request(), &create_model_config())
+            .await
+            .unwrap();
+
+        let content = completion.choices[0].message.content.clone().unwrap();
+        assert!(content.contains("Let me think"));
+        assert!(content.ends_with("It's 4."));
+        assert_eq!(completion.usage.completion_tokens, 10);
+
+        let requests = stub.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].stop, Some(vec![consts::THINK_END.to_string()]));
+        match requests[1].messages.last() {
+            Some(request::Message::Assistant(message)) => {
+                assert_eq!(
+                    message.content.as_deref(),
+                    Some("<think>Let me think</think>")
+                );
+            }
+            _ => panic!("Expected Assistant message"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_completion_through_stub_upstream() {
+        let stub = Arc::new(StubUpstream::new(vec![
+            StubReply::Chunks(stub_chunks(&["Let", " me think"])),
+            StubReply::Chunks(stub_chunks(&["It's", " 4."])),
+        ]));
+        let service = stub_service(&stub);
+        let mut request = create_request();
+        request.stream = Some(true);
+
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+        service
+            .stream_completion(request, &create_model_config(), sender)
+            .await
+            .unwrap();
+
+        let mut content = String::new();
+        while let Ok(Ok(event)) = receiver.try_recv() {
+            let event = String::from_utf8(event.to_vec()).unwrap();
+            let Some(data) = event.strip_prefix("data: ") else {
+                continue;
+            };
+            if let Ok(chunk) = serde_json::from_str::<ChatCompletionChunk>(data.trim()) {
+                for choice in chunk.choices {
+                    content.push_str(choice.delta.content.as_deref().unwrap_or(""));
+                }
+            }
+        }
+
+        assert_eq!(content, "<think>Let me think</think>It's 4.");
+        assert_eq!(stub.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_create_completion_propagates_stub_upstream_error() {
+        let stub = Arc::new(StubUpstream::new(vec![StubReply::Error(
+            ReasonerError::api("error: upstream exploded"),
+        )]));
+        let service = stub_service(&stub);
+
+        let result = service
+            .create_completion(create_request(), &create_model_config())
+            .await;
+
+        match result {
+            Err(ReasonerError::ApiError { message, .. }) => {
+                assert_eq!(message, "error: upstream exploded");
+            }
+            _ => panic!("Expected ApiError"),
+        }
     }
 
     #[tokio::test]
@@ -612,6 +2668,10 @@ mod tests {
         });
 
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        let frame = receiver.try_recv().unwrap().unwrap();
+        let frame = std::str::from_utf8(&frame).unwrap();
+        assert!(frame.starts_with("data: {\"error\":"));
+        assert!(frame.contains("invalid_request_error"));
         assert!(receiver.try_recv().is_err(), "Channel should be closed on validation error");
     }
 }
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::models::FinishReason;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CachedReasoning {
+    pub(crate) text: String,
+    pub(crate) finish_reason: FinishReason,
+    pub(crate) reasoning_tokens: i32,
+}
+
+pub(crate) struct ReasoningCache {
+    entries: Mutex<HashMap<u64, (Instant, CachedReasoning)>>,
+    capacity: usize,
+    ttl: Duration,
+}
+
+impl ReasoningCache {
+    pub(crate) fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            capacity,
+            ttl,
+        }
+    }
+
+    pub(crate) fn get(&self, key: u64) -> Option<CachedReasoning> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(&key) {
+            Some((inserted, reasoning)) if inserted.elapsed() < self.ttl => Some(reasoning.clone()),
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub(crate) fn insert(&self, key: u64, reasoning: CachedReasoning) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            entries.retain(|_, (inserted, _)| inserted.elapsed() < self.ttl);
+        }
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, (inserted, _))| *inserted)
+                .map(|(key, _)| *key);
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(key, (Instant::now(), reasoning));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(text: &str) -> CachedReasoning {
+        CachedReasoning {
+            text: text.to_string(),
+            finish_reason: FinishReason::Stop,
+            reasoning_tokens: 10,
+        }
+    }
+
+    #[test]
+    fn test_reasoning_cache_get_and_insert() {
+        let cache = ReasoningCache::new(2, Duration::from_secs(60));
+        assert_eq!(cache.get(1), None);
+
+        cache.insert(1, cached("one"));
+        assert_eq!(cache.get(1), Some(cached("one")));
+    }
+
+    #[test]
+    fn test_reasoning_cache_evicts_oldest_beyond_capacity() {
+        let cache = ReasoningCache::new(2, Duration::from_secs(60));
+        cache.insert(1, cached("one"));
+        cache.insert(2, cached("two"));
+        cache.insert(3, cached("three"));
+
+        assert_eq!(cache.get(1), None);
+        assert_eq!(cache.get(2), Some(cached("two")));
+        assert_eq!(cache.get(3), Some(cached("three")));
+    }
+
+    #[test]
+    fn test_reasoning_cache_expires_entries() {
+        let cache = ReasoningCache::new(2, Duration::ZERO);
+        cache.insert(1, cached("one"));
+        assert_eq!(cache.get(1), None);
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::OnceCell;
+
+use crate::errors::ReasonerError;
+use crate::models::response_direct::ChatCompletion;
+
+type Flight = Arc<OnceCell<Result<ChatCompletion, ReasonerError>>>;
+
+/// Collapses identical concurrent completions into a single upstream run whose
+/// result is handed to every caller that joined while it was in flight.
+#[derive(Default)]
+pub(crate) struct SingleFlight {
+    in_flight: Mutex<HashMap<u64, Flight>>,
+}
+
+impl SingleFlight {
+    pub(crate) async fn run<F>(
+        &self,
+        key: u64,
+        completion: F,
+    ) -> Result<ChatCompletion, ReasonerError>
+    where
+        F: Future<Output = Result<ChatCompletion, ReasonerError>>,
+    {
+        let flight = self
+            .in_flight
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_default()
+            .clone();
+
+        let result = flight.get_or_init(|| completion).await.clone();
+
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if in_flight
+            .get(&key)
+            .is_some_and(|current| Arc::ptr_eq(current, &flight))
+        {
+            in_flight.remove(&key);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Usage;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn completion(id: &str) -> ChatCompletion {
+        ChatCompletion {
+            id: id.to_string(),
+            object: "chat.completion".to_string(),
+            created: 0,
+            model: "test".to_string(),
+            choices: vec![],
+            usage: Usage {
+                prompt_tokens: 0,
+                completion_tokens: 0,
+                total_tokens: 0,
+                prompt_tokens_details: None,
+                completion_tokens_details: None,
+            },
+            diagnostics: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_single_flight_shares_concurrent_result() {
+        let single_flight = SingleFlight::default();
+        let runs = AtomicUsize::new(0);
+        let run = || async {
+            runs.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(completion("shared"))
+        };
+
+        let (first, second) =
+            tokio::join!(single_flight.run(1, run()), single_flight.run(1, run()));
+
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(first.unwrap().id, "shared");
+        assert_eq!(second.unwrap().id, "shared");
+        assert!(single_flight.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_single_flight_runs_again_after_completion() {
+        let single_flight = SingleFlight::default();
+
+        let first = single_flight
+            .run(1, async { Ok(completion("first")) })
+            .await;
+        let second = single_flight
+            .run(1, async { Ok(completion("second")) })
+            .await;
+
+        assert_eq!(first.unwrap().id, "first");
+        assert_eq!(second.unwrap().id, "second");
+    }
+}
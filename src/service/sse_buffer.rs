@@ -0,0 +1,103 @@
+/// Reassembles server-sent events from network chunks that may split an event
+/// anywhere, including in the middle of a multi-byte character.
+#[derive(Default)]
+pub(crate) struct SseBuffer {
+    buffer: Vec<u8>,
+}
+
+impl SseBuffer {
+    pub(crate) fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Next complete event without its terminating blank line.
+    pub(crate) fn next_event(&mut self) -> Option<Vec<u8>> {
+        let (end, separator_len) = find_event_end(&self.buffer)?;
+        let event = self.buffer[..end].to_vec();
+        self.buffer.drain(..end + separator_len);
+        Some(event)
+    }
+
+    /// Unterminated remainder once the stream has ended, unless it is blank.
+    pub(crate) fn finish(&mut self) -> Option<Vec<u8>> {
+        let rest = std::mem::take(&mut self.buffer);
+        (!rest.iter().all(u8::is_ascii_whitespace)).then_some(rest)
+    }
+}
+
+fn find_event_end(buffer: &[u8]) -> Option<(usize, usize)> {
+    (0..buffer.len()).find_map(|index| {
+        let rest = &buffer[index..];
+        if rest.starts_with(b"\r\n\r\n") {
+            Some((index, 4))
+        } else if rest.starts_with(b"\n\n") {
+            Some((index, 2))
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain(buffer: &mut SseBuffer) -> Vec<String> {
+        std::iter::from_fn(|| buffer.next_event())
+            .map(|event| String::from_utf8(event).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_sse_buffer_joins_events_split_across_chunks() {
+        let mut buffer = SseBuffer::default();
+
+        buffer.push(b"data: {\"a\":");
+        assert!(drain(&mut buffer).is_empty());
+
+        buffer.push(b"1}\n");
+        assert!(drain(&mut buffer).is_empty());
+
+        buffer.push(b"\ndata: {\"b\":2}\n\ndata: [DO");
+        assert_eq!(
+            drain(&mut buffer),
+            vec!["data: {\"a\":1}", "data: {\"b\":2}"]
+        );
+
+        buffer.push(b"NE]\n\n");
+        assert_eq!(drain(&mut buffer), vec!["data: [DONE]"]);
+        assert_eq!(buffer.finish(), None);
+    }
+
+    #[test]
+    fn test_sse_buffer_accepts_crlf_separators() {
+        let mut buffer = SseBuffer::default();
+        buffer.push(b"data: one\r\n\r\ndata: two\r\n\r\n");
+
+        assert_eq!(drain(&mut buffer), vec!["data: one", "data: two"]);
+    }
+
+    #[test]
+    fn test_sse_buffer_keeps_split_multi_byte_characters() {
+        let mut buffer = SseBuffer::default();
+        let event = "data: wörld\n\n".as_bytes();
+        let split = event.iter().position(|byte| *byte > 0x7f).unwrap() + 1;
+
+        buffer.push(&event[..split]);
+        buffer.push(&event[split..]);
+
+        assert_eq!(drain(&mut buffer), vec!["data: wörld"]);
+    }
+
+    #[test]
+    fn test_sse_buffer_returns_unterminated_remainder_on_finish() {
+        let mut buffer = SseBuffer::default();
+        buffer.push(b"data: {\"a\":1}");
+
+        assert!(drain(&mut buffer).is_empty());
+        assert_eq!(buffer.finish(), Some(b"data: {\"a\":1}".to_vec()));
+
+        buffer.push(b"\n");
+        assert_eq!(buffer.finish(), None);
+    }
+}
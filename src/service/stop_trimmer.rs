@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use crate::llm_request::strip_trailing_stop;
+use crate::models::response_stream::ChunkChoice;
+
+/// Holds back streamed answer content that could be the start of a stop
+/// sequence, so a stop string echoed by the provider never reaches the client.
+pub(crate) struct StopTrimmer {
+    stops: Vec<String>,
+    pending: HashMap<i32, String>,
+}
+
+impl StopTrimmer {
+    pub(crate) fn new(stops: Vec<String>) -> Option<Self> {
+        (!stops.is_empty()).then(|| Self {
+            stops,
+            pending: HashMap::new(),
+        })
+    }
+
+    pub(crate) fn apply(&mut self, choice: &mut ChunkChoice) {
+        let mut content = match choice.delta.content.take() {
+            Some(content) => self.push(choice.index, &content),
+            None => String::new(),
+        };
+        if choice.finish_reason.is_some() {
+            content.push_str(&self.finish(choice.index));
+        }
+        if !content.is_empty() || choice.finish_reason.is_none() {
+            choice.delta.content = Some(content);
+        }
+    }
+
+    pub(crate) fn finish(&mut self, index: i32) -> String {
+        let pending = self.pending.remove(&index).unwrap_or_default();
+        strip_trailing_stop(&pending, &self.stops).to_string()
+    }
+
+    fn push(&mut self, index: i32, content: &str) -> String {
+        let pending = self.pending.entry(index).or_default();
+        pending.push_str(content);
+
+        let held = pending
+            .char_indices()
+            .map(|(byte_index, _)| byte_index)
+            .find(|&byte_index| {
+                let tail = &pending[byte_index..];
+                self.stops.iter().any(|stop| stop.starts_with(tail))
+            })
+            .unwrap_or(pending.len());
+        let held = pending.split_off(held);
+        std::mem::replace(pending, held)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FinishReason;
+    use crate::models::response_stream::ChunkChoiceDelta;
+
+    fn content_choice(content: &str, finish_reason: Option<FinishReason>) -> ChunkChoice {
+        ChunkChoice {
+            index: 0,
+            delta: ChunkChoiceDelta {
+                content: Some(content.to_string()),
+                ..Default::default()
+            },
+            logprobs: None,
+            finish_reason,
+        }
+    }
+
+    #[test]
+    fn test_stop_trimmer_drops_stop_split_across_chunks() {
+        let mut trimmer = StopTrimmer::new(vec!["<END>".to_string()]).unwrap();
+        let mut emitted = String::new();
+
+        for (content, finish_reason) in [
+            ("Hello <", None),
+            ("world <E", None),
+            ("ND>", Some(FinishReason::Stop)),
+        ] {
+            let mut choice = content_choice(content, finish_reason);
+            trimmer.apply(&mut choice);
+            emitted.push_str(choice.delta.content.as_deref().unwrap_or(""));
+        }
+
+        assert_eq!(emitted, "Hello <world ");
+    }
+
+    #[test]
+    fn test_stop_trimmer_flushes_held_prefix_on_finish() {
+        let mut trimmer = StopTrimmer::new(vec!["<END>".to_string()]).unwrap();
+
+        let mut choice = content_choice("a <E", None);
+        trimmer.apply(&mut choice);
+        assert_eq!(choice.delta.content.as_deref(), Some("a "));
+
+        assert_eq!(trimmer.finish(0), "<E");
+    }
+
+    #[test]
+    fn test_stop_trimmer_disabled_without_stops() {
+        assert!(StopTrimmer::new(vec![]).is_none());
+    }
+}
@@ -0,0 +1,85 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::consts;
+
+pub struct StreamPermit {
+    _permit: OwnedSemaphorePermit,
+}
+
+#[derive(Clone)]
+pub(crate) struct StreamLimiter {
+    semaphore: Arc<Semaphore>,
+    max_streams: usize,
+}
+
+impl StreamLimiter {
+    pub(crate) fn new(max_streams: Option<usize>) -> Self {
+        let max_streams = max_streams.unwrap_or(Semaphore::MAX_PERMITS);
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_streams)),
+            max_streams,
+        }
+    }
+
+    pub(crate) fn try_acquire(&self) -> Option<StreamPermit> {
+        let permit = self.semaphore.clone().try_acquire_owned().ok()?;
+        Some(StreamPermit { _permit: permit })
+    }
+
+    pub(crate) fn active(&self) -> usize {
+        self.max_streams - self.semaphore.available_permits()
+    }
+
+    /// Resolves once every permit handed out so far has been released.
+    pub(crate) async fn idle(&self) {
+        while self.active() > 0 {
+            tokio::time::sleep(Duration::from_millis(consts::STREAM_DRAIN_POLL_INTERVAL_MS)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_limiter_rejects_beyond_limit() {
+        let limiter = StreamLimiter::new(Some(2));
+
+        let first = limiter.try_acquire();
+        let second = limiter.try_acquire();
+        assert!(first.is_some());
+        assert!(second.is_some());
+        assert!(limiter.try_acquire().is_none());
+        assert_eq!(limiter.active(), 2);
+
+        drop(first);
+        assert_eq!(limiter.active(), 1);
+        assert!(limiter.try_acquire().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_stream_limiter_idle_after_permits_released() {
+        let limiter = StreamLimiter::new(None);
+        let permit = limiter.try_acquire().unwrap();
+
+        let release = async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            drop(permit);
+        };
+        tokio::join!(limiter.idle(), release);
+
+        assert_eq!(limiter.active(), 0);
+    }
+
+    #[test]
+    fn test_stream_limiter_unlimited_by_default() {
+        let limiter = StreamLimiter::new(None);
+        let permits: Vec<_> = (0..100).map(|_| limiter.try_acquire()).collect();
+        assert!(permits.iter().all(Option::is_some));
+        assert_eq!(limiter.active(), 100);
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use crate::llm_request::strip_think_tags;
+use crate::models::response_stream::ChunkChoice;
+
+/// Removes think markers the provider leaks into streamed answer content,
+/// holding back any tail that could be the start of a marker split across chunks.
+pub(crate) struct ThinkTagStripper {
+    tags: Vec<String>,
+    pending: HashMap<i32, String>,
+}
+
+impl ThinkTagStripper {
+    pub(crate) fn new(tags: Vec<String>) -> Self {
+        Self {
+            tags,
+            pending: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn apply(&mut self, choice: &mut ChunkChoice) {
+        let mut content = match choice.delta.content.take() {
+            Some(content) => self.push(choice.index, &content),
+            None => String::new(),
+        };
+        if choice.finish_reason.is_some() {
+            content.push_str(&self.finish(choice.index));
+        }
+        if !content.is_empty() || choice.finish_reason.is_none() {
+            choice.delta.content = Some(content);
+        }
+    }
+
+    pub(crate) fn finish(&mut self, index: i32) -> String {
+        self.pending.remove(&index).unwrap_or_default()
+    }
+
+    fn push(&mut self, index: i32, content: &str) -> String {
+        let pending = self.pending.entry(index).or_default();
+        pending.push_str(content);
+        *pending = strip_think_tags(pending, &self.tags);
+
+        let held = pending
+            .char_indices()
+            .map(|(byte_index, _)| byte_index)
+            .find(|&byte_index| {
+                let tail = &pending[byte_index..];
+                self.tags.iter().any(|tag| tag.starts_with(tail))
+            })
+            .unwrap_or(pending.len());
+        let held = pending.split_off(held);
+        std::mem::replace(pending, held)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consts::{THINK_END, THINK_START};
+    use crate::models::FinishReason;
+    use crate::models::response_stream::ChunkChoiceDelta;
+
+    fn content_choice(content: &str, finish_reason: Option<FinishReason>) -> ChunkChoice {
+        ChunkChoice {
+            index: 0,
+            delta: ChunkChoiceDelta {
+                content: Some(content.to_string()),
+                ..Default::default()
+            },
+            logprobs: None,
+            finish_reason,
+        }
+    }
+
+    #[test]
+    fn test_think_tag_stripper_drops_tags_split_across_chunks() {
+        let mut stripper =
+            ThinkTagStripper::new(vec![THINK_START.to_string(), THINK_END.to_string()]);
+        let mut emitted = String::new();
+
+        for (content, finish_reason) in [
+            ("</th", None),
+            ("ink>Hello <", None),
+            ("think> world <", Some(FinishReason::Stop)),
+        ] {
+            let mut choice = content_choice(content, finish_reason);
+            stripper.apply(&mut choice);
+            emitted.push_str(choice.delta.content.as_deref().unwrap_or(""));
+        }
+
+        assert_eq!(emitted, "Hello  world <");
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct ModelUsage {
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub estimated_cost: f64,
+}
+
+#[derive(Default)]
+pub(crate) struct UsageTracker {
+    models: Mutex<BTreeMap<String, ModelUsage>>,
+}
+
+impl UsageTracker {
+    pub(crate) fn record(
+        &self,
+        model: &str,
+        prompt_tokens: i32,
+        completion_tokens: i32,
+        estimated_cost: Option<f64>,
+    ) {
+        let mut models = self.models.lock().unwrap();
+        let usage = models.entry(model.to_string()).or_default();
+        usage.requests += 1;
+        usage.prompt_tokens += prompt_tokens.max(0) as u64;
+        usage.completion_tokens += completion_tokens.max(0) as u64;
+        usage.estimated_cost += estimated_cost.unwrap_or(0.0);
+    }
+
+    pub(crate) fn snapshot(&self) -> BTreeMap<String, ModelUsage> {
+        self.models.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_tracker_accumulates_per_model() {
+        let tracker = UsageTracker::default();
+
+        tracker.record("model-a", 100, 50, Some(0.25));
+        tracker.record("model-a", 200, 10, Some(0.5));
+        tracker.record("model-b", 10, 5, None);
+
+        let snapshot = tracker.snapshot();
+        assert_eq!(
+            snapshot["model-a"],
+            ModelUsage {
+                requests: 2,
+                prompt_tokens: 300,
+                completion_tokens: 60,
+                estimated_cost: 0.75,
+            }
+        );
+        assert_eq!(
+            snapshot["model-b"],
+            ModelUsage {
+                requests: 1,
+                prompt_tokens: 10,
+                completion_tokens: 5,
+                estimated_cost: 0.0,
+            }
+        );
+    }
+}
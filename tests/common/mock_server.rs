@@ -57,3 +57,45 @@ pub async fn setup_error_mock(
 
     mock_server
 }
+
+pub async fn setup_streaming_mocks(reasoning_sse: String, answer_sse: String) -> MockServer {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_bytes(reasoning_sse.into_bytes())
+                .insert_header("content-type", "text/event-stream"),
+        )
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_bytes(answer_sse.into_bytes())
+                .insert_header("content-type", "text/event-stream"),
+        )
+        .mount(&mock_server)
+        .await;
+
+    mock_server
+}
+
+pub async fn setup_sequential_mocks(responses: Vec<ResponseTemplate>) -> MockServer {
+    let mock_server = MockServer::start().await;
+
+    for response in responses {
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(response)
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+    }
+
+    mock_server
+}
@@ -2,3 +2,4 @@ pub mod setup;
 pub mod sse;
 pub mod streaming;
 pub mod mock_server;
+pub mod trickle;
@@ -15,10 +15,13 @@ pub fn create_test_config() -> Config {
             api_url: "http://localhost:8081".to_string(),
             api_key: "test-key".to_string(),
             reasoning_budget: 100,
-            extra: None,
+            ..Default::default()
         },
     );
-    Config { models }
+    Config {
+        models,
+        ..Default::default()
+    }
 }
 
 pub async fn create_test_app_components() -> (Arc<Config>, Arc<ReasoningService>) {
@@ -35,6 +38,6 @@ pub fn create_model_config(base_url: String) -> ModelConfig {
         api_url: base_url,
         api_key: "test-key".to_string(),
         reasoning_budget: 100,
-        extra: None,
+        ..Default::default()
     }
 }
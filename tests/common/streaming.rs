@@ -48,9 +48,8 @@ pub fn validate_sse_format(lines: &[&str]) -> (bool, bool, bool) {
     let mut has_crlf = false;
 
     for (i, line) in lines.iter().enumerate() {
-        if line.starts_with("data: ") {
+        if let Some(data_content) = line.strip_prefix("data: ") {
             has_data_lines = true;
-            let data_content = &line[6..];
             if data_content == "[DONE]" {
                 continue;
             }
@@ -58,12 +57,18 @@ pub fn validate_sse_format(lines: &[&str]) -> (bool, bool, bool) {
                 has_crlf = true;
             }
         }
-        if *line == "" && i > 0 {
-            if lines[i - 1].starts_with("data: ") {
-                has_empty_lines = true;
-            }
+        if line.is_empty() && i > 0 && lines[i - 1].starts_with("data: ") {
+            has_empty_lines = true;
         }
     }
 
     (has_data_lines, has_empty_lines, has_crlf)
 }
+
+pub fn parse_stream_chunks(chunks: &[String]) -> Vec<serde_json::Value> {
+    chunks
+        .iter()
+        .filter_map(|chunk| chunk.trim().strip_prefix("data: "))
+        .filter_map(|data| serde_json::from_str(data).ok())
+        .collect()
+}
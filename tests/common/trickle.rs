@@ -0,0 +1,114 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// SSE response body written to the socket piece by piece, each piece after its delay.
+#[derive(Clone, Default)]
+pub struct TrickleResponse {
+    segments: Vec<(Duration, Vec<u8>)>,
+}
+
+impl TrickleResponse {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn segment(mut self, delay: Duration, bytes: impl Into<Vec<u8>>) -> Self {
+        self.segments.push((delay, bytes.into()));
+        self
+    }
+
+    pub fn byte_by_byte(body: &str, delay: Duration) -> Self {
+        body.bytes().fold(Self::new(), |response, byte| {
+            response.segment(delay, vec![byte])
+        })
+    }
+}
+
+/// Bare HTTP/1.1 upstream for timing-sensitive streaming tests that wiremock cannot express.
+/// Each incoming connection is answered with the next response in order; the last one repeats.
+pub struct TrickleServer {
+    address: SocketAddr,
+    received_requests: Arc<AtomicUsize>,
+}
+
+impl TrickleServer {
+    pub async fn start(responses: Vec<TrickleResponse>) -> Self {
+        assert!(!responses.is_empty(), "at least one response is required");
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let received_requests = Arc::new(AtomicUsize::new(0));
+
+        let counter = received_requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let index = counter.fetch_add(1, Ordering::SeqCst);
+                let response = responses[index.min(responses.len() - 1)].clone();
+                tokio::spawn(serve_connection(stream, response));
+            }
+        });
+
+        Self {
+            address,
+            received_requests,
+        }
+    }
+
+    pub fn uri(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    pub fn received_requests(&self) -> usize {
+        self.received_requests.load(Ordering::SeqCst)
+    }
+}
+
+async fn serve_connection(mut stream: TcpStream, response: TrickleResponse) {
+    if read_request(&mut stream).await.is_none() {
+        return;
+    }
+
+    let head = "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\nconnection: close\r\n\r\n";
+    if stream.write_all(head.as_bytes()).await.is_err() {
+        return;
+    }
+    for (delay, bytes) in response.segments {
+        tokio::time::sleep(delay).await;
+        if stream.write_all(&bytes).await.is_err() || stream.flush().await.is_err() {
+            return;
+        }
+    }
+    let _ = stream.shutdown().await;
+}
+
+async fn read_request(stream: &mut TcpStream) -> Option<Vec<u8>> {
+    let mut request = vec![];
+    let mut buffer = [0u8; 4096];
+    loop {
+        let read = stream.read(&mut buffer).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        request.extend_from_slice(&buffer[..read]);
+
+        let Some(head_end) = request.windows(4).position(|window| window == b"\r\n\r\n") else {
+            continue;
+        };
+        let head = String::from_utf8_lossy(&request[..head_end]).to_lowercase();
+        let content_length = head
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .unwrap_or(0);
+        if request.len() >= head_end + 4 + content_length {
+            return Some(request.split_off(head_end + 4));
+        }
+    }
+}
@@ -12,12 +12,7 @@ pub fn sample_chat_request() -> request::ChatCompletionCreate {
             content: request::MessageContent::String("Hello, how are you?".to_string()),
         })],
         max_tokens: Some(100),
-        stop: None,
-        stream: None,
-        stream_options: None,
-        tools: None,
-        tool_choice: None,
-        extra: Default::default(),
+        ..Default::default()
     }
 }
 
@@ -33,6 +28,7 @@ pub fn sample_reasoning_response() -> ChatCompletion {
                 reasoning_content: None,
                 content: Some("Let me think about this carefully...".to_string()),
                 tool_calls: None,
+                refusal: None,
             },
             logprobs: None,
             finish_reason: FinishReason::Stop,
@@ -41,7 +37,10 @@ pub fn sample_reasoning_response() -> ChatCompletion {
             prompt_tokens: 10,
             completion_tokens: 50,
             total_tokens: 60,
+            prompt_tokens_details: None,
+            completion_tokens_details: None,
         },
+        diagnostics: None,
     }
 }
 
@@ -57,6 +56,7 @@ pub fn sample_answer_response() -> ChatCompletion {
                 reasoning_content: None,
                 content: Some("I'm doing great, thank you!".to_string()),
                 tool_calls: None,
+                refusal: None,
             },
             logprobs: None,
             finish_reason: FinishReason::Stop,
@@ -65,7 +65,10 @@ pub fn sample_answer_response() -> ChatCompletion {
             prompt_tokens: 10,
             completion_tokens: 30,
             total_tokens: 40,
+            prompt_tokens_details: None,
+            completion_tokens_details: None,
         },
+        diagnostics: None,
     }
 }
 
@@ -81,7 +84,9 @@ pub fn sample_reasoning_chunks() -> Vec<ChatCompletionChunk> {
                 delta: ChunkChoiceDelta {
                     content: Some("Let".to_string()),
                     role: None,
+                    reasoning_content: None,
                     tool_calls: None,
+                    refusal: None,
                 },
                 logprobs: None,
                 finish_reason: None,
@@ -98,7 +103,9 @@ pub fn sample_reasoning_chunks() -> Vec<ChatCompletionChunk> {
                 delta: ChunkChoiceDelta {
                     content: Some(" me".to_string()),
                     role: None,
+                    reasoning_content: None,
                     tool_calls: None,
+                    refusal: None,
                 },
                 logprobs: None,
                 finish_reason: None,
@@ -115,7 +122,9 @@ pub fn sample_reasoning_chunks() -> Vec<ChatCompletionChunk> {
                 delta: ChunkChoiceDelta {
                     content: Some(" think".to_string()),
                     role: None,
+                    reasoning_content: None,
                     tool_calls: None,
+                    refusal: None,
                 },
                 logprobs: None,
                 finish_reason: Some(FinishReason::Stop),
@@ -124,6 +133,8 @@ pub fn sample_reasoning_chunks() -> Vec<ChatCompletionChunk> {
                 prompt_tokens: 10,
                 completion_tokens: 10,
                 total_tokens: 20,
+                prompt_tokens_details: None,
+                completion_tokens_details: None,
             }),
         },
     ]
@@ -141,7 +152,9 @@ pub fn sample_answer_chunks() -> Vec<ChatCompletionChunk> {
                 delta: ChunkChoiceDelta {
                     content: Some("I'm".to_string()),
                     role: None,
+                    reasoning_content: None,
                     tool_calls: None,
+                    refusal: None,
                 },
                 logprobs: None,
                 finish_reason: None,
@@ -158,7 +171,9 @@ pub fn sample_answer_chunks() -> Vec<ChatCompletionChunk> {
                 delta: ChunkChoiceDelta {
                     content: Some(" doing".to_string()),
                     role: None,
+                    reasoning_content: None,
                     tool_calls: None,
+                    refusal: None,
                 },
                 logprobs: None,
                 finish_reason: None,
@@ -175,7 +190,9 @@ pub fn sample_answer_chunks() -> Vec<ChatCompletionChunk> {
                 delta: ChunkChoiceDelta {
                     content: Some(" great!".to_string()),
                     role: None,
+                    reasoning_content: None,
                     tool_calls: None,
+                    refusal: None,
                 },
                 logprobs: None,
                 finish_reason: Some(FinishReason::Stop),
@@ -184,6 +201,8 @@ pub fn sample_answer_chunks() -> Vec<ChatCompletionChunk> {
                 prompt_tokens: 10,
                 completion_tokens: 8,
                 total_tokens: 18,
+                prompt_tokens_details: None,
+                completion_tokens_details: None,
             }),
         },
     ]
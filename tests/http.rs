@@ -14,7 +14,9 @@ use adaptive_reasoner::config::{Config, ModelConfig};
 use adaptive_reasoner::models::model_list;
 use adaptive_reasoner::service::ReasoningService;
 
+#[allow(dead_code)]
 mod common;
+#[allow(dead_code)]
 mod fixtures;
 
 use rstest::rstest;
@@ -54,7 +56,47 @@ async fn test_http_error_codes(
         .set_json(&request_body)
         .to_request();
     let resp = test::call_service(&app, req).await;
-    assert_eq!(resp.status(), StatusCode::BAD_GATEWAY);
+    assert_eq!(resp.status().as_u16(), status_code);
+
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"]["message"], message);
+    assert_eq!(body["error"]["type"], error_type);
+}
+
+#[actix_web::test]
+async fn test_http_error_synthesizes_openai_envelope() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(400).set_body_string("unsupported parameter: foo"))
+        .mount(&mock_server)
+        .await;
+
+    let mut config = create_test_config();
+    config.models.get_mut("test-model").unwrap().api_url = mock_server.uri();
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(ReasoningService::new(Client::new()));
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let request_body =
+        json!({"model": "test-model", "messages": [{"role": "user", "content": "Hello"}]});
+    let req = test::TestRequest::post()
+        .uri("/v1/chat/completions")
+        .set_json(&request_body)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"]["type"], "invalid_request_error");
+    assert!(body["error"]["code"].is_null());
+    assert!(
+        body["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("unsupported parameter: foo")
+    );
 }
 
 fn create_test_config() -> Config {
@@ -66,10 +108,24 @@ fn create_test_config() -> Config {
             api_url: "http://localhost:8081".to_string(),
             api_key: "test-key".to_string(),
             reasoning_budget: 100,
-            extra: None,
+            ..Default::default()
         },
     );
-    Config { models }
+    Config {
+        models,
+        ..Default::default()
+    }
+}
+
+async fn assert_invalid_request_body<B: actix_web::body::MessageBody>(
+    resp: actix_web::dev::ServiceResponse<B>,
+    expected_message: &str,
+) {
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"]["type"], "invalid_request_error");
+    let message = body["error"]["message"].as_str().unwrap();
+    assert!(message.contains(expected_message), "{message}");
 }
 
 #[actix_web::test]
@@ -91,6 +147,155 @@ async fn test_http_models_endpoint() {
     ));
 }
 
+#[actix_web::test]
+async fn test_http_models_enriched_with_discovered_metadata() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/models"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "data": [
+                {"id": "other-model", "object": "model", "owned_by": "upstream"},
+                {
+                    "id": "test-model",
+                    "object": "model",
+                    "owned_by": "upstream",
+                    "context_length": 32768,
+                    "capabilities": {"tools": true},
+                },
+            ],
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let mut config = create_test_config();
+    config.models.get_mut("test-model").unwrap().api_url = mock_server.uri();
+    let mut missing_model = config.models["test-model"].clone();
+    missing_model.model_name = "unknown-upstream-model".to_string();
+    config
+        .models
+        .insert("missing-model".to_string(), missing_model);
+
+    let reasoning_service = Arc::new(ReasoningService::new(Client::new()));
+    reasoning_service
+        .discover_upstream_models(&config.models)
+        .await;
+    let app = test::init_service(create_app(reasoning_service.clone(), Arc::new(config))).await;
+
+    let req = test::TestRequest::get().uri("/v1/models").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let model = |id: &str| {
+        body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|model| model["id"] == id)
+            .unwrap()
+            .clone()
+    };
+    let enriched = model("test-model");
+    assert_eq!(enriched["owned_by"], "adaptive_reasoner");
+    assert_eq!(enriched["context_length"], 32768);
+    assert_eq!(enriched["capabilities"]["tools"], true);
+
+    let missing = model("missing-model");
+    assert!(missing.get("context_length").is_none());
+}
+
+#[actix_web::test]
+async fn test_http_models_created_timestamps() {
+    let mut config = create_test_config();
+    let mut pinned_model = config.models["test-model"].clone();
+    pinned_model.created = Some(1_700_000_000);
+    config
+        .models
+        .insert("pinned-model".to_string(), pinned_model);
+
+    let reasoning_service = Arc::new(ReasoningService::new(Client::new()));
+    let app = test::init_service(create_app(reasoning_service.clone(), Arc::new(config))).await;
+
+    let req = test::TestRequest::get().uri("/v1/models").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let body: model_list::ModelList = test::read_body_json(resp).await;
+    let created = |id: &str| {
+        body.data
+            .iter()
+            .find(|model| model.id == id)
+            .unwrap()
+            .created
+    };
+    assert_eq!(created("test-model"), reasoning_service.started_at());
+    assert!(created("test-model") > 0);
+    assert_eq!(created("pinned-model"), 1_700_000_000);
+}
+
+#[actix_web::test]
+async fn test_http_openapi_endpoint() {
+    let (config, reasoning_service) = crate::common::setup::create_test_app_components().await;
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let req = test::TestRequest::get().uri("/openapi.json").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert!(body["paths"]["/v1/chat/completions"]["post"].is_object());
+
+    let request_schema = &body["components"]["schemas"]["ChatCompletionCreate"];
+    let required = request_schema["required"].as_array().unwrap();
+    assert!(required.contains(&json!("model")));
+    assert!(required.contains(&json!("messages")));
+}
+
+#[actix_web::test]
+async fn test_http_admin_usage_reports_spend_per_model() {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(crate::fixtures::sample_reasoning_response()).unwrap(),
+        serde_json::to_value(crate::fixtures::sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut config = create_test_config();
+    let model_config = config.models.get_mut("test-model").unwrap();
+    model_config.api_url = mock_server.uri();
+    model_config.input_price_per_1k = Some(0.5);
+    model_config.output_price_per_1k = Some(1.5);
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(ReasoningService::new(Client::new()));
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let req = test::TestRequest::get().uri("/admin/usage").to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body, json!({"models": {}}));
+
+    let request_body =
+        json!({"model": "test-model", "messages": [{"role": "user", "content": "Hello"}]});
+    let req = test::TestRequest::post()
+        .uri("/v1/chat/completions")
+        .set_json(&request_body)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get().uri("/admin/usage").to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let model_usage = &body["models"]["test-model"];
+    assert_eq!(model_usage["requests"], 1);
+    assert_eq!(model_usage["prompt_tokens"], 20);
+    assert_eq!(model_usage["completion_tokens"], 80);
+    assert_eq!(
+        model_usage["estimated_cost"].as_f64(),
+        Some((20.0 * 0.5 + 80.0 * 1.5) / 1000.0)
+    );
+}
+
 #[actix_web::test]
 async fn test_http_chat_completion_invalid_model() {
     let (config, reasoning_service) = crate::common::setup::create_test_app_components().await;
@@ -171,7 +376,10 @@ async fn test_http_chat_completion_api_error() {
         .set_json(&request_body)
         .to_request();
     let resp = test::call_service(&app, req).await;
-    assert_eq!(resp.status(), StatusCode::BAD_GATEWAY);
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"]["message"], "Internal server error");
 }
 
 #[actix_web::test]
@@ -185,18 +393,22 @@ async fn test_http_chat_completion_non_streaming() {
 
     let config = Arc::new(config);
     let http_client = Client::new();
-    let reasoning_service = Arc::new(ReasoningService::new(http_client));
+    let reasoning_service = Arc::new(
+        ReasoningService::builder(http_client)
+            .fresh_completion_ids(false)
+            .build(),
+    );
 
     Mock::given(method("POST"))
         .and(path("/chat/completions"))
-        .respond_with(ResponseTemplate::new(200).set_body_json(&sample_reasoning_response()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(sample_reasoning_response()))
         .up_to_n_times(1)
         .mount(&mock_server)
         .await;
 
     Mock::given(method("POST"))
         .and(path("/chat/completions"))
-        .respond_with(ResponseTemplate::new(200).set_body_json(&sample_answer_response()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(sample_answer_response()))
         .mount(&mock_server)
         .await;
 
@@ -335,8 +547,7 @@ async fn test_http_chat_completion_streaming() {
             let line = lines[i];
             let _empty_line = lines[i + 1];
 
-            if line.starts_with("data: ") {
-                let data_str = &line[6..];
+            if let Some(data_str) = line.strip_prefix("data: ") {
                 if data_str == "[DONE]" {
                     has_done_marker = true;
                 } else if let Ok(json_val) = serde_json::from_str::<serde_json::Value>(data_str) {
@@ -375,8 +586,8 @@ async fn test_http_chat_completion_response_format() {
     use crate::fixtures::{sample_reasoning_response, sample_answer_response};
 
     let mock_server = crate::common::mock_server::setup_two_phase_mocks(
-        serde_json::to_value(&sample_reasoning_response()).unwrap(),
-        serde_json::to_value(&sample_answer_response()).unwrap(),
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
     ).await;
 
     let mut config = create_test_config();
@@ -384,7 +595,11 @@ async fn test_http_chat_completion_response_format() {
 
     let config = Arc::new(config);
     let http_client = Client::new();
-    let reasoning_service = Arc::new(ReasoningService::new(http_client));
+    let reasoning_service = Arc::new(
+        ReasoningService::builder(http_client)
+            .fresh_completion_ids(false)
+            .build(),
+    );
 
     let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
 
@@ -476,6 +691,7 @@ async fn test_http_chat_completion_finish_reason_variants() {
                 reasoning_content: None,
                 content: Some("Partial".to_string()),
                 tool_calls: None,
+                refusal: None,
             },
             logprobs: None,
             finish_reason: FinishReason::Length,
@@ -484,7 +700,10 @@ async fn test_http_chat_completion_finish_reason_variants() {
             prompt_tokens: 10,
             completion_tokens: 100,
             total_tokens: 110,
+            prompt_tokens_details: None,
+            completion_tokens_details: None,
         },
+        diagnostics: None,
     };
 
     let reasoning_response = ChatCompletion {
@@ -498,6 +717,7 @@ async fn test_http_chat_completion_finish_reason_variants() {
                 reasoning_content: None,
                 content: Some("Reasoning".to_string()),
                 tool_calls: None,
+                refusal: None,
             },
             logprobs: None,
             finish_reason: FinishReason::Length,
@@ -506,7 +726,10 @@ async fn test_http_chat_completion_finish_reason_variants() {
             prompt_tokens: 10,
             completion_tokens: 200,
             total_tokens: 210,
+            prompt_tokens_details: None,
+            completion_tokens_details: None,
         },
+        diagnostics: None,
     };
 
     Mock::given(method("POST"))
@@ -571,6 +794,7 @@ async fn test_http_chat_completion_usage_statistics() {
                 reasoning_content: None,
                 content: Some("Reasoning".to_string()),
                 tool_calls: None,
+                refusal: None,
             },
             logprobs: None,
             finish_reason: adaptive_reasoner::models::FinishReason::Stop,
@@ -579,7 +803,10 @@ async fn test_http_chat_completion_usage_statistics() {
             prompt_tokens: 15,
             completion_tokens: 25,
             total_tokens: 40,
+            prompt_tokens_details: None,
+            completion_tokens_details: None,
         },
+        diagnostics: None,
     };
 
     let answer_response = ChatCompletion {
@@ -593,6 +820,7 @@ async fn test_http_chat_completion_usage_statistics() {
                 reasoning_content: None,
                 content: Some("Answer".to_string()),
                 tool_calls: None,
+                refusal: None,
             },
             logprobs: None,
             finish_reason: adaptive_reasoner::models::FinishReason::Stop,
@@ -601,7 +829,10 @@ async fn test_http_chat_completion_usage_statistics() {
             prompt_tokens: 15,
             completion_tokens: 10,
             total_tokens: 25,
+            prompt_tokens_details: None,
+            completion_tokens_details: None,
         },
+        diagnostics: None,
     };
 
     Mock::given(method("POST"))
@@ -859,8 +1090,7 @@ async fn test_http_streaming_chunk_ordering() {
     let mut content_found = false;
 
     for line in &lines {
-        if line.starts_with("data: ") {
-            let data_str = &line[6..];
+        if let Some(data_str) = line.strip_prefix("data: ") {
             if data_str == "[DONE]" {
                 has_done_marker = true;
                 continue;
@@ -868,13 +1098,12 @@ async fn test_http_streaming_chunk_ordering() {
 
             data_chunks += 1;
 
-            if let Ok(json_val) = serde_json::from_str::<serde_json::Value>(data_str) {
-                if json_val.get("choices").is_some()
+            if let Ok(json_val) = serde_json::from_str::<serde_json::Value>(data_str)
+                && (json_val.get("choices").is_some()
                     || json_val.get("delta").is_some()
-                    || json_val.get("content").is_some()
-                {
-                    content_found = true;
-                }
+                    || json_val.get("content").is_some())
+            {
+                content_found = true;
             }
         }
     }
@@ -1031,13 +1260,11 @@ async fn test_http_streaming_malformed_json_chunk() {
 
     let mut valid_json_count = 0;
     for line in &lines {
-        if line.starts_with("data: ") {
-            let data_str = &line[6..];
-            if data_str != "[DONE]" {
-                if serde_json::from_str::<serde_json::Value>(data_str).is_ok() {
-                    valid_json_count += 1;
-                }
-            }
+        if let Some(data_str) = line.strip_prefix("data: ")
+            && data_str != "[DONE]"
+            && serde_json::from_str::<serde_json::Value>(data_str).is_ok()
+        {
+            valid_json_count += 1;
         }
     }
 
@@ -1104,3 +1331,820 @@ async fn test_http_error_invalid_json_response() {
     let resp = test::call_service(&app, req).await;
     assert_eq!(resp.status(), StatusCode::BAD_GATEWAY);
 }
+
+#[actix_web::test]
+async fn test_http_chat_completion_upstream_model_override() {
+    use crate::fixtures::{sample_answer_response, sample_reasoning_response};
+
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut config = create_test_config();
+    let model_config = config.models.get_mut("test-model").unwrap();
+    model_config.api_url = mock_server.uri();
+    model_config.allowed_upstream_models = vec!["test-model-v2".to_string()];
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(ReasoningService::new(Client::new()));
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let req = test::TestRequest::post()
+        .uri("/v1/chat/completions")
+        .insert_header(("X-AR-Upstream-Model", "test-model-v2"))
+        .set_json(json!({"model": "test-model", "messages": [{"role": "user", "content": "Hello"}]}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["model"], "test-model");
+
+    let received = mock_server.received_requests().await.unwrap();
+    assert_eq!(received.len(), 2);
+    for upstream_request in received {
+        let upstream_body: serde_json::Value = upstream_request.body_json().unwrap();
+        assert_eq!(upstream_body["model"], "test-model-v2");
+    }
+}
+
+#[actix_web::test]
+async fn test_http_single_flight_keeps_upstream_model_overrides_apart() {
+    use crate::fixtures::{sample_answer_response, sample_reasoning_response};
+    use std::time::Duration;
+    use wiremock::matchers::body_partial_json;
+
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(body_partial_json(json!({"stop": ["</think>"]})))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(sample_reasoning_response())
+                .set_delay(Duration::from_millis(200)),
+        )
+        .with_priority(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(sample_answer_response()))
+        .mount(&mock_server)
+        .await;
+
+    let mut config = create_test_config();
+    let model_config = config.models.get_mut("test-model").unwrap();
+    model_config.api_url = mock_server.uri();
+    model_config.allowed_upstream_models =
+        vec!["test-model-v2".to_string(), "test-model-v3".to_string()];
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(
+        ReasoningService::builder(Client::new())
+            .single_flight(true)
+            .build(),
+    );
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let request = |upstream_model: &'static str| {
+        test::TestRequest::post()
+            .uri("/v1/chat/completions")
+            .insert_header(("X-AR-Upstream-Model", upstream_model))
+            .set_json(json!({"model": "test-model", "messages": [{"role": "user", "content": "Hello"}]}))
+            .to_request()
+    };
+    let (first, second) = tokio::join!(
+        test::call_service(&app, request("test-model-v2")),
+        test::call_service(&app, request("test-model-v3")),
+    );
+    assert_eq!(first.status(), StatusCode::OK);
+    assert_eq!(second.status(), StatusCode::OK);
+
+    let mut upstream_models: Vec<String> = mock_server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|upstream_request| {
+            let upstream_body: serde_json::Value = upstream_request.body_json().unwrap();
+            upstream_body["model"].as_str().unwrap().to_string()
+        })
+        .collect();
+    upstream_models.sort();
+    assert_eq!(
+        upstream_models,
+        vec!["test-model-v2", "test-model-v2", "test-model-v3", "test-model-v3"]
+    );
+}
+
+#[actix_web::test]
+async fn test_http_chat_completion_upstream_model_override_not_allowed() {
+    let mock_server = MockServer::start().await;
+
+    let mut config = create_test_config();
+    let model_config = config.models.get_mut("test-model").unwrap();
+    model_config.api_url = mock_server.uri();
+    model_config.allowed_upstream_models = vec!["test-model-v2".to_string()];
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(ReasoningService::new(Client::new()));
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let req = test::TestRequest::post()
+        .uri("/v1/chat/completions")
+        .insert_header(("X-AR-Upstream-Model", "some-other-model"))
+        .set_json(json!({"model": "test-model", "messages": [{"role": "user", "content": "Hello"}]}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_invalid_request_body(resp, "upstream model override not allowed: some-other-model").await;
+    assert!(mock_server.received_requests().await.unwrap().is_empty());
+}
+
+#[rstest]
+#[case(Some(500), Some("50"), StatusCode::OK, Some(50))]
+#[case(Some(500), Some("10000"), StatusCode::OK, Some(500))]
+#[case(Some(500), None, StatusCode::OK, Some(100))]
+#[case(None, Some("50"), StatusCode::OK, Some(100))]
+#[case(Some(500), Some("lots"), StatusCode::BAD_REQUEST, None)]
+#[case(Some(10000), Some("auto"), StatusCode::OK, Some(4096))]
+#[actix_web::test]
+async fn test_http_chat_completion_reasoning_budget_header(
+    #[case] max_header_reasoning_budget: Option<i32>,
+    #[case] header: Option<&str>,
+    #[case] expected_status: StatusCode,
+    #[case] expected_budget: Option<i32>,
+) {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(crate::fixtures::sample_reasoning_response()).unwrap(),
+        serde_json::to_value(crate::fixtures::sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut config = create_test_config();
+    config.models.get_mut("test-model").unwrap().api_url = mock_server.uri();
+    config.server.max_header_reasoning_budget = max_header_reasoning_budget;
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(ReasoningService::new(Client::new()));
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let mut req = test::TestRequest::post()
+        .uri("/v1/chat/completions")
+        .set_json(json!({"model": "test-model", "messages": [{"role": "user", "content": "Hello"}]}));
+    if let Some(header) = header {
+        req = req.insert_header(("X-AR-Reasoning-Budget", header));
+    }
+    let resp = test::call_service(&app, req.to_request()).await;
+    assert_eq!(resp.status(), expected_status);
+    if expected_status == StatusCode::BAD_REQUEST {
+        assert_invalid_request_body(resp, "invalid reasoning budget header").await;
+    }
+
+    let received = mock_server.received_requests().await.unwrap();
+    match expected_budget {
+        Some(budget) => {
+            let reasoning_body: serde_json::Value = received[0].body_json().unwrap();
+            assert_eq!(reasoning_body["max_tokens"], budget);
+        }
+        None => assert!(received.is_empty()),
+    }
+}
+
+#[rstest]
+#[case::unclamped(None, 4096)]
+#[case::clamped(Some(500), 500)]
+#[actix_web::test]
+async fn test_http_chat_completion_body_reasoning_budget_auto(
+    #[case] max_header_reasoning_budget: Option<i32>,
+    #[case] expected_budget: i32,
+) {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(crate::fixtures::sample_reasoning_response()).unwrap(),
+        serde_json::to_value(crate::fixtures::sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut config = create_test_config();
+    config.models.get_mut("test-model").unwrap().api_url = mock_server.uri();
+    config.server.max_header_reasoning_budget = max_header_reasoning_budget;
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(ReasoningService::new(Client::new()));
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let req = test::TestRequest::post()
+        .uri("/v1/chat/completions")
+        .set_json(json!({
+            "model": "test-model",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "reasoning_budget": "auto"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let received = mock_server.received_requests().await.unwrap();
+    let reasoning_body: serde_json::Value = received[0].body_json().unwrap();
+    assert_eq!(reasoning_body["max_tokens"], expected_budget);
+}
+
+#[actix_web::test]
+async fn test_http_reasoning_budget_header_overrides_adaptive_estimate() {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(crate::fixtures::sample_reasoning_response()).unwrap(),
+        serde_json::to_value(crate::fixtures::sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut config = create_test_config();
+    let model_config = config.models.get_mut("test-model").unwrap();
+    model_config.api_url = mock_server.uri();
+    model_config.reasoning_budget_min = Some(200);
+    model_config.reasoning_budget_max = Some(4000);
+    config.server.max_header_reasoning_budget = Some(500);
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(ReasoningService::new(Client::new()));
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let req = test::TestRequest::post()
+        .uri("/v1/chat/completions")
+        .insert_header(("X-AR-Reasoning-Budget", "50"))
+        .set_json(json!({"model": "test-model", "messages": [{"role": "user", "content": "Hello"}]}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let received = mock_server.received_requests().await.unwrap();
+    let reasoning_body: serde_json::Value = received[0].body_json().unwrap();
+    assert_eq!(reasoning_body["max_tokens"], 50);
+}
+
+#[rstest]
+#[case::high("high", 50)]
+#[case::low("low", 25)]
+#[actix_web::test]
+async fn test_http_reasoning_effort_within_reasoning_budget_header(
+    #[case] reasoning_effort: &str,
+    #[case] expected_budget: i32,
+) {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(crate::fixtures::sample_reasoning_response()).unwrap(),
+        serde_json::to_value(crate::fixtures::sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut config = create_test_config();
+    config.models.get_mut("test-model").unwrap().api_url = mock_server.uri();
+    config.server.max_header_reasoning_budget = Some(500);
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(ReasoningService::new(Client::new()));
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let req = test::TestRequest::post()
+        .uri("/v1/chat/completions")
+        .insert_header(("X-AR-Reasoning-Budget", "50"))
+        .set_json(json!({
+            "model": "test-model",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "reasoning_effort": reasoning_effort
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let received = mock_server.received_requests().await.unwrap();
+    let reasoning_body: serde_json::Value = received[0].body_json().unwrap();
+    assert_eq!(reasoning_body["max_tokens"], expected_budget);
+}
+
+#[rstest]
+#[case(Some(500), Some("50"), 50)]
+#[case(Some(500), None, 500)]
+#[case(None, None, 5000)]
+#[actix_web::test]
+async fn test_http_chat_completion_body_reasoning_budget_clamped(
+    #[case] max_header_reasoning_budget: Option<i32>,
+    #[case] header: Option<&str>,
+    #[case] expected_budget: i32,
+) {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(crate::fixtures::sample_reasoning_response()).unwrap(),
+        serde_json::to_value(crate::fixtures::sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut config = create_test_config();
+    config.models.get_mut("test-model").unwrap().api_url = mock_server.uri();
+    config.server.max_header_reasoning_budget = max_header_reasoning_budget;
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(ReasoningService::new(Client::new()));
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let mut req = test::TestRequest::post()
+        .uri("/v1/chat/completions")
+        .set_json(json!({
+            "model": "test-model",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "reasoning_budget": 5000
+        }));
+    if let Some(header) = header {
+        req = req.insert_header(("X-AR-Reasoning-Budget", header));
+    }
+    let resp = test::call_service(&app, req.to_request()).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let received = mock_server.received_requests().await.unwrap();
+    let reasoning_body: serde_json::Value = received[0].body_json().unwrap();
+    assert_eq!(reasoning_body["max_tokens"], expected_budget);
+}
+
+#[actix_web::test]
+async fn test_http_streaming_concurrency_limit() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_bytes(
+                    crate::common::sse::build_sse_stream(&crate::fixtures::sample_reasoning_chunks())
+                        .into_bytes(),
+                )
+                .insert_header(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"))
+                .set_delay(std::time::Duration::from_secs(2)),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let mut config = create_test_config();
+    config.models.get_mut("test-model").unwrap().api_url = mock_server.uri();
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(
+        ReasoningService::builder(Client::new())
+            .max_concurrent_streams(Some(2))
+            .build(),
+    );
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let request_body = json!({
+        "model": "test-model",
+        "messages": [{"role": "user", "content": "Hello"}],
+        "stream": true
+    });
+
+    let mut statuses = vec![];
+    for _ in 0..3 {
+        let req = test::TestRequest::post()
+            .uri("/v1/chat/completions")
+            .set_json(&request_body)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        if resp.status() == StatusCode::SERVICE_UNAVAILABLE {
+            assert_eq!(resp.headers().get(header::RETRY_AFTER).unwrap(), "1");
+        }
+        statuses.push(resp.status());
+    }
+    assert_eq!(
+        statuses,
+        vec![StatusCode::OK, StatusCode::OK, StatusCode::SERVICE_UNAVAILABLE]
+    );
+
+    let req = test::TestRequest::get().uri("/metrics").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = test::read_body(resp).await;
+    assert!(
+        String::from_utf8_lossy(&body).contains("adaptive_reasoner_active_streams 2"),
+        "Unexpected metrics body: {:?}",
+        body
+    );
+}
+
+#[actix_web::test]
+async fn test_http_chat_completion_request_timeout() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(crate::fixtures::sample_reasoning_response())
+                .set_delay(std::time::Duration::from_secs(5)),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let mut config = create_test_config();
+    config.models.get_mut("test-model").unwrap().api_url = mock_server.uri();
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(ReasoningService::new(Client::new()));
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let req = test::TestRequest::post()
+        .uri("/v1/chat/completions")
+        .set_json(json!({
+            "model": "test-model",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "timeout_secs": 1
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+}
+
+#[rstest]
+#[case(true, StatusCode::OK)]
+#[case(false, StatusCode::BAD_REQUEST)]
+#[actix_web::test]
+async fn test_http_chat_completion_lenient_roles(
+    #[case] lenient_roles: bool,
+    #[case] expected_status: StatusCode,
+) {
+    use crate::fixtures::{sample_answer_response, sample_reasoning_response};
+
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut config = create_test_config();
+    config.models.get_mut("test-model").unwrap().api_url = mock_server.uri();
+    config.server.lenient_roles = lenient_roles;
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(ReasoningService::new(Client::new()));
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let req = test::TestRequest::post()
+        .uri("/v1/chat/completions")
+        .set_json(json!({
+            "model": "test-model",
+            "messages": [
+                {"role": "System", "content": "Be brief"},
+                {"role": "human", "content": "Hello"}
+            ]
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), expected_status);
+}
+
+#[rstest]
+#[case(true, json!({"include_usage": true}), StatusCode::OK)]
+#[case(true, json!({}), StatusCode::BAD_REQUEST)]
+#[case(false, json!({}), StatusCode::OK)]
+#[actix_web::test]
+async fn test_http_chat_completion_require_stream_usage(
+    #[case] require_stream_usage: bool,
+    #[case] stream_options: serde_json::Value,
+    #[case] expected_status: StatusCode,
+) {
+    use crate::fixtures::{sample_answer_chunks, sample_reasoning_chunks};
+
+    let mock_server = crate::common::mock_server::setup_streaming_mocks(
+        crate::common::sse::build_sse_stream(&sample_reasoning_chunks()),
+        crate::common::sse::build_sse_stream(&sample_answer_chunks()),
+    )
+    .await;
+
+    let mut config = create_test_config();
+    config.models.get_mut("test-model").unwrap().api_url = mock_server.uri();
+    config.server.require_stream_usage = require_stream_usage;
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(ReasoningService::new(Client::new()));
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let req = test::TestRequest::post()
+        .uri("/v1/chat/completions")
+        .set_json(json!({
+            "model": "test-model",
+            "messages": [{"role": "user", "content": "Hello"}],
+            "stream": true,
+            "stream_options": stream_options
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), expected_status);
+    if expected_status == StatusCode::BAD_REQUEST {
+        assert_invalid_request_body(resp, "stream_options.include_usage").await;
+    }
+
+    let received = mock_server.received_requests().await.unwrap();
+    if expected_status == StatusCode::BAD_REQUEST {
+        assert!(received.is_empty());
+    }
+}
+
+#[rstest]
+#[case(64, StatusCode::BAD_REQUEST)]
+#[case(1024, StatusCode::OK)]
+#[actix_web::test]
+async fn test_http_chat_completion_max_decoded_image_bytes(
+    #[case] max_decoded_image_bytes: usize,
+    #[case] expected_status: StatusCode,
+) {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(crate::fixtures::sample_reasoning_response()).unwrap(),
+        serde_json::to_value(crate::fixtures::sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut config = create_test_config();
+    config.models.get_mut("test-model").unwrap().api_url = mock_server.uri();
+    config.server.max_decoded_image_bytes = Some(max_decoded_image_bytes);
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(ReasoningService::new(Client::new()));
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    // 128 base64 characters decode to 96 bytes
+    let image_url = format!("data:image/png;base64,{}", "A".repeat(128));
+    let req = test::TestRequest::post()
+        .uri("/v1/chat/completions")
+        .set_json(json!({
+            "model": "test-model",
+            "messages": [{"role": "user", "content": [
+                {"type": "text", "text": "What is this?"},
+                {"type": "image_url", "image_url": {"url": image_url}}
+            ]}]
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), expected_status);
+    if expected_status == StatusCode::BAD_REQUEST {
+        assert_invalid_request_body(resp, "decoded image size 96 bytes exceeds limit of 64 bytes")
+            .await;
+    }
+
+    let received = mock_server.received_requests().await.unwrap();
+    if expected_status == StatusCode::BAD_REQUEST {
+        assert!(received.is_empty());
+    }
+}
+
+#[actix_web::test]
+async fn test_http_chat_completion_redact_tool_arguments_forwards_verbatim() {
+    use crate::fixtures::{sample_answer_response, sample_reasoning_response};
+
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut config = create_test_config();
+    config.models.get_mut("test-model").unwrap().api_url = mock_server.uri();
+    config.server.redact_tool_arguments = true;
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(ReasoningService::new(Client::new()));
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let arguments = "{\"query\": \"SELECT * FROM users WHERE email = 'a@b.c'\"}";
+    let req = test::TestRequest::post()
+        .uri("/v1/chat/completions")
+        .set_json(json!({
+            "model": "test-model",
+            "messages": [
+                {"role": "user", "content": "Find my account"},
+                {"role": "assistant", "tool_calls": [{
+                    "id": "call_1",
+                    "type": "function",
+                    "function": {"name": "run_sql", "arguments": arguments}
+                }]},
+                {"role": "tool", "tool_call_id": "call_1", "content": "[]"}
+            ]
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    for upstream_request in mock_server.received_requests().await.unwrap() {
+        let body: serde_json::Value = upstream_request.body_json().unwrap();
+        assert_eq!(
+            body["messages"][1]["tool_calls"][0]["function"]["arguments"],
+            arguments
+        );
+    }
+}
+
+async fn unreachable_upstream_url() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    drop(listener);
+    format!("http://{address}")
+}
+
+#[actix_web::test]
+async fn test_http_health_ok_with_upstreams_down() {
+    let mut config = create_test_config();
+    config.models.get_mut("test-model").unwrap().api_url = unreachable_upstream_url().await;
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(ReasoningService::new(Client::new()));
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let req = test::TestRequest::get().uri("/health").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body, json!({"status": "ok", "models": 1}));
+
+    let req = test::TestRequest::get().uri("/ready").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["reachable"], 0);
+}
+
+#[actix_web::test]
+async fn test_http_ready_with_reachable_upstream() {
+    let mock_server = MockServer::start().await;
+
+    let mut config = create_test_config();
+    config.models.get_mut("test-model").unwrap().api_url = mock_server.uri();
+    let mut down_model = config.models["test-model"].clone();
+    down_model.api_url = unreachable_upstream_url().await;
+    config.models.insert("down-model".to_string(), down_model);
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(ReasoningService::new(Client::new()));
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let req = test::TestRequest::get().uri("/ready").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(
+        body,
+        json!({"status": "ready", "upstreams": 2, "reachable": 1})
+    );
+}
+
+#[rstest]
+#[case::server_error(503, None)]
+#[case::host_not_allowed(200, Some(vec!["upstream.example.com".to_string()]))]
+#[actix_web::test]
+async fn test_http_ready_rejects_unusable_upstream(
+    #[case] probe_status: u16,
+    #[case] allowed_upstream_hosts: Option<Vec<String>>,
+) {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("HEAD"))
+        .respond_with(ResponseTemplate::new(probe_status))
+        .mount(&mock_server)
+        .await;
+
+    let mut config = create_test_config();
+    config.models.get_mut("test-model").unwrap().api_url = mock_server.uri();
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(
+        ReasoningService::builder(Client::new())
+            .allowed_upstream_hosts(allowed_upstream_hosts)
+            .build(),
+    );
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let req = test::TestRequest::get().uri("/ready").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(
+        body,
+        json!({"status": "unavailable", "upstreams": 1, "reachable": 0})
+    );
+}
+
+#[actix_web::test]
+async fn test_http_models_hides_unhealthy_model_until_recovery() {
+    let server_error = ResponseTemplate::new(500)
+        .set_body_json(json!({"error": {"message": "Internal server error", "type": "internal_error"}}));
+    let mock_server = crate::common::mock_server::setup_sequential_mocks(vec![
+        server_error.clone(),
+        server_error.clone(),
+        server_error,
+        ResponseTemplate::new(200).set_body_json(crate::fixtures::sample_reasoning_response()),
+        ResponseTemplate::new(200).set_body_json(crate::fixtures::sample_answer_response()),
+    ]).await;
+
+    let mut config = create_test_config();
+    config.server.hide_unhealthy_models = true;
+    let model_config = config.models.get_mut("test-model").unwrap();
+    model_config.api_url = mock_server.uri();
+    model_config.max_retries = Some(0);
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(ReasoningService::new(Client::new()));
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let request_body =
+        json!({"model": "test-model", "messages": [{"role": "user", "content": "Hello"}]});
+    let mut listed_models = vec![];
+    for expected_status in [
+        StatusCode::INTERNAL_SERVER_ERROR,
+        StatusCode::INTERNAL_SERVER_ERROR,
+        StatusCode::INTERNAL_SERVER_ERROR,
+        StatusCode::OK,
+    ] {
+        let req = test::TestRequest::post()
+            .uri("/v1/chat/completions")
+            .set_json(&request_body)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), expected_status);
+
+        let req = test::TestRequest::get().uri("/v1/models").to_request();
+        let body: model_list::ModelList = test::call_and_read_body_json(&app, req).await;
+        listed_models.push(body.data.len());
+    }
+
+    assert_eq!(listed_models, vec![1, 1, 0, 1]);
+}
+
+static CAPTURED_LOGS: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+struct CaptureLogger;
+
+impl log::Log for CaptureLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        CAPTURED_LOGS.lock().unwrap().push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+fn install_capture_logger() {
+    static INSTALL: std::sync::Once = std::sync::Once::new();
+    INSTALL.call_once(|| {
+        let filter = env_logger::Builder::new()
+            .filter_level(log::LevelFilter::Info)
+            .build();
+        log::set_boxed_logger(Box::new(
+            adaptive_reasoner::log_context::RequestScopedLogger::new(filter, CaptureLogger),
+        ))
+        .unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+    });
+}
+
+#[rstest]
+#[case(true, Some("debug"), "elevated-log-marker", StatusCode::OK, true)]
+#[case(true, None, "plain-log-marker", StatusCode::OK, false)]
+#[case(false, Some("debug"), "disallowed-log-marker", StatusCode::OK, false)]
+#[case(true, Some("info"), "rejected-log-marker", StatusCode::BAD_REQUEST, false)]
+#[actix_web::test]
+async fn test_http_chat_completion_request_log_level(
+    #[case] allow_request_log_level: bool,
+    #[case] header: Option<&str>,
+    #[case] marker: &str,
+    #[case] expected_status: StatusCode,
+    #[case] expected_logged: bool,
+) {
+    install_capture_logger();
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(crate::fixtures::sample_reasoning_response()).unwrap(),
+        serde_json::to_value(crate::fixtures::sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut config = create_test_config();
+    config.server.allow_request_log_level = allow_request_log_level;
+    config.models.get_mut("test-model").unwrap().api_url = mock_server.uri();
+
+    let config = Arc::new(config);
+    let reasoning_service = Arc::new(ReasoningService::new(Client::new()));
+    let app = test::init_service(create_app(reasoning_service.clone(), config.clone())).await;
+
+    let request_body =
+        json!({"model": "test-model", "messages": [{"role": "user", "content": marker}]});
+    let mut req = test::TestRequest::post()
+        .uri("/v1/chat/completions")
+        .set_json(&request_body);
+    if let Some(header) = header {
+        req = req.insert_header(("X-AR-Log-Level", header));
+    }
+    let resp = test::call_service(&app, req.to_request()).await;
+    assert_eq!(resp.status(), expected_status);
+    if expected_status == StatusCode::BAD_REQUEST {
+        assert_invalid_request_body(resp, "request log level not allowed: info").await;
+    }
+
+    let logged = CAPTURED_LOGS
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|line| line.contains(marker));
+    assert_eq!(logged, expected_logged);
+}
@@ -1,20 +1,29 @@
-use adaptive_reasoner::config::ModelConfig;
+use adaptive_reasoner::config::{
+    AuthScheme, ConfidenceGate, ModelConfig, ReasoningBudgetExceededBehavior,
+    ReasoningToolCallBehavior,
+};
 use adaptive_reasoner::consts;
+use adaptive_reasoner::log_context;
+use adaptive_reasoner::models::FinishReason;
+use adaptive_reasoner::models::PromptTokensDetails;
 use adaptive_reasoner::models::request;
 use adaptive_reasoner::service::ReasoningService;
 use reqwest::Client;
 use serde_json::json;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use wiremock::{
     Mock, MockServer, ResponseTemplate,
     matchers::{method, path},
 };
 
+use crate::common::trickle::{TrickleResponse, TrickleServer};
 use crate::fixtures::{
     sample_answer_chunks, sample_answer_response, sample_chat_request, sample_reasoning_chunks,
     sample_reasoning_response,
 };
 
+#[allow(dead_code)]
 mod common;
 mod fixtures;
 
@@ -58,15 +67,17 @@ async fn test_integration_http_error_codes(
         "Expected error from {} response",
         status_code
     );
-    match result.unwrap_err() {
-        adaptive_reasoner::errors::ReasonerError::ApiError(msg) => {
-            assert!(
-                msg.contains(&format!("status {}", status_code)),
-                "Expected {} status in error",
-                status_code
-            );
-        }
-        _ => panic!("Expected ApiError variant"),
+    let error = result.unwrap_err();
+    assert!(
+        error.message().contains(&format!("status {}", status_code)),
+        "Expected {} status in error",
+        status_code
+    );
+    match (status_code, &error) {
+        (429, adaptive_reasoner::errors::ReasonerError::RateLimitError { .. })
+        | (400..=428 | 430..=499, adaptive_reasoner::errors::ReasonerError::ClientError { .. })
+        | (500..=599, adaptive_reasoner::errors::ReasonerError::ApiError { .. }) => {}
+        _ => panic!("Unexpected error variant for {}: {:?}", status_code, error),
     }
 }
 
@@ -76,7 +87,7 @@ fn create_model_config(base_url: String) -> ModelConfig {
         api_url: base_url,
         api_key: "test-key".to_string(),
         reasoning_budget: 100,
-        extra: None,
+        ..Default::default()
     }
 }
 
@@ -89,15 +100,17 @@ async fn test_integration_complete_reasoning_and_answer_flow() {
     use crate::fixtures::{sample_answer_response, sample_reasoning_response};
 
     let mock_server = crate::common::mock_server::setup_two_phase_mocks(
-        serde_json::to_value(&sample_reasoning_response()).unwrap(),
-        serde_json::to_value(&sample_answer_response()).unwrap(),
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
     )
     .await;
 
     let model_config = create_model_config(mock_server.uri());
 
     let http_client = Client::new();
-    let service = ReasoningService::new(http_client);
+    let service = ReasoningService::builder(http_client)
+        .fresh_completion_ids(false)
+        .build();
     let request = sample_chat_request();
 
     let result = service.create_completion(request, &model_config).await;
@@ -221,7 +234,7 @@ async fn test_integration_api_failure_at_reasoning_phase() {
 
     assert!(result.is_err(), "Expected error from reasoning phase");
     match result.unwrap_err() {
-        adaptive_reasoner::errors::ReasonerError::ApiError(msg) => {
+        adaptive_reasoner::errors::ReasonerError::ApiError { message: msg, .. } => {
             assert!(msg.contains("status 500"), "Expected 500 status in error");
         }
         _ => panic!("Expected ApiError variant"),
@@ -235,7 +248,7 @@ async fn test_integration_api_failure_at_answer_phase() {
 
     Mock::given(method("POST"))
         .and(path("/chat/completions"))
-        .respond_with(ResponseTemplate::new(200).set_body_json(&sample_reasoning_response()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(sample_reasoning_response()))
         .up_to_n_times(1)
         .mount(&mock_server)
         .await;
@@ -259,7 +272,7 @@ async fn test_integration_api_failure_at_answer_phase() {
 
     assert!(result.is_err(), "Expected error from answer phase");
     match result.unwrap_err() {
-        adaptive_reasoner::errors::ReasonerError::ApiError(msg) => {
+        adaptive_reasoner::errors::ReasonerError::ApiError { message: msg, .. } => {
             assert!(msg.contains("status 500"), "Expected 500 status in error");
         }
         _ => panic!("Expected ApiError variant"),
@@ -282,12 +295,42 @@ async fn test_integration_malformed_response() {
 
     assert!(result.is_err(), "Expected error from malformed response");
     match result.unwrap_err() {
-        adaptive_reasoner::errors::ReasonerError::ParseError(_)
-        | adaptive_reasoner::errors::ReasonerError::ApiError(_) => {}
+        adaptive_reasoner::errors::ReasonerError::ParseError { .. }
+        | adaptive_reasoner::errors::ReasonerError::ApiError { .. } => {}
         _ => panic!("Expected ParseError or ApiError variant"),
     }
 }
 
+#[rstest]
+#[case(502)]
+#[case(200)]
+#[tokio::test]
+async fn test_integration_html_error_page(#[case] status_code: u16) {
+    let html =
+        "<html>\n  <head><title>502 Bad Gateway</title></head>\n  <body>nginx</body>\n</html>";
+    let mock_server = crate::common::mock_server::setup_sequential_mocks(vec![
+        ResponseTemplate::new(status_code).set_body_raw(html, "text/html; charset=utf-8"),
+    ])
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.max_retries = Some(0);
+    let service = ReasoningService::new(Client::new());
+
+    let result = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await;
+
+    match result {
+        Err(adaptive_reasoner::errors::ReasonerError::ApiError { message: msg, .. }) => {
+            assert!(msg.contains(&format!("status {status_code}")));
+            assert!(msg.contains("upstream returned an HTML error page"));
+            assert!(msg.contains("<title>502 Bad Gateway</title>"));
+        }
+        other => panic!("Expected ApiError, got {:?}", other),
+    }
+}
+
 #[tokio::test]
 async fn test_integration_reasoning_budget_exceeded() {
     let mut reasoning_response = sample_reasoning_response();
@@ -331,6 +374,318 @@ async fn test_integration_reasoning_budget_exceeded() {
     }
 }
 
+#[tokio::test]
+async fn test_integration_merged_response_gets_fresh_identity() {
+    let mut answer_response = sample_answer_response();
+    answer_response.id = "chatcmpl-test-2".to_string();
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(&answer_response).unwrap(),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let first = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+    let second = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    assert!(first.id.starts_with("chatcmpl-"));
+    assert_ne!(first.id, "chatcmpl-test-1");
+    assert_ne!(first.id, "chatcmpl-test-2");
+    assert_ne!(first.id, second.id);
+    assert_ne!(first.created, sample_reasoning_response().created);
+}
+
+#[tokio::test]
+async fn test_integration_usage_breaks_out_reasoning_tokens() {
+    let mut answer_response = sample_answer_response();
+    answer_response.usage.prompt_tokens = 70;
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(&answer_response).unwrap(),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let usage = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap()
+        .usage;
+
+    let reasoning_usage = sample_reasoning_response().usage;
+    assert_eq!(usage.prompt_tokens, reasoning_usage.prompt_tokens);
+    assert_eq!(
+        usage.completion_tokens_details.unwrap().reasoning_tokens,
+        reasoning_usage.completion_tokens
+    );
+    assert_eq!(
+        usage.completion_tokens,
+        reasoning_usage.completion_tokens + answer_response.usage.completion_tokens
+    );
+    assert_eq!(
+        usage.total_tokens,
+        usage.prompt_tokens + usage.completion_tokens
+    );
+}
+
+#[tokio::test]
+async fn test_integration_streaming_usage_breaks_out_reasoning_tokens() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            crate::common::sse::build_sse_stream(&sample_reasoning_chunks()),
+            "text/event-stream",
+        ))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            crate::common::sse::build_sse_stream(&sample_answer_chunks()),
+            "text/event-stream",
+        ))
+        .mount(&mock_server)
+        .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+    request.stream_options = Some(request::StreamOptions {
+        include_usage: Some(true),
+    });
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    let usage = &chunks.last().unwrap()["usage"];
+
+    assert_eq!(usage["completion_tokens_details"]["reasoning_tokens"], 10);
+    assert_eq!(usage["completion_tokens"], 18);
+    assert_eq!(usage["prompt_tokens"], 10);
+    assert_eq!(usage["total_tokens"], 28);
+}
+
+#[tokio::test]
+async fn test_integration_answer_refusal_is_surfaced() {
+    let mut answer_response = sample_answer_response();
+    answer_response.choices[0].message.content = None;
+    answer_response.choices[0].message.refusal = Some("I can't help with that.".to_string());
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(&answer_response).unwrap(),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        completion.choices[0].message.refusal.as_deref(),
+        Some("I can't help with that.")
+    );
+    let body = serde_json::to_value(&completion).unwrap();
+    assert_eq!(
+        body["choices"][0]["message"]["refusal"],
+        "I can't help with that."
+    );
+}
+
+#[tokio::test]
+async fn test_integration_reasoning_refusal_short_circuits() {
+    let mut reasoning_response = sample_reasoning_response();
+    reasoning_response.choices[0].message.content = None;
+    reasoning_response.choices[0].message.refusal = Some("I can't help with that.".to_string());
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(&reasoning_response).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.short_circuit_reasoning_refusal = true;
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    let message = &completion.choices[0].message;
+    assert_eq!(message.refusal.as_deref(), Some("I can't help with that."));
+    assert!(message.content.is_none());
+    assert_eq!(completion.choices[0].finish_reason, FinishReason::Stop);
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_integration_streaming_answer_refusal_is_surfaced() {
+    let mut answer_chunks = sample_answer_chunks();
+    answer_chunks[0].choices[0].delta.content = None;
+    answer_chunks[0].choices[0].delta.refusal = Some("I can't help with that.".to_string());
+    answer_chunks.truncate(1);
+    let mock_server = crate::common::mock_server::setup_streaming_mocks(
+        crate::common::sse::build_sse_stream(&sample_reasoning_chunks()),
+        crate::common::sse::build_sse_stream(&answer_chunks),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.synthesize_answer_from_reasoning = true;
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    let refusal: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["refusal"].as_str())
+        .collect();
+    assert_eq!(refusal, "I can't help with that.");
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert!(content.ends_with("</think>"), "{content:?}");
+}
+
+#[tokio::test]
+async fn test_integration_streaming_reasoning_refusal_short_circuits() {
+    let mut reasoning_chunks = sample_reasoning_chunks();
+    for chunk in reasoning_chunks.iter_mut() {
+        chunk.choices[0].delta.content = None;
+    }
+    reasoning_chunks[0].choices[0].delta.refusal = Some("I can't help with that.".to_string());
+    let mock_server = crate::common::mock_server::setup_streaming_mocks(
+        crate::common::sse::build_sse_stream(&reasoning_chunks),
+        crate::common::sse::build_sse_stream(&sample_answer_chunks()),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.short_circuit_reasoning_refusal = true;
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    let last_choice = &chunks.last().unwrap()["choices"][0];
+    assert_eq!(last_choice["delta"]["refusal"], "I can't help with that.");
+    assert_eq!(last_choice["finish_reason"], "stop");
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "<think></think>");
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_integration_custom_reasoning_cutoff_stub() {
+    let mut reasoning_response = sample_reasoning_response();
+    reasoning_response.choices[0].finish_reason = adaptive_reasoner::models::FinishReason::Length;
+
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(&reasoning_response).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.reasoning_cutoff_stub = Some("Genug nachgedacht, jetzt die Antwort.".to_string());
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    let content = completion.choices[0].message.content.as_deref().unwrap();
+    assert!(content.contains("...\n\nGenug nachgedacht, jetzt die Antwort."));
+    assert!(!content.contains("Right, this is taking too long"));
+
+    let answer_body = mock_server.received_requests().await.unwrap()[1]
+        .body_json::<serde_json::Value>()
+        .unwrap();
+    assert!(answer_body.to_string().contains("Genug nachgedacht"));
+}
+
+#[tokio::test]
+async fn test_integration_answer_system_prompt_only_in_answer_phase() {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.answer_system_prompt =
+        Some("Respond concisely based on your reasoning above.".to_string());
+    let service = ReasoningService::new(Client::new());
+
+    service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    let received = mock_server.received_requests().await.unwrap();
+    let reasoning_body: serde_json::Value = received[0].body_json().unwrap();
+    assert!(
+        !reasoning_body["messages"]
+            .to_string()
+            .contains("Respond concisely")
+    );
+
+    let answer_body: serde_json::Value = received[1].body_json().unwrap();
+    assert_eq!(answer_body["messages"][0]["role"], "system");
+    assert_eq!(
+        answer_body["messages"][0]["content"],
+        "Respond concisely based on your reasoning above."
+    );
+}
+
 #[tokio::test]
 async fn test_integration_tool_calls_propagation() {
     let mock_server = MockServer::start().await;
@@ -386,7 +741,7 @@ async fn test_integration_empty_reasoning_content() {
 
     let mock_server = crate::common::mock_server::setup_two_phase_mocks(
         serde_json::to_value(&reasoning_response).unwrap(),
-        serde_json::to_value(&sample_answer_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
     )
     .await;
 
@@ -412,6 +767,80 @@ async fn test_integration_empty_reasoning_content() {
     );
 }
 
+#[rstest]
+#[case(false)]
+#[case(true)]
+#[tokio::test]
+async fn test_integration_emit_reasoning_content(#[case] emit_reasoning_content: bool) {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.emit_reasoning_content = emit_reasoning_content;
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    let message = &completion.choices[0].message;
+    if emit_reasoning_content {
+        assert_eq!(
+            message.reasoning_content.as_deref(),
+            Some("Let me think about this carefully...")
+        );
+        assert_eq!(
+            message.content.as_deref(),
+            Some("I'm doing great, thank you!")
+        );
+    } else {
+        assert!(message.reasoning_content.is_none());
+        assert_eq!(
+            message.content.as_deref(),
+            Some(
+                "<think>\nLet me think about this carefully...\n</think>\nI'm doing great, thank you!"
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_integration_reasoning_turn_messages() {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.reasoning_turn_messages = true;
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    let body = serde_json::to_value(&completion).unwrap();
+    assert_eq!(
+        body["diagnostics"]["messages"],
+        json!([
+            {"role": "reasoning", "content": "Let me think about this carefully..."},
+            {"role": "assistant", "content": "I'm doing great, thank you!"},
+        ])
+    );
+    assert_eq!(
+        completion.choices[0].message.content.as_deref(),
+        Some(
+            "<think>\nLet me think about this carefully...\n</think>\nI'm doing great, thank you!"
+        )
+    );
+}
+
 #[tokio::test]
 async fn test_integration_chunk_ordering_guarantee() {
     let mock_server = MockServer::start().await;
@@ -580,7 +1009,7 @@ async fn test_integration_incomplete_stream_missing_done() {
     }
 
     assert!(
-        received_messages.len() > 0,
+        !received_messages.is_empty(),
         "Expected to receive some chunks before stream ended"
     );
 }
@@ -774,8 +1203,8 @@ async fn test_integration_empty_response_body() {
 
     assert!(result.is_err(), "Expected error from empty response");
     match result.unwrap_err() {
-        adaptive_reasoner::errors::ReasonerError::ParseError(_)
-        | adaptive_reasoner::errors::ReasonerError::ApiError(_) => {}
+        adaptive_reasoner::errors::ReasonerError::ParseError { .. }
+        | adaptive_reasoner::errors::ReasonerError::ApiError { .. } => {}
         _ => panic!("Expected ParseError or ApiError variant"),
     }
 }
@@ -794,8 +1223,8 @@ async fn test_integration_invalid_json_response() {
 
     assert!(result.is_err(), "Expected error from invalid JSON");
     match result.unwrap_err() {
-        adaptive_reasoner::errors::ReasonerError::ParseError(_)
-        | adaptive_reasoner::errors::ReasonerError::ApiError(_) => {}
+        adaptive_reasoner::errors::ReasonerError::ParseError { .. }
+        | adaptive_reasoner::errors::ReasonerError::ApiError { .. } => {}
         _ => panic!("Expected ParseError or ApiError variant"),
     }
 }
@@ -938,7 +1367,7 @@ async fn test_performance_streaming_concurrent_requests() {
 
             let mut count = 0;
             while let Some(result) = receiver.recv().await {
-                if let Ok(_) = result {
+                if result.is_ok() {
                     count += 1;
                     if count > 10 {
                         break;
@@ -1086,3 +1515,3697 @@ async fn test_performance_memory_stress() {
         success_count, num_requests
     );
 }
+
+#[tokio::test]
+async fn test_integration_max_answer_chars_truncates_multi_byte_answer() {
+    let mut answer_response = sample_answer_response();
+    answer_response.choices[0].message.content = Some("Привет, мир!".to_string());
+
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(answer_response).unwrap(),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+    let mut request = sample_chat_request();
+    request.max_answer_chars = Some(6);
+
+    let completion = service
+        .create_completion(request, &model_config)
+        .await
+        .unwrap();
+
+    let choice = &completion.choices[0];
+    assert_eq!(
+        choice.finish_reason,
+        adaptive_reasoner::models::FinishReason::Length
+    );
+    let content = choice.message.content.as_ref().unwrap();
+    assert!(
+        content.ends_with("\nПривет"),
+        "Expected truncated answer, got {:?}",
+        content
+    );
+}
+
+#[tokio::test]
+async fn test_integration_max_answer_chars_model_default_without_truncation() {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.max_answer_chars = Some(1000);
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    let choice = &completion.choices[0];
+    assert_eq!(
+        choice.finish_reason,
+        adaptive_reasoner::models::FinishReason::Stop
+    );
+    assert!(
+        choice
+            .message
+            .content
+            .as_ref()
+            .unwrap()
+            .ends_with("I'm doing great, thank you!")
+    );
+}
+
+#[tokio::test]
+async fn test_integration_max_answer_chars_streaming_truncation() {
+    let mut answer_chunks = sample_answer_chunks();
+    answer_chunks[0].choices[0].delta.content = Some("Héllo".to_string());
+    answer_chunks[1].choices[0].delta.content = Some(" wörld".to_string());
+    answer_chunks[2].choices[0].delta.content = Some("!".to_string());
+
+    let mock_server = crate::common::mock_server::setup_streaming_mocks(
+        crate::common::sse::build_sse_stream(&sample_reasoning_chunks()),
+        crate::common::sse::build_sse_stream(&answer_chunks),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.max_answer_chars = Some(100);
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+    request.max_answer_chars = Some(8);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert!(
+        content.ends_with(&"</think>Héllo wö"),
+        "Expected forwarding to stop at cap, got {:?}",
+        content
+    );
+
+    let finish_reasons: Vec<&serde_json::Value> = chunks
+        .iter()
+        .map(|chunk| &chunk["choices"][0]["finish_reason"])
+        .filter(|finish_reason| !finish_reason.is_null())
+        .collect();
+    assert_eq!(finish_reasons, vec!["length"]);
+}
+
+#[tokio::test]
+async fn test_integration_max_answer_chars_streaming_usage_reflects_truncation() {
+    let mock_server = crate::common::mock_server::setup_streaming_mocks(
+        crate::common::sse::build_sse_stream(&sample_reasoning_chunks()),
+        crate::common::sse::build_sse_stream(&sample_answer_chunks()),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+    request.stream_options = Some(request::StreamOptions {
+        include_usage: Some(true),
+    });
+    request.max_answer_chars = Some(6);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert!(content.ends_with("</think>I'm do"), "got {:?}", content);
+
+    let usage = chunks
+        .iter()
+        .find_map(|chunk| chunk.get("usage").filter(|usage| !usage.is_null()))
+        .expect("usage chunk");
+    assert_eq!(usage["completion_tokens"], 12);
+    assert_eq!(usage["total_tokens"], 22);
+}
+
+#[tokio::test]
+async fn test_integration_streaming_multiple_choices() {
+    let mut answer_chunks = sample_answer_chunks();
+    for chunk in answer_chunks.iter_mut() {
+        let mut second_choice = chunk.choices[0].clone();
+        second_choice.index = 1;
+        second_choice.delta.content = second_choice.delta.content.map(|text| text.to_uppercase());
+        chunk.choices.push(second_choice);
+    }
+
+    let mock_server = crate::common::mock_server::setup_streaming_mocks(
+        crate::common::sse::build_sse_stream(&sample_reasoning_chunks()),
+        crate::common::sse::build_sse_stream(&answer_chunks),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+    request.n = Some(2);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+
+    let content_for = |index: u64| -> String {
+        chunks
+            .iter()
+            .flat_map(|chunk| chunk["choices"].as_array().unwrap().iter())
+            .filter(|choice| choice["index"] == index)
+            .filter_map(|choice| choice["delta"]["content"].as_str())
+            .collect()
+    };
+    assert_eq!(
+        content_for(0),
+        "<think>Let me think</think>I'm doing great!"
+    );
+    assert_eq!(
+        content_for(1),
+        "<think>Let me think</think>I'M DOING GREAT!"
+    );
+    assert_eq!(content_for(2), "");
+
+    let received = mock_server.received_requests().await.unwrap();
+    let reasoning_body: serde_json::Value = received[0].body_json().unwrap();
+    assert!(reasoning_body.get("n").is_none());
+    let answer_body: serde_json::Value = received[1].body_json().unwrap();
+    assert_eq!(answer_body["n"], 2);
+}
+
+#[tokio::test]
+async fn test_integration_streaming_reasoning_progress_comments() {
+    let mock_server = crate::common::mock_server::setup_streaming_mocks(
+        crate::common::sse::build_sse_stream(&sample_reasoning_chunks()),
+        crate::common::sse::build_sse_stream(&sample_answer_chunks()),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::builder(Client::new())
+        .reasoning_progress(true)
+        .build();
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+
+    let comments: Vec<&str> = received
+        .iter()
+        .filter_map(|message| message.strip_prefix(": "))
+        .map(str::trim)
+        .collect();
+    assert_eq!(
+        comments,
+        vec![
+            "reasoning_tokens=1",
+            "reasoning_tokens=2",
+            "reasoning_tokens=3"
+        ]
+    );
+
+    let think_end = received
+        .iter()
+        .position(|message| message.contains("</think>"))
+        .unwrap();
+    let last_comment = received
+        .iter()
+        .rposition(|message| message.starts_with(": "))
+        .unwrap();
+    assert!(last_comment < think_end);
+
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "<think>Let me think</think>I'm doing great!");
+}
+
+#[rstest]
+#[case(true)]
+#[case(false)]
+#[tokio::test]
+async fn test_integration_streaming_throughput_diagnostics(#[case] enabled: bool) {
+    let mock_server = crate::common::mock_server::setup_streaming_mocks(
+        crate::common::sse::build_sse_stream(&sample_reasoning_chunks()),
+        crate::common::sse::build_sse_stream(&sample_answer_chunks()),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::builder(Client::new())
+        .stream_diagnostics(enabled)
+        .build();
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    let diagnostics: Vec<&serde_json::Value> = chunks
+        .iter()
+        .filter(|chunk| chunk["object"] == "chat.completion.diagnostics")
+        .collect();
+
+    if !enabled {
+        assert!(diagnostics.is_empty());
+        return;
+    }
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0]["diagnostics"]["tokens_per_second"].is_number());
+    assert!(
+        diagnostics[0]["diagnostics"]["answer_tokens"]
+            .as_i64()
+            .unwrap()
+            > 0
+    );
+
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "<think>Let me think</think>I'm doing great!");
+}
+
+async fn assert_upstream_auth(
+    auth_scheme: serde_json::Value,
+    matcher: impl wiremock::Match + 'static,
+) {
+    assert_upstream_auth_fields(json!({"auth_scheme": auth_scheme}), matcher).await;
+}
+
+async fn assert_upstream_auth_fields(
+    auth_fields: serde_json::Value,
+    matcher: impl wiremock::Match + 'static,
+) {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(matcher)
+        .respond_with(ResponseTemplate::new(200).set_body_json(sample_reasoning_response()))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let mut model_config = json!({
+        "model_name": "test-model",
+        "api_url": mock_server.uri(),
+        "api_key": "test-key",
+        "reasoning_budget": 100,
+    });
+    for (field, value) in auth_fields.as_object().unwrap() {
+        model_config[field] = value.clone();
+    }
+    let model_config: ModelConfig = serde_json::from_value(model_config).unwrap();
+
+    let service = ReasoningService::new(Client::new());
+    let result = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await;
+
+    assert!(
+        result.is_ok(),
+        "Expected completion, got {:?}",
+        result.err()
+    );
+
+    let sends_authorization = match model_config.resolve_auth_scheme() {
+        AuthScheme::Bearer => true,
+        AuthScheme::Header { name, .. } => name.eq_ignore_ascii_case("authorization"),
+        AuthScheme::Query { .. } => false,
+    };
+    for received in mock_server.received_requests().await.unwrap() {
+        assert_eq!(
+            received.headers.contains_key("authorization"),
+            sends_authorization
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_integration_auth_scheme_bearer() {
+    use wiremock::matchers::header;
+
+    assert_upstream_auth(json!("bearer"), header("authorization", "Bearer test-key")).await;
+}
+
+#[tokio::test]
+async fn test_integration_auth_scheme_header() {
+    use wiremock::matchers::header;
+
+    assert_upstream_auth(
+        json!({"header": {"name": "x-api-key"}}),
+        header("x-api-key", "test-key"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_integration_auth_scheme_header_prefix() {
+    use wiremock::matchers::header;
+
+    assert_upstream_auth(
+        json!({"header": {"name": "x-api-key", "prefix": "Key "}}),
+        header("x-api-key", "Key test-key"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_integration_auth_header_azure_style() {
+    use wiremock::matchers::header;
+
+    assert_upstream_auth_fields(
+        json!({"auth_header": "api-key", "auth_prefix": ""}),
+        header("api-key", "test-key"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_integration_auth_prefix() {
+    use wiremock::matchers::header;
+
+    assert_upstream_auth_fields(
+        json!({"auth_prefix": "Token "}),
+        header("authorization", "Token test-key"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_integration_auth_scheme_query() {
+    use wiremock::matchers::query_param;
+
+    assert_upstream_auth(
+        json!({"query": {"name": "key"}}),
+        query_param("key", "test-key"),
+    )
+    .await;
+}
+
+#[test]
+fn test_integration_auth_scheme_defaults_to_bearer() {
+    let model_config: ModelConfig = serde_json::from_value(json!({
+        "model_name": "test-model",
+        "api_url": "http://localhost",
+        "api_key": "test-key",
+        "reasoning_budget": 100,
+    }))
+    .unwrap();
+
+    assert_eq!(model_config.auth_scheme, AuthScheme::Bearer);
+}
+
+#[test]
+fn test_integration_auth_scheme_header_config_still_parses() {
+    let model_config: ModelConfig = serde_json::from_value(json!({
+        "model_name": "test-model",
+        "api_url": "http://localhost",
+        "api_key": "test-key",
+        "reasoning_budget": 100,
+        "auth_scheme": {"header": {"name": "api-key"}},
+    }))
+    .unwrap();
+
+    assert_eq!(
+        model_config.auth_scheme,
+        AuthScheme::Header {
+            name: "api-key".to_string(),
+            prefix: None,
+        }
+    );
+    assert_eq!(model_config.auth_header, None);
+    assert_eq!(model_config.auth_prefix, None);
+}
+
+#[tokio::test]
+async fn test_integration_azure_upstream_url_and_header() {
+    use wiremock::matchers::{header, query_param};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/openai/deployments/my-deployment/chat/completions"))
+        .and(query_param("api-version", "2024-06-01"))
+        .and(header("api-key", "test-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(sample_reasoning_response()))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let model_config: ModelConfig = serde_json::from_value(json!({
+        "model_name": "test-model",
+        "api_url": format!("{}/", mock_server.uri()),
+        "api_key": "test-key",
+        "reasoning_budget": 100,
+        "upstream": {
+            "azure": {"deployment": "my-deployment", "api_version": "2024-06-01"}
+        },
+    }))
+    .unwrap();
+
+    let service = ReasoningService::new(Client::new());
+    let result = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await;
+
+    assert!(
+        result.is_ok(),
+        "Expected completion, got {:?}",
+        result.err()
+    );
+    for received in mock_server.received_requests().await.unwrap() {
+        assert!(!received.headers.contains_key("authorization"));
+    }
+}
+
+#[tokio::test]
+async fn test_integration_azure_upstream_applies_request_template() {
+    let mut reasoning_response = serde_json::to_value(sample_reasoning_response()).unwrap();
+    reasoning_response["prompt_filter_results"] = json!([{"prompt_index": 0}]);
+    reasoning_response["choices"][0]["content_filter_results"] = json!({});
+
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/openai/deployments/my-deployment/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(reasoning_response))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/openai/deployments/my-deployment/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(sample_answer_response()))
+        .mount(&mock_server)
+        .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.upstream = serde_json::from_value(json!({
+        "azure": {"deployment": "my-deployment", "api_version": "2024-06-01"}
+    }))
+    .unwrap();
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+    assert!(
+        completion.choices[0]
+            .message
+            .content
+            .as_deref()
+            .unwrap()
+            .contains("I'm doing great, thank you!")
+    );
+
+    let received = mock_server.received_requests().await.unwrap();
+    assert_eq!(received.len(), 2);
+    for upstream_request in received {
+        let body: serde_json::Value = upstream_request.body_json().unwrap();
+        assert!(body.get("model").is_none());
+        assert!(body["messages"].is_array());
+    }
+}
+
+#[tokio::test]
+async fn test_integration_logprobs_only_on_answer_phase() {
+    let mut answer_response = serde_json::to_value(sample_answer_response()).unwrap();
+    answer_response["choices"][0]["logprobs"] = json!({
+        "content": [{"token": "I'm", "logprob": -0.25, "bytes": [73, 39, 109], "top_logprobs": []}]
+    });
+
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        answer_response,
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+    let mut request = sample_chat_request();
+    request.logprobs = Some(true);
+    request.top_logprobs = Some(2);
+
+    let completion = service
+        .create_completion(request, &model_config)
+        .await
+        .unwrap();
+
+    let received = mock_server.received_requests().await.unwrap();
+    assert_eq!(received.len(), 2);
+
+    let reasoning_body: serde_json::Value = received[0].body_json().unwrap();
+    assert!(reasoning_body.get("logprobs").is_none());
+    assert!(reasoning_body.get("top_logprobs").is_none());
+
+    let answer_body: serde_json::Value = received[1].body_json().unwrap();
+    assert_eq!(answer_body["logprobs"], true);
+    assert_eq!(answer_body["top_logprobs"], 2);
+
+    let logprobs = completion.choices[0].logprobs.as_ref().unwrap();
+    assert_eq!(logprobs.content.as_ref().unwrap()[0].logprob, -0.25);
+}
+
+#[tokio::test]
+async fn test_integration_default_reasoning_param_filter() {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+    let mut request = sample_chat_request();
+    request.temperature = Some(0.5);
+    request.n = Some(2);
+    request.best_of = Some(3);
+
+    service
+        .create_completion(request, &model_config)
+        .await
+        .unwrap();
+
+    let received = mock_server.received_requests().await.unwrap();
+    assert_eq!(received.len(), 2);
+
+    let reasoning_body: serde_json::Value = received[0].body_json().unwrap();
+    assert_eq!(reasoning_body["temperature"], 0.5);
+    assert!(reasoning_body.get("n").is_none());
+    assert!(reasoning_body.get("best_of").is_none());
+
+    let answer_body: serde_json::Value = received[1].body_json().unwrap();
+    assert_eq!(answer_body["temperature"], 0.5);
+    assert_eq!(answer_body["n"], 2);
+    assert_eq!(answer_body["best_of"], 3);
+}
+
+#[tokio::test]
+async fn test_integration_single_pass_streaming() {
+    let mut chunks = sample_answer_chunks();
+    chunks[0].choices[0].delta.content = Some("Hmm".to_string());
+    chunks[1].choices[0].delta.content = Some("</think>".to_string());
+    chunks[2].choices[0].delta.content = Some("Four.".to_string());
+
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_bytes(crate::common::sse::build_sse_stream(&chunks).into_bytes())
+                .insert_header("content-type", "text/event-stream"),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+    request.single_pass = Some(true);
+    request.stream_options = Some(request::StreamOptions {
+        include_usage: Some(true),
+    });
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "<think>Hmm</think>Four.");
+
+    let last = chunks.last().unwrap();
+    assert_eq!(last["usage"]["completion_tokens"], 8);
+
+    let upstream_body: serde_json::Value = mock_server.received_requests().await.unwrap()[0]
+        .body_json()
+        .unwrap();
+    assert!(upstream_body.get("stop").is_none());
+    assert_eq!(upstream_body["max_tokens"], 100);
+}
+
+#[tokio::test]
+async fn test_integration_single_pass_non_streaming() {
+    let mut response = sample_answer_response();
+    response.choices[0].message.content = Some("Hmm\n</think>\n\nFour.".to_string());
+
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&response))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.single_pass = true;
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        completion.choices[0].message.content.as_deref(),
+        Some("<think>\nHmm\n</think>\nFour.")
+    );
+    assert_eq!(completion.usage.completion_tokens, 30);
+}
+
+#[tokio::test]
+async fn test_integration_zero_budget_passes_through_non_streaming() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(sample_answer_response()))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.reasoning_budget = Some(0);
+
+    let completion = service
+        .create_completion(request, &model_config)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        completion.choices[0].message.content.as_deref(),
+        Some("I'm doing great, thank you!")
+    );
+    assert_eq!(
+        completion.usage.completion_tokens,
+        sample_answer_response().usage.completion_tokens
+    );
+
+    let upstream_body: serde_json::Value = mock_server.received_requests().await.unwrap()[0]
+        .body_json()
+        .unwrap();
+    assert!(upstream_body.get("stop").is_none());
+    let messages = upstream_body["messages"].as_array().unwrap();
+    assert_eq!(messages.last().unwrap()["role"], "user");
+}
+
+#[tokio::test]
+async fn test_integration_zero_budget_passes_through_streaming() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            crate::common::sse::build_sse_stream(&sample_answer_chunks()),
+            "text/event-stream",
+        ))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.reasoning_budget = 0;
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+    request.stream_options = Some(request::StreamOptions {
+        include_usage: Some(true),
+    });
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "I'm doing great!");
+
+    let usage = chunks
+        .iter()
+        .rev()
+        .find(|chunk| !chunk["usage"].is_null())
+        .map(|chunk| chunk["usage"].clone())
+        .unwrap();
+    let upstream_usage = sample_answer_chunks()
+        .into_iter()
+        .rev()
+        .find_map(|chunk| chunk.usage)
+        .unwrap();
+    assert_eq!(usage["completion_tokens"], upstream_usage.completion_tokens);
+    assert_eq!(usage["prompt_tokens"], upstream_usage.prompt_tokens);
+}
+
+async fn setup_flaky_two_phase_mocks() -> MockServer {
+    let server_error = ResponseTemplate::new(500).set_body_json(json!({
+        "error": {"message": "Internal server error", "type": "internal_error"}
+    }));
+
+    crate::common::mock_server::setup_sequential_mocks(vec![
+        server_error.clone(),
+        ResponseTemplate::new(200).set_body_json(sample_reasoning_response()),
+        server_error,
+        ResponseTemplate::new(200).set_body_json(sample_answer_response()),
+    ])
+    .await
+}
+
+#[tokio::test]
+async fn test_integration_shared_retry_budget_covers_both_phases() {
+    let mock_server = setup_flaky_two_phase_mocks().await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.max_total_retries = Some(2);
+    let service = ReasoningService::new(Client::new());
+
+    let result = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await;
+
+    assert!(
+        result.is_ok(),
+        "Expected completion, got {:?}",
+        result.err()
+    );
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 4);
+}
+
+#[tokio::test]
+async fn test_integration_deterministic_ids_for_seeded_requests() {
+    let mut responses = vec![];
+    for upstream_id in ["chatcmpl-upstream-1", "chatcmpl-upstream-2"] {
+        let mut reasoning_response = sample_reasoning_response();
+        reasoning_response.id = upstream_id.to_string();
+        responses.push(ResponseTemplate::new(200).set_body_json(reasoning_response));
+        responses.push(ResponseTemplate::new(200).set_body_json(sample_answer_response()));
+    }
+    let mock_server = crate::common::mock_server::setup_sequential_mocks(responses).await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.deterministic_ids = true;
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.seed = Some(1234);
+
+    let first = service
+        .create_completion(request.clone(), &model_config)
+        .await
+        .unwrap();
+    let second = service
+        .create_completion(request, &model_config)
+        .await
+        .unwrap();
+
+    assert_eq!(first.id, second.id);
+    assert_ne!(first.id, "chatcmpl-upstream-1");
+
+    let reasoning_body: serde_json::Value = mock_server.received_requests().await.unwrap()[0]
+        .body_json()
+        .unwrap();
+    assert_eq!(reasoning_body["seed"], 1234);
+}
+
+#[rstest]
+#[case(FinishReason::Stop, Some(FinishReason::Stop), FinishReason::Stop)]
+#[case(FinishReason::Stop, Some(FinishReason::Length), FinishReason::Length)]
+#[case(
+    FinishReason::Stop,
+    Some(FinishReason::ToolCalls),
+    FinishReason::ToolCalls
+)]
+#[case(FinishReason::Stop, None, FinishReason::Length)]
+#[case(FinishReason::Length, Some(FinishReason::Stop), FinishReason::Stop)]
+#[case(FinishReason::Length, Some(FinishReason::Length), FinishReason::Length)]
+#[case(
+    FinishReason::Length,
+    Some(FinishReason::ToolCalls),
+    FinishReason::ToolCalls
+)]
+#[case(FinishReason::Length, None, FinishReason::Length)]
+#[tokio::test]
+async fn test_integration_finish_reason_precedence(
+    #[case] reasoning_finish_reason: FinishReason,
+    #[case] answer_finish_reason: Option<FinishReason>,
+    #[case] expected: FinishReason,
+) {
+    let mut reasoning_response = sample_reasoning_response();
+    reasoning_response.choices[0].finish_reason = reasoning_finish_reason;
+    let mut answer_response = sample_answer_response();
+    answer_response.choices[0].finish_reason = answer_finish_reason.unwrap_or(FinishReason::Stop);
+
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(reasoning_response).unwrap(),
+        serde_json::to_value(answer_response).unwrap(),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    if answer_finish_reason.is_none() {
+        // The reasoning fixture uses 50 tokens, leaving nothing for the answer
+        request.max_tokens = Some(50);
+    }
+
+    let completion = service
+        .create_completion(request, &model_config)
+        .await
+        .unwrap();
+
+    let expected_requests = if answer_finish_reason.is_some() { 2 } else { 1 };
+    assert_eq!(
+        mock_server.received_requests().await.unwrap().len(),
+        expected_requests
+    );
+    assert_eq!(completion.choices[0].finish_reason, expected);
+}
+
+fn client_reasoning_request() -> request::ChatCompletionCreate {
+    let mut request = sample_chat_request();
+    request.messages = vec![request::Message::User(request::MessageSystemUser {
+        content: request::MessageContent::String(
+            "<think>The user wants a greeting.</think>Say hello".to_string(),
+        ),
+    })];
+    request
+}
+
+#[tokio::test]
+async fn test_integration_client_reasoning_skips_reasoning_phase() {
+    let mock_server = crate::common::mock_server::setup_chat_completion_mock(
+        200,
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.reuse_client_reasoning = true;
+    let service = ReasoningService::builder(Client::new())
+        .fresh_completion_ids(false)
+        .build();
+
+    let completion = service
+        .create_completion(client_reasoning_request(), &model_config)
+        .await
+        .unwrap();
+
+    let received = mock_server.received_requests().await.unwrap();
+    assert_eq!(received.len(), 1);
+    let answer_body: serde_json::Value = received[0].body_json().unwrap();
+    assert!(answer_body.get("stop").is_none());
+    assert_eq!(
+        last_message_content(&answer_body),
+        "<think>The user wants a greeting.</think>"
+    );
+
+    assert_eq!(completion.id, "chatcmpl-test-2");
+    assert_eq!(completion.usage.prompt_tokens, 10);
+    assert_eq!(completion.usage.completion_tokens, 30);
+    let content = completion.choices[0].message.content.as_ref().unwrap();
+    assert!(content.contains("I'm doing great, thank you!"));
+}
+
+#[tokio::test]
+async fn test_integration_client_reasoning_skips_reasoning_phase_streaming() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_bytes(
+                    crate::common::sse::build_sse_stream(&sample_answer_chunks()).into_bytes(),
+                )
+                .insert_header("content-type", "text/event-stream"),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.reuse_client_reasoning = true;
+    let service = ReasoningService::builder(Client::new())
+        .fresh_completion_ids(false)
+        .build();
+
+    let mut request = client_reasoning_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(
+        content,
+        "<think>The user wants a greeting.</think>I'm doing great!"
+    );
+    assert!(chunks.iter().all(|chunk| chunk["id"] == "chatcmpl-test-2"));
+}
+
+async fn setup_text_json_mock() -> MockServer {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            serde_json::to_vec(&sample_reasoning_response()).unwrap(),
+            "text/json",
+        ))
+        .mount(&mock_server)
+        .await;
+    mock_server
+}
+
+#[tokio::test]
+async fn test_integration_accepted_content_types_allow_list() {
+    let mock_server = setup_text_json_mock().await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.accepted_content_types =
+        vec!["application/json".to_string(), "text/json".to_string()];
+    let service = ReasoningService::new(Client::new());
+
+    let result = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await;
+
+    assert!(
+        result.is_ok(),
+        "Expected completion, got {:?}",
+        result.err()
+    );
+}
+
+#[tokio::test]
+async fn test_integration_disallowed_content_type_fails() {
+    let mock_server = setup_text_json_mock().await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.accepted_content_types = vec!["application/json".to_string()];
+    let service = ReasoningService::new(Client::new());
+
+    let result = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await;
+
+    match result {
+        Err(adaptive_reasoner::errors::ReasonerError::ParseError { message: msg, .. }) => {
+            assert!(msg.contains("text/json"), "Unexpected message: {}", msg);
+        }
+        other => panic!("Expected ParseError, got {:?}", other),
+    }
+}
+
+async fn setup_slow_mock() -> MockServer {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(sample_reasoning_response())
+                .set_delay(std::time::Duration::from_secs(5)),
+        )
+        .mount(&mock_server)
+        .await;
+    mock_server
+}
+
+#[tokio::test]
+async fn test_integration_request_timeout_trips() {
+    let mock_server = setup_slow_mock().await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.timeout_secs = Some(1);
+
+    let started = std::time::Instant::now();
+    let result = service.create_completion(request, &model_config).await;
+
+    assert!(started.elapsed() < std::time::Duration::from_secs(3));
+    match result {
+        Err(adaptive_reasoner::errors::ReasonerError::TimeoutError { .. }) => {}
+        other => panic!("Expected TimeoutError, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_integration_request_timeout_streaming_error_frame() {
+    let mock_server = setup_slow_mock().await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+    request.timeout_secs = Some(1);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    let handle = tokio::spawn(async move {
+        service
+            .stream_completion(request, &model_config, sender)
+            .await
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0]["error"]["type"], "timeout");
+    assert!(matches!(
+        handle.await.unwrap(),
+        Err(adaptive_reasoner::errors::ReasonerError::TimeoutError { .. })
+    ));
+}
+
+fn reasoning_cache_service() -> ReasoningService {
+    ReasoningService::builder(Client::new())
+        .reasoning_cache(16, std::time::Duration::from_secs(60), false)
+        .build()
+}
+
+#[tokio::test]
+async fn test_integration_reasoning_cache_skips_repeated_reasoning() {
+    let mock_server = crate::common::mock_server::setup_sequential_mocks(vec![
+        ResponseTemplate::new(200).set_body_json(sample_reasoning_response()),
+        ResponseTemplate::new(200).set_body_json(sample_answer_response()),
+        ResponseTemplate::new(200).set_body_json(sample_answer_response()),
+    ])
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = reasoning_cache_service();
+
+    let mut request = sample_chat_request();
+    request.seed = Some(42);
+
+    let first = service
+        .create_completion(request.clone(), &model_config)
+        .await
+        .unwrap();
+    let second = service
+        .create_completion(request, &model_config)
+        .await
+        .unwrap();
+
+    let received = mock_server.received_requests().await.unwrap();
+    assert_eq!(received.len(), 3);
+    let cached_answer_body: serde_json::Value = received[2].body_json().unwrap();
+    assert!(cached_answer_body.get("stop").is_none());
+    assert_eq!(
+        last_message_content(&cached_answer_body),
+        "<think>Let me think about this carefully...</think>"
+    );
+
+    assert_eq!(
+        first.choices[0].message.content,
+        second.choices[0].message.content
+    );
+    assert_eq!(
+        second.usage.completion_tokens,
+        first.usage.completion_tokens
+    );
+}
+
+#[tokio::test]
+async fn test_integration_reasoning_cache_ignores_unseeded_requests() {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = reasoning_cache_service();
+
+    for _ in 0..2 {
+        service
+            .create_completion(sample_chat_request(), &model_config)
+            .await
+            .unwrap();
+    }
+
+    let received = mock_server.received_requests().await.unwrap();
+    let reasoning_calls = received
+        .iter()
+        .filter(|upstream_request| {
+            let body: serde_json::Value = upstream_request.body_json().unwrap();
+            body.get("stop").is_some()
+        })
+        .count();
+    assert_eq!(reasoning_calls, 2);
+}
+
+#[tokio::test]
+async fn test_integration_reasoning_cache_streaming() {
+    let sse_response = |sse: String| {
+        ResponseTemplate::new(200)
+            .set_body_bytes(sse.into_bytes())
+            .insert_header("content-type", "text/event-stream")
+    };
+    let mock_server = crate::common::mock_server::setup_sequential_mocks(vec![
+        sse_response(crate::common::sse::build_sse_stream(
+            &sample_reasoning_chunks(),
+        )),
+        sse_response(crate::common::sse::build_sse_stream(&sample_answer_chunks())),
+        sse_response(crate::common::sse::build_sse_stream(&sample_answer_chunks())),
+    ])
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = reasoning_cache_service();
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+    request.seed = Some(42);
+
+    let mut contents = vec![];
+    for _ in 0..2 {
+        let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+        let service = service.clone();
+        let request = request.clone();
+        let model_config = model_config.clone();
+        tokio::spawn(async move {
+            let _ = service
+                .stream_completion(request, &model_config, sender)
+                .await;
+        });
+
+        let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+        let content: String = crate::common::streaming::parse_stream_chunks(&received)
+            .iter()
+            .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+            .collect();
+        contents.push(content);
+    }
+
+    assert_eq!(contents[0], "<think>Let me think</think>I'm doing great!");
+    assert_eq!(contents[1], contents[0]);
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 3);
+}
+
+#[rstest]
+#[case::collapsed(None, false, 2)]
+#[case::elevated_log_level(Some(log::LevelFilter::Debug), false, 10)]
+#[case::differing_reasoning_budgets(None, true, 4)]
+#[tokio::test]
+async fn test_integration_single_flight_collapses_identical_requests(
+    #[case] log_level: Option<log::LevelFilter>,
+    #[case] differing_budgets: bool,
+    #[case] expected_upstream_requests: usize,
+) {
+    use wiremock::matchers::body_partial_json;
+
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(body_partial_json(json!({"stop": ["</think>"]})))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(sample_reasoning_response())
+                .set_delay(Duration::from_millis(200)),
+        )
+        .with_priority(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(sample_answer_response()))
+        .mount(&mock_server)
+        .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::builder(Client::new())
+        .single_flight(true)
+        .build();
+
+    let mut requests = tokio::task::JoinSet::new();
+    for i in 0..5 {
+        let service = service.clone();
+        let model_config = model_config.clone();
+        let mut request = sample_chat_request();
+        if differing_budgets {
+            request.reasoning_budget = Some(50 + (i % 2) * 10);
+        }
+        requests.spawn(log_context::scope(log_level, async move {
+            service.create_completion(request, &model_config).await
+        }));
+    }
+
+    let mut ids = vec![];
+    while let Some(completion) = requests.join_next().await {
+        ids.push(completion.unwrap().unwrap().id);
+    }
+
+    assert_eq!(ids.len(), 5);
+    assert_eq!(
+        ids.iter().all(|id| *id == ids[0]),
+        log_level.is_none() && !differing_budgets
+    );
+    assert_eq!(
+        mock_server.received_requests().await.unwrap().len(),
+        expected_upstream_requests
+    );
+}
+
+fn sample_reasoning_length_response() -> serde_json::Value {
+    let mut reasoning_response = sample_reasoning_response();
+    reasoning_response.choices[0].finish_reason = adaptive_reasoner::models::FinishReason::Length;
+    serde_json::to_value(reasoning_response).unwrap()
+}
+
+fn last_message_content(body: &serde_json::Value) -> String {
+    let messages = body["messages"].as_array().unwrap();
+    messages.last().unwrap()["content"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_integration_budget_exceeded_stub() {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        sample_reasoning_length_response(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.reasoning_budget_exceeded_behavior = ReasoningBudgetExceededBehavior::Stub;
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    let received = mock_server.received_requests().await.unwrap();
+    assert_eq!(received.len(), 2);
+    let answer_body: serde_json::Value = received[1].body_json().unwrap();
+    assert!(last_message_content(&answer_body).contains("Right, this is taking too long"));
+    assert_eq!(
+        completion.choices[0].finish_reason,
+        adaptive_reasoner::models::FinishReason::Stop
+    );
+}
+
+#[tokio::test]
+async fn test_integration_budget_exceeded_retry_with_larger_budget() {
+    let mut retry_response = sample_reasoning_response();
+    retry_response.choices[0].message.content = Some(" Got it.".to_string());
+    retry_response.usage.completion_tokens = 20;
+
+    let mock_server = crate::common::mock_server::setup_sequential_mocks(vec![
+        ResponseTemplate::new(200).set_body_json(sample_reasoning_length_response()),
+        ResponseTemplate::new(200).set_body_json(retry_response),
+        ResponseTemplate::new(200).set_body_json(sample_answer_response()),
+    ])
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.reasoning_budget_exceeded_behavior =
+        ReasoningBudgetExceededBehavior::RetryWithLargerBudget { max_budget: 80 };
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    let received = mock_server.received_requests().await.unwrap();
+    assert_eq!(received.len(), 3);
+
+    let retry_body: serde_json::Value = received[1].body_json().unwrap();
+    assert_eq!(retry_body["max_tokens"], 30);
+    assert_eq!(
+        last_message_content(&retry_body),
+        "<think>Let me think about this carefully..."
+    );
+
+    let answer_body: serde_json::Value = received[2].body_json().unwrap();
+    let answer_prefix = last_message_content(&answer_body);
+    assert!(answer_prefix.contains("carefully... Got it."));
+    assert!(!answer_prefix.contains("Right, this is taking too long"));
+
+    assert_eq!(completion.usage.completion_tokens, 50 + 20 + 30);
+}
+
+#[tokio::test]
+async fn test_integration_budget_exceeded_return_length() {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        sample_reasoning_length_response(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.reasoning_budget_exceeded_behavior = ReasoningBudgetExceededBehavior::ReturnLength;
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 1);
+    assert_eq!(
+        completion.choices[0].finish_reason,
+        adaptive_reasoner::models::FinishReason::Length
+    );
+    let content = completion.choices[0].message.content.as_ref().unwrap();
+    assert!(content.contains("Let me think about this carefully..."));
+    assert!(!content.contains("Right, this is taking too long"));
+    assert_eq!(completion.usage.completion_tokens, 50);
+}
+
+#[tokio::test]
+async fn test_integration_budget_exceeded_streaming_retry() {
+    let mut reasoning_chunks = sample_reasoning_chunks();
+    let last = reasoning_chunks.len() - 1;
+    reasoning_chunks[last].choices[0].finish_reason =
+        Some(adaptive_reasoner::models::FinishReason::Length);
+    let mut retry_chunks = sample_reasoning_chunks();
+    retry_chunks.truncate(1);
+    retry_chunks[0].choices[0].delta.content = Some(" harder".to_string());
+    retry_chunks[0].choices[0].finish_reason = Some(adaptive_reasoner::models::FinishReason::Stop);
+
+    let sse_response = |sse: String| {
+        ResponseTemplate::new(200)
+            .set_body_bytes(sse.into_bytes())
+            .insert_header("content-type", "text/event-stream")
+    };
+    let mock_server = crate::common::mock_server::setup_sequential_mocks(vec![
+        sse_response(crate::common::sse::build_sse_stream(&reasoning_chunks)),
+        sse_response(crate::common::sse::build_sse_stream(&retry_chunks)),
+        sse_response(crate::common::sse::build_sse_stream(&sample_answer_chunks())),
+    ])
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.reasoning_budget_exceeded_behavior =
+        ReasoningBudgetExceededBehavior::RetryWithLargerBudget { max_budget: 200 };
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(
+        content,
+        "<think>Let me think harder</think>I'm doing great!"
+    );
+
+    let received = mock_server.received_requests().await.unwrap();
+    assert_eq!(received.len(), 3);
+    let retry_body: serde_json::Value = received[1].body_json().unwrap();
+    assert_eq!(retry_body["max_tokens"], 190);
+    assert_eq!(last_message_content(&retry_body), "<think>Let me think");
+}
+
+#[tokio::test]
+async fn test_integration_budget_exceeded_streaming_return_length() {
+    let mut reasoning_chunks = sample_reasoning_chunks();
+    let last = reasoning_chunks.len() - 1;
+    reasoning_chunks[last].choices[0].finish_reason =
+        Some(adaptive_reasoner::models::FinishReason::Length);
+
+    let mock_server = crate::common::mock_server::setup_streaming_mocks(
+        crate::common::sse::build_sse_stream(&reasoning_chunks),
+        crate::common::sse::build_sse_stream(&sample_answer_chunks()),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.reasoning_budget_exceeded_behavior = ReasoningBudgetExceededBehavior::ReturnLength;
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "<think>Let me think</think>");
+
+    let finish_reasons: Vec<&serde_json::Value> = chunks
+        .iter()
+        .map(|chunk| &chunk["choices"][0]["finish_reason"])
+        .filter(|finish_reason| !finish_reason.is_null())
+        .collect();
+    assert_eq!(finish_reasons, vec!["length"]);
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_integration_shared_retry_budget_exhausted_across_phases() {
+    let mock_server = setup_flaky_two_phase_mocks().await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.max_total_retries = Some(1);
+    let service = ReasoningService::new(Client::new());
+
+    let result = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await;
+
+    match result {
+        Err(adaptive_reasoner::errors::ReasonerError::ApiError { message: msg, .. }) => {
+            assert!(msg.contains("status 500"));
+        }
+        other => panic!("Expected ApiError, got {:?}", other),
+    }
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_integration_shared_retry_budget_spans_answer_api_key() {
+    let mock_server = setup_flaky_two_phase_mocks().await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.max_total_retries = Some(1);
+    model_config.answer_api_key = Some("answer-key".to_string());
+    let service = ReasoningService::new(Client::new());
+
+    let result = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await;
+
+    assert!(result.is_err(), "Expected an error, got {:?}", result);
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_integration_shared_retry_budget_caps_transient_retries() {
+    let unavailable = ResponseTemplate::new(503).set_body_json(json!({
+        "error": {"message": "Upstream unavailable", "type": "server_error"}
+    }));
+    let mock_server = crate::common::mock_server::setup_sequential_mocks(vec![
+        unavailable.clone(),
+        ResponseTemplate::new(200).set_body_json(sample_reasoning_response()),
+        unavailable,
+    ])
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.max_total_retries = Some(1);
+    model_config.retry_base_delay_ms = Some(10);
+    let service = ReasoningService::new(Client::new());
+
+    let result = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await;
+
+    match result {
+        Err(adaptive_reasoner::errors::ReasonerError::ApiError { message: msg, .. }) => {
+            assert!(msg.contains("status 503"));
+        }
+        other => panic!("Expected ApiError, got {:?}", other),
+    }
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 3);
+}
+
+#[rstest]
+#[case::overloaded("Model is overloaded, try again", 3)]
+#[case::validation("Invalid value for temperature", 1)]
+#[tokio::test]
+async fn test_integration_retryable_error_substrings(
+    #[case] error_message: &str,
+    #[case] expected_requests: usize,
+) {
+    let mock_server = crate::common::mock_server::setup_sequential_mocks(vec![
+        ResponseTemplate::new(400).set_body_json(json!({
+            "error": {"message": error_message, "type": "invalid_request_error"}
+        })),
+        ResponseTemplate::new(200).set_body_json(sample_reasoning_response()),
+        ResponseTemplate::new(200).set_body_json(sample_answer_response()),
+    ])
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.retry_base_delay_ms = Some(10);
+    model_config.retryable_error_substrings = vec!["OVERLOADED".to_string()];
+    let service = ReasoningService::new(Client::new());
+
+    let result = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await;
+
+    if expected_requests > 1 {
+        assert!(
+            result.is_ok(),
+            "Expected completion, got {:?}",
+            result.err()
+        );
+    } else {
+        match result {
+            Err(adaptive_reasoner::errors::ReasonerError::ClientError { message: msg, .. }) => {
+                assert!(msg.contains("status 400"));
+                assert!(msg.contains(error_message));
+            }
+            other => panic!("Expected ClientError, got {:?}", other),
+        }
+    }
+    assert_eq!(
+        mock_server.received_requests().await.unwrap().len(),
+        expected_requests
+    );
+}
+
+#[rstest]
+#[case::slow_reasoning(1500, 0, None, 1, false, 30)]
+#[case::changing_reasoning(0, 300, None, 2, true, 110)]
+#[case::confirming_reasoning(
+    0,
+    300,
+    Some("The answer is: I'm doing  great, thank you!"),
+    1,
+    true,
+    80
+)]
+#[tokio::test]
+async fn test_integration_speculative_answer(
+    #[case] reasoning_delay_ms: u64,
+    #[case] answer_delay_ms: u64,
+    #[case] reasoning_text: Option<&str>,
+    #[case] expected_answer_requests: usize,
+    #[case] reasoning_used: bool,
+    #[case] expected_completion_tokens: i32,
+) {
+    use wiremock::matchers::body_partial_json;
+
+    let mut reasoning_response = sample_reasoning_response();
+    if let Some(text) = reasoning_text {
+        reasoning_response.choices[0].message.content = Some(text.to_string());
+    }
+    let reasoning_text = reasoning_response.choices[0]
+        .message
+        .content
+        .clone()
+        .unwrap();
+
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(body_partial_json(json!({"stop": ["</think>"]})))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(reasoning_response)
+                .set_delay(std::time::Duration::from_millis(reasoning_delay_ms)),
+        )
+        .with_priority(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(sample_answer_response())
+                .set_delay(std::time::Duration::from_millis(answer_delay_ms)),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.speculative_answer = true;
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    let content = completion.choices[0].message.content.clone().unwrap();
+    assert!(content.ends_with("I'm doing great, thank you!"));
+    assert_eq!(content.contains(&reasoning_text), reasoning_used);
+    assert_eq!(
+        completion.usage.completion_tokens,
+        expected_completion_tokens
+    );
+
+    let received = mock_server.received_requests().await.unwrap();
+    let answer_bodies: Vec<serde_json::Value> = received
+        .iter()
+        .map(|upstream_request| upstream_request.body_json().unwrap())
+        .filter(|body: &serde_json::Value| body.get("stop").is_none())
+        .collect();
+    assert_eq!(answer_bodies.len(), expected_answer_requests);
+    let expected_answer_prefill = if expected_answer_requests == 1 {
+        "<think></think>".to_string()
+    } else {
+        format!("<think>{reasoning_text}</think>")
+    };
+    assert_eq!(
+        last_message_content(answer_bodies.last().unwrap()),
+        expected_answer_prefill
+    );
+}
+
+#[tokio::test]
+async fn test_integration_empty_content_non_streaming() {
+    let mut reasoning_response = sample_reasoning_response();
+    reasoning_response.choices[0].message.content = None;
+    let mut answer_response = sample_answer_response();
+    answer_response.choices[0].message.content = Some("".to_string());
+
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(&reasoning_response).unwrap(),
+        serde_json::to_value(&answer_response).unwrap(),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    assert_eq!(completion.choices.len(), 1);
+    assert_eq!(completion.choices[0].message.content.as_deref(), Some(""));
+    assert_eq!(completion.choices[0].finish_reason, FinishReason::Stop);
+}
+
+#[tokio::test]
+async fn test_integration_empty_content_streaming() {
+    let no_chunks: Vec<serde_json::Value> = vec![];
+    let mock_server = crate::common::mock_server::setup_streaming_mocks(
+        crate::common::sse::build_sse_stream(&no_chunks),
+        crate::common::sse::build_sse_stream(&no_chunks),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+    request.stream_options = Some(request::StreamOptions {
+        include_usage: Some(true),
+    });
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    assert_eq!(chunks[0]["choices"][0]["delta"]["role"], "assistant");
+
+    let finish_chunk = chunks
+        .iter()
+        .find(|chunk| !chunk["choices"][0]["finish_reason"].is_null())
+        .expect("Expected a finish_reason chunk");
+    assert_eq!(finish_chunk["choices"][0]["finish_reason"], "stop");
+    assert_eq!(finish_chunk["choices"][0]["delta"]["content"], "");
+
+    let usage_chunk = chunks.last().unwrap();
+    assert!(usage_chunk["usage"].is_object());
+    assert_eq!(usage_chunk["choices"].as_array().unwrap().len(), 0);
+
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "<think></think>");
+}
+
+#[tokio::test]
+async fn test_integration_estimated_cost_sums_both_phases() {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.input_price_per_1k = Some(1.0);
+    model_config.output_price_per_1k = Some(2.0);
+    let service = ReasoningService::new(Client::new());
+
+    service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    // Both phases send the prompt: 10 + 10 input tokens, 50 + 30 output tokens
+    let usage = service.usage();
+    let model_usage = &usage["test-model"];
+    assert_eq!(model_usage.requests, 1);
+    assert_eq!(model_usage.prompt_tokens, 20);
+    assert_eq!(model_usage.completion_tokens, 80);
+    assert_eq!(
+        model_usage.estimated_cost,
+        (20.0 * 1.0 + 80.0 * 2.0) / 1000.0
+    );
+}
+
+#[tokio::test]
+async fn test_integration_streaming_diagnostics_include_estimated_cost() {
+    let mock_server = crate::common::mock_server::setup_streaming_mocks(
+        crate::common::sse::build_sse_stream(&sample_reasoning_chunks()),
+        crate::common::sse::build_sse_stream(&sample_answer_chunks()),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.input_price_per_1k = Some(1.0);
+    model_config.output_price_per_1k = Some(2.0);
+    let service = ReasoningService::builder(Client::new())
+        .stream_diagnostics(true)
+        .build();
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    let stream_service = service.clone();
+    tokio::spawn(async move {
+        let _ = stream_service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    let diagnostics = chunks
+        .iter()
+        .find(|chunk| chunk["object"] == "chat.completion.diagnostics")
+        .unwrap();
+
+    // Reasoning stream reports 10 + 10 tokens, answer stream 10 + 8
+    let expected_cost = (20.0 * 1.0 + 18.0 * 2.0) / 1000.0;
+    assert_eq!(
+        diagnostics["diagnostics"]["estimated_cost"].as_f64(),
+        Some(expected_cost)
+    );
+
+    let usage = service.usage();
+    assert_eq!(usage["test-model"].prompt_tokens, 20);
+    assert_eq!(usage["test-model"].completion_tokens, 18);
+    assert_eq!(usage["test-model"].estimated_cost, expected_cost);
+}
+
+#[tokio::test]
+async fn test_integration_answer_stop_reaches_answer_request() {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.answer_stop = Some(vec!["<think>".to_string()]);
+    let service = ReasoningService::new(Client::new());
+
+    service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    let received = mock_server.received_requests().await.unwrap();
+    let reasoning_body: serde_json::Value = received[0].body_json().unwrap();
+    assert_eq!(reasoning_body["stop"], json!(["</think>"]));
+    let answer_body: serde_json::Value = received[1].body_json().unwrap();
+    assert_eq!(answer_body["stop"], json!(["<think>"]));
+}
+
+#[tokio::test]
+async fn test_integration_echoed_think_start_is_deduplicated() {
+    let mut reasoning_response = sample_reasoning_response();
+    reasoning_response.choices[0].message.content =
+        Some("<think>Let me think about this carefully...".to_string());
+
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(&reasoning_response).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    let received = mock_server.received_requests().await.unwrap();
+    let answer_body: serde_json::Value = received[1].body_json().unwrap();
+    assert_eq!(
+        last_message_content(&answer_body),
+        "<think>Let me think about this carefully...</think>"
+    );
+}
+
+#[rstest]
+#[case(Some("[END]"), Some("data: [END]\n\n"))]
+#[case(None, None)]
+#[tokio::test]
+async fn test_integration_streaming_custom_done_sentinel(
+    #[case] sentinel: Option<&str>,
+    #[case] expected_last_frame: Option<&str>,
+) {
+    let mock_server = crate::common::mock_server::setup_streaming_mocks(
+        crate::common::sse::build_sse_stream(&sample_reasoning_chunks()),
+        crate::common::sse::build_sse_stream(&sample_answer_chunks()),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::builder(Client::new())
+        .sse_done_sentinel(sentinel.map(str::to_string))
+        .build();
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let mut frames = vec![];
+    while let Some(result) = receiver.recv().await {
+        frames.push(String::from_utf8_lossy(&result.unwrap()).to_string());
+    }
+
+    // The upstream streams still end with [DONE], which must not leak through
+    assert!(frames.iter().all(|frame| !frame.contains("[DONE]")));
+    let content: String = crate::common::streaming::parse_stream_chunks(&frames)
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "<think>Let me think</think>I'm doing great!");
+
+    let last_frame = frames.last().unwrap();
+    match expected_last_frame {
+        Some(expected) => assert_eq!(last_frame, expected),
+        None => assert!(last_frame.starts_with("data: {")),
+    }
+}
+
+fn sample_tool_call() -> serde_json::Value {
+    json!({
+        "id": "call_123",
+        "type": "function",
+        "function": {"name": "get_weather", "arguments": "{\"city\": \"Paris\"}"}
+    })
+}
+
+#[tokio::test]
+async fn test_integration_answer_content_and_tool_calls_both_survive() {
+    let mut answer_response = sample_answer_response();
+    answer_response.choices[0].message.content = Some("Let me check the weather.".to_string());
+    answer_response.choices[0].message.tool_calls = Some(vec![sample_tool_call()]);
+    answer_response.choices[0].finish_reason = FinishReason::ToolCalls;
+
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(&answer_response).unwrap(),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    let choice = &completion.choices[0];
+    assert!(matches!(choice.finish_reason, FinishReason::ToolCalls));
+    let content = choice.message.content.as_ref().unwrap();
+    assert!(content.contains("Let me think about this carefully..."));
+    assert!(content.ends_with("Let me check the weather."));
+    assert_eq!(choice.message.tool_calls, Some(vec![sample_tool_call()]));
+}
+
+#[tokio::test]
+async fn test_integration_streaming_answer_content_and_tool_calls_both_survive() {
+    let mut answer_chunks = sample_answer_chunks();
+    let last_chunk = answer_chunks.last_mut().unwrap();
+    last_chunk.choices[0].delta.tool_calls = Some(vec![sample_tool_call()]);
+    last_chunk.choices[0].finish_reason = Some(FinishReason::ToolCalls);
+
+    let mock_server = crate::common::mock_server::setup_streaming_mocks(
+        crate::common::sse::build_sse_stream(&sample_reasoning_chunks()),
+        crate::common::sse::build_sse_stream(&answer_chunks),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "<think>Let me think</think>I'm doing great!");
+
+    let tool_call_chunk = chunks
+        .iter()
+        .find(|chunk| chunk["choices"][0]["delta"]["tool_calls"].is_array())
+        .unwrap();
+    assert_eq!(tool_call_chunk["choices"][0]["delta"]["content"], " great!");
+    assert_eq!(
+        tool_call_chunk["choices"][0]["delta"]["tool_calls"],
+        json!([sample_tool_call()])
+    );
+    assert_eq!(tool_call_chunk["choices"][0]["finish_reason"], "tool_calls");
+}
+
+#[rstest]
+#[case(vec!["127.0.0.1"], true)]
+#[case(vec!["api.openai.com"], false)]
+#[tokio::test]
+async fn test_integration_allowed_upstream_hosts(
+    #[case] allowed_hosts: Vec<&str>,
+    #[case] expect_allowed: bool,
+) {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::builder(Client::new())
+        .allowed_upstream_hosts(Some(
+            allowed_hosts.into_iter().map(str::to_string).collect(),
+        ))
+        .build();
+
+    let result = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await;
+
+    let received = mock_server.received_requests().await.unwrap();
+    if expect_allowed {
+        assert!(result.is_ok());
+        assert_eq!(received.len(), 2);
+    } else {
+        assert!(matches!(
+            result,
+            Err(adaptive_reasoner::errors::ReasonerError::ConfigError { .. })
+        ));
+        assert!(received.is_empty());
+    }
+}
+
+fn answer_with_logprob(content: &str, logprob: f32) -> serde_json::Value {
+    let mut answer_response = serde_json::to_value(sample_answer_response()).unwrap();
+    answer_response["choices"][0]["message"]["content"] = json!(content);
+    answer_response["choices"][0]["logprobs"] = json!({
+        "content": [
+            {"token": "a", "logprob": logprob},
+            {"token": "b", "logprob": logprob}
+        ]
+    });
+    answer_response
+}
+
+#[rstest]
+#[case(-3.0, 3)]
+#[case(-0.1, 2)]
+#[tokio::test]
+async fn test_integration_confidence_gate_retries_low_confidence_answer(
+    #[case] first_answer_logprob: f32,
+    #[case] expected_requests: usize,
+) {
+    let mock_server = crate::common::mock_server::setup_sequential_mocks(vec![
+        ResponseTemplate::new(200).set_body_json(sample_reasoning_response()),
+        ResponseTemplate::new(200)
+            .set_body_json(answer_with_logprob("First answer", first_answer_logprob)),
+        ResponseTemplate::new(200).set_body_json(answer_with_logprob("Second answer", -0.1)),
+    ])
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.confidence_gate = Some(ConfidenceGate {
+        min_avg_logprob: -1.0,
+        max_retries: 1,
+    });
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.logprobs = Some(true);
+
+    let completion = service
+        .create_completion(request, &model_config)
+        .await
+        .unwrap();
+
+    let received = mock_server.received_requests().await.unwrap();
+    assert_eq!(received.len(), expected_requests);
+
+    let content = completion.choices[0].message.content.as_ref().unwrap();
+    let expected_answer = if expected_requests == 3 {
+        "Second answer"
+    } else {
+        "First answer"
+    };
+    assert!(content.ends_with(expected_answer));
+}
+
+#[rstest]
+#[case(None, Some(0.6), Some(0.2))]
+#[case(Some(1.0), Some(1.0), Some(1.0))]
+#[tokio::test]
+async fn test_integration_default_temperatures_per_phase(
+    #[case] client_temperature: Option<f64>,
+    #[case] expected_reasoning: Option<f64>,
+    #[case] expected_answer: Option<f64>,
+) {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.reasoning_default_temperature = Some(0.6);
+    model_config.answer_default_temperature = Some(0.2);
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.temperature = client_temperature;
+    service
+        .create_completion(request, &model_config)
+        .await
+        .unwrap();
+
+    let received = mock_server.received_requests().await.unwrap();
+    let reasoning_body: serde_json::Value = received[0].body_json().unwrap();
+    assert_eq!(reasoning_body["temperature"].as_f64(), expected_reasoning);
+    let answer_body: serde_json::Value = received[1].body_json().unwrap();
+    assert_eq!(answer_body["temperature"].as_f64(), expected_answer);
+}
+
+#[rstest]
+#[case(ReasoningToolCallBehavior::Surface)]
+#[case(ReasoningToolCallBehavior::Answer)]
+#[tokio::test]
+async fn test_integration_reasoning_returns_only_tool_calls(
+    #[case] behavior: ReasoningToolCallBehavior,
+) {
+    let mut reasoning_response = sample_reasoning_response();
+    reasoning_response.choices[0].message.content = None;
+    reasoning_response.choices[0].message.tool_calls = Some(vec![sample_tool_call()]);
+    reasoning_response.choices[0].finish_reason = FinishReason::ToolCalls;
+
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(&reasoning_response).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.reasoning_tool_calls = behavior;
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    let received = mock_server.received_requests().await.unwrap();
+    let choice = &completion.choices[0];
+    match behavior {
+        ReasoningToolCallBehavior::Surface => {
+            assert_eq!(received.len(), 1);
+            assert!(matches!(choice.finish_reason, FinishReason::ToolCalls));
+            assert_eq!(choice.message.content, None);
+            assert_eq!(choice.message.tool_calls, Some(vec![sample_tool_call()]));
+            assert_eq!(completion.usage.prompt_tokens, 10);
+            assert_eq!(completion.usage.completion_tokens, 50);
+        }
+        ReasoningToolCallBehavior::Answer => {
+            assert_eq!(received.len(), 2);
+            assert!(matches!(choice.finish_reason, FinishReason::Stop));
+            assert!(
+                choice
+                    .message
+                    .content
+                    .as_ref()
+                    .unwrap()
+                    .ends_with("I'm doing great, thank you!")
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_integration_streaming_reasoning_returns_only_tool_calls() {
+    let mut reasoning_chunks = sample_reasoning_chunks();
+    for chunk in reasoning_chunks.iter_mut() {
+        chunk.choices[0].delta.content = None;
+    }
+    reasoning_chunks[0].choices[0].delta.tool_calls = Some(vec![sample_tool_call()]);
+    reasoning_chunks.last_mut().unwrap().choices[0].finish_reason = Some(FinishReason::ToolCalls);
+
+    let mock_server = crate::common::mock_server::setup_streaming_mocks(
+        crate::common::sse::build_sse_stream(&reasoning_chunks),
+        crate::common::sse::build_sse_stream(&sample_answer_chunks()),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.reasoning_tool_calls = ReasoningToolCallBehavior::Surface;
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "<think></think>");
+
+    let tool_call_chunk = chunks
+        .iter()
+        .find(|chunk| chunk["choices"][0]["delta"]["tool_calls"].is_array())
+        .unwrap();
+    assert_eq!(
+        tool_call_chunk["choices"][0]["delta"]["tool_calls"],
+        json!([sample_tool_call()])
+    );
+    assert_eq!(
+        chunks.last().unwrap()["choices"][0]["finish_reason"],
+        "tool_calls"
+    );
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_integration_trailing_stop_sequence_is_stripped() {
+    let mut answer_response = sample_answer_response();
+    answer_response.choices[0].message.content = Some("I'm doing great!<END>".to_string());
+
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(&answer_response).unwrap(),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stop = Some(vec!["<END>".to_string()]);
+    let completion = service
+        .create_completion(request, &model_config)
+        .await
+        .unwrap();
+
+    let content = completion.choices[0].message.content.as_ref().unwrap();
+    assert!(content.ends_with("I'm doing great!"));
+    assert!(!content.contains("<END>"));
+}
+
+#[tokio::test]
+async fn test_integration_streaming_trailing_stop_sequence_is_stripped() {
+    let mut answer_chunks = sample_answer_chunks();
+    answer_chunks[1].choices[0].delta.content = Some(" doing <E".to_string());
+    answer_chunks[2].choices[0].delta.content = Some("ND>".to_string());
+
+    let mock_server = crate::common::mock_server::setup_streaming_mocks(
+        crate::common::sse::build_sse_stream(&sample_reasoning_chunks()),
+        crate::common::sse::build_sse_stream(&answer_chunks),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.answer_stop = Some(vec!["<END>".to_string()]);
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "<think>Let me think</think>I'm doing ");
+}
+
+fn trickled_sse_response(chunks: &[String], delay: Duration) -> TrickleResponse {
+    chunks
+        .iter()
+        .fold(TrickleResponse::new(), |response, chunk| {
+            response.segment(delay, chunk.clone())
+        })
+}
+
+fn sse_events<T: serde::Serialize>(chunks: &[T]) -> Vec<String> {
+    crate::common::sse::build_sse_stream(chunks)
+        .split_inclusive("\n\n")
+        .map(str::to_string)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_integration_streaming_idle_upstream_times_out() {
+    let mut reasoning_events = sse_events(&sample_reasoning_chunks());
+    let stalled_events = reasoning_events.split_off(1);
+    let reasoning = trickled_sse_response(&reasoning_events, Duration::ZERO)
+        .segment(Duration::from_millis(1500), stalled_events.concat());
+    let upstream = TrickleServer::start(vec![reasoning]).await;
+
+    let model_config = create_model_config(upstream.uri());
+    let service = ReasoningService::builder(Client::new())
+        .stream_idle_timeout(Some(Duration::from_millis(200)))
+        .build();
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    let stream = tokio::spawn(async move {
+        service
+            .stream_completion(request, &model_config, sender)
+            .await
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "<think>Let");
+
+    match stream.await.unwrap() {
+        Err(adaptive_reasoner::errors::ReasonerError::TimeoutError { message: msg, .. }) => {
+            assert!(msg.contains("idle"));
+        }
+        other => panic!("Expected TimeoutError, got {:?}", other),
+    }
+    assert_eq!(upstream.received_requests(), 1);
+}
+
+#[tokio::test]
+async fn test_integration_streaming_terminated_at_max_lifetime() {
+    let endless_events = vec![sse_events(&sample_reasoning_chunks())[0].clone(); 50];
+    let reasoning = trickled_sse_response(&endless_events, Duration::from_millis(100));
+    let upstream = TrickleServer::start(vec![reasoning]).await;
+
+    let model_config = create_model_config(upstream.uri());
+    let service = ReasoningService::builder(Client::new())
+        .stream_idle_timeout(Some(Duration::from_secs(1)))
+        .max_stream_duration(Some(Duration::from_millis(600)))
+        .build();
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let started = std::time::Instant::now();
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    let stream = tokio::spawn(async move {
+        service
+            .stream_completion(request, &model_config, sender)
+            .await
+    });
+
+    let mut received = vec![];
+    while let Some(Ok(bytes)) = receiver.recv().await {
+        received.push(String::from_utf8_lossy(&bytes).to_string());
+    }
+    assert!(started.elapsed() < Duration::from_secs(3));
+    assert_eq!(received.last().unwrap(), "data: [DONE]\n\n");
+
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    assert!(chunks.len() > 1);
+    assert_eq!(chunks.last().unwrap()["error"]["type"], "timeout");
+
+    match stream.await.unwrap() {
+        Err(adaptive_reasoner::errors::ReasonerError::TimeoutError { message: msg, .. }) => {
+            assert!(msg.contains("maximum lifetime"));
+        }
+        other => panic!("Expected TimeoutError, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_integration_streaming_keep_alive_during_slow_upstream() {
+    let upstream = TrickleServer::start(vec![
+        trickled_sse_response(
+            &sse_events(&sample_reasoning_chunks()),
+            Duration::from_millis(250),
+        ),
+        trickled_sse_response(&sse_events(&sample_answer_chunks()), Duration::ZERO),
+    ])
+    .await;
+
+    let model_config = create_model_config(upstream.uri());
+    let service = ReasoningService::builder(Client::new())
+        .stream_keep_alive(Some(Duration::from_millis(100)))
+        .stream_idle_timeout(Some(Duration::from_secs(2)))
+        .build();
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    let stream = tokio::spawn(async move {
+        service
+            .stream_completion(request, &model_config, sender)
+            .await
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    assert!(stream.await.unwrap().is_ok());
+
+    let keep_alives = received
+        .iter()
+        .filter(|message| message.as_str() == ": keep-alive\n\n")
+        .count();
+    assert!(
+        keep_alives >= 4,
+        "expected keep-alive comments, got {keep_alives}"
+    );
+
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "<think>Let me think</think>I'm doing great!");
+    assert_eq!(upstream.received_requests(), 2);
+}
+
+#[tokio::test]
+async fn test_integration_streaming_keep_alive_before_upstream_responds() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(
+                    crate::common::sse::build_sse_stream(&sample_reasoning_chunks()),
+                    "text/event-stream",
+                )
+                .set_delay(Duration::from_millis(450)),
+        )
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            crate::common::sse::build_sse_stream(&sample_answer_chunks()),
+            "text/event-stream",
+        ))
+        .mount(&mock_server)
+        .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::builder(Client::new())
+        .stream_keep_alive(Some(Duration::from_millis(100)))
+        .build();
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    let stream = tokio::spawn(async move {
+        service
+            .stream_completion(request, &model_config, sender)
+            .await
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    assert!(stream.await.unwrap().is_ok());
+
+    let leading_keep_alives = received
+        .iter()
+        .take_while(|message| message.as_str() == ": keep-alive\n\n")
+        .count();
+    assert!(
+        leading_keep_alives >= 3,
+        "expected keep-alive comments before the first chunk, got {leading_keep_alives}"
+    );
+
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "<think>Let me think</think>I'm doing great!");
+}
+
+fn split_sse_events(events: &[String], delay: Duration) -> TrickleResponse {
+    events
+        .iter()
+        .fold(TrickleResponse::new(), |response, event| {
+            let (head, tail) = event.split_at(event.len() / 2);
+            response
+                .segment(delay, head.to_string())
+                .segment(delay, tail.to_string())
+        })
+}
+
+#[tokio::test]
+async fn test_integration_streaming_reassembles_events_split_across_chunks() {
+    let answer_events = sse_events(&sample_answer_chunks())
+        .iter()
+        .map(|event| event.replace("\n\n", "\r\n\r\n"))
+        .collect::<Vec<_>>();
+    let upstream = TrickleServer::start(vec![
+        split_sse_events(
+            &sse_events(&sample_reasoning_chunks()),
+            Duration::from_millis(20),
+        ),
+        split_sse_events(&answer_events, Duration::from_millis(20)),
+    ])
+    .await;
+
+    let model_config = create_model_config(upstream.uri());
+    let service = ReasoningService::builder(Client::new()).build();
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    let stream = tokio::spawn(async move {
+        service
+            .stream_completion(request, &model_config, sender)
+            .await
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    assert!(stream.await.unwrap().is_ok());
+
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "<think>Let me think</think>I'm doing great!");
+    assert_eq!(upstream.received_requests(), 2);
+}
+
+#[tokio::test]
+async fn test_integration_cached_prompt_tokens_are_summed_across_phases() {
+    let mut reasoning_response = sample_reasoning_response();
+    reasoning_response.usage.prompt_tokens_details = Some(PromptTokensDetails { cached_tokens: 4 });
+    let mut answer_response = sample_answer_response();
+    answer_response.usage.prompt_tokens_details = Some(PromptTokensDetails { cached_tokens: 8 });
+
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(&reasoning_response).unwrap(),
+        serde_json::to_value(&answer_response).unwrap(),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        completion.usage.prompt_tokens_details,
+        Some(PromptTokensDetails { cached_tokens: 12 })
+    );
+}
+
+#[tokio::test]
+async fn test_integration_prompt_tokens_details_absent_without_upstream_details() {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    assert_eq!(completion.usage.prompt_tokens_details, None);
+    let body = serde_json::to_value(&completion).unwrap();
+    assert!(body["usage"].get("prompt_tokens_details").is_none());
+}
+
+#[tokio::test]
+async fn test_integration_streaming_cached_prompt_tokens_are_summed_across_phases() {
+    let mut reasoning_chunks = sample_reasoning_chunks();
+    if let Some(usage) = reasoning_chunks.last_mut().unwrap().usage.as_mut() {
+        usage.prompt_tokens_details = Some(PromptTokensDetails { cached_tokens: 4 });
+    }
+    let mut answer_chunks = sample_answer_chunks();
+    if let Some(usage) = answer_chunks.last_mut().unwrap().usage.as_mut() {
+        usage.prompt_tokens_details = Some(PromptTokensDetails { cached_tokens: 8 });
+    }
+
+    let mock_server = crate::common::mock_server::setup_streaming_mocks(
+        crate::common::sse::build_sse_stream(&reasoning_chunks),
+        crate::common::sse::build_sse_stream(&answer_chunks),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+    request.stream_options = Some(request::StreamOptions {
+        include_usage: Some(true),
+    });
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    let usage = &chunks.last().unwrap()["usage"];
+    assert_eq!(usage["prompt_tokens_details"]["cached_tokens"], 12);
+}
+
+#[tokio::test]
+async fn test_integration_empty_answer_synthesized_from_reasoning() {
+    let mut reasoning_response = sample_reasoning_response();
+    reasoning_response.choices[0].message.content =
+        Some("Two plus two is basic arithmetic.\n\nSo the answer is 4.".to_string());
+    let mut answer_response = sample_answer_response();
+    answer_response.choices[0].message.content = Some("  ".to_string());
+
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(&reasoning_response).unwrap(),
+        serde_json::to_value(&answer_response).unwrap(),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.synthesize_answer_from_reasoning = true;
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    let content = completion.choices[0].message.content.as_ref().unwrap();
+    assert!(content.ends_with("</think>\nSo the answer is 4."));
+}
+
+#[tokio::test]
+async fn test_integration_streaming_empty_answer_synthesized_from_reasoning() {
+    let mut answer_chunks = sample_answer_chunks();
+    for chunk in answer_chunks.iter_mut() {
+        chunk.choices[0].delta.content = Some("".to_string());
+    }
+
+    let mock_server = crate::common::mock_server::setup_streaming_mocks(
+        crate::common::sse::build_sse_stream(&sample_reasoning_chunks()),
+        crate::common::sse::build_sse_stream(&answer_chunks),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.synthesize_answer_from_reasoning = true;
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "<think>Let me think</think>Let me think");
+}
+
+#[tokio::test]
+async fn test_integration_strips_reasoning_from_prior_turns() {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.max_retained_reasoning_turns = Some(1);
+    let service = ReasoningService::new(Client::new());
+
+    let request: request::ChatCompletionCreate = serde_json::from_value(json!({
+        "model": "test-model",
+        "messages": [
+            {"role": "user", "content": "First question"},
+            {"role": "assistant", "content": "<think>\nFirst thoughts\n</think>\nFirst answer"},
+            {"role": "user", "content": "Second question"},
+            {"role": "assistant", "content": "<think>\nSecond thoughts\n</think>\nSecond answer"},
+            {"role": "user", "content": "Third question"},
+        ],
+    }))
+    .unwrap();
+
+    let result = service.create_completion(request, &model_config).await;
+    assert!(
+        result.is_ok(),
+        "Expected completion, got {:?}",
+        result.err()
+    );
+
+    let received = mock_server.received_requests().await.unwrap();
+    assert_eq!(received.len(), 2);
+    for upstream_request in received {
+        let body: serde_json::Value = upstream_request.body_json().unwrap();
+        assert_eq!(body["messages"][1]["content"], "First answer");
+        assert_eq!(
+            body["messages"][3]["content"],
+            "<think>\nSecond thoughts\n</think>\nSecond answer"
+        );
+    }
+}
+
+#[rstest]
+#[case::recovers_after_transient_failures(503, None, 4, true)]
+#[case::gives_up_after_max_retries(503, Some(1), 2, false)]
+#[case::client_errors_are_not_retried(400, None, 1, false)]
+#[tokio::test]
+async fn test_integration_retries_transient_failures_with_backoff(
+    #[case] status: u16,
+    #[case] max_retries: Option<u32>,
+    #[case] expected_requests: usize,
+    #[case] succeeds: bool,
+) {
+    let failure = ResponseTemplate::new(status).set_body_json(json!({
+        "error": {"message": "Upstream unavailable", "type": "server_error"}
+    }));
+    let mock_server = crate::common::mock_server::setup_sequential_mocks(vec![
+        failure.clone(),
+        failure,
+        ResponseTemplate::new(200).set_body_json(sample_reasoning_response()),
+        ResponseTemplate::new(200).set_body_json(sample_answer_response()),
+    ])
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.max_retries = max_retries;
+    model_config.retry_base_delay_ms = Some(10);
+    let service = ReasoningService::new(Client::new());
+
+    let result = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await;
+
+    if succeeds {
+        assert!(
+            result.is_ok(),
+            "Expected completion, got {:?}",
+            result.err()
+        );
+    } else {
+        match result {
+            Err(error) => {
+                assert!(error.message().contains(&format!("status {status}")));
+                assert_eq!(error.is_retryable(), status >= 500);
+            }
+            other => panic!("Expected an error, got {:?}", other),
+        }
+    }
+    assert_eq!(
+        mock_server.received_requests().await.unwrap().len(),
+        expected_requests
+    );
+}
+
+#[tokio::test]
+async fn test_integration_streaming_answer_error_sends_error_frame() {
+    let mock_server = crate::common::mock_server::setup_sequential_mocks(vec![
+        ResponseTemplate::new(200).set_body_raw(
+            crate::common::sse::build_sse_stream(&sample_reasoning_chunks()),
+            "text/event-stream",
+        ),
+        ResponseTemplate::new(400).set_body_json(json!({
+            "error": {"message": "Context length exceeded", "type": "invalid_request_error"}
+        })),
+    ])
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    let handle = tokio::spawn(async move {
+        service
+            .stream_completion(request, &model_config, sender)
+            .await
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "<think>Let me think</think>");
+
+    let error = &chunks.last().unwrap()["error"];
+    assert_eq!(error["type"], "invalid_request_error");
+    assert!(
+        error["message"]
+            .as_str()
+            .unwrap()
+            .contains("Context length exceeded")
+    );
+    assert!(!received.iter().any(|message| message.contains("[DONE]")));
+    assert!(matches!(
+        handle.await.unwrap(),
+        Err(adaptive_reasoner::errors::ReasonerError::ClientError { status: 400, .. })
+    ));
+}
+
+#[rstest]
+#[case::honored("1", None)]
+#[case::clamped("86400", Some(1))]
+#[tokio::test]
+async fn test_integration_rate_limit_honors_retry_after(
+    #[case] retry_after: &str,
+    #[case] max_retry_after_secs: Option<u64>,
+) {
+    let mock_server = crate::common::mock_server::setup_sequential_mocks(vec![
+        ResponseTemplate::new(429)
+            .insert_header("Retry-After", retry_after)
+            .set_body_json(json!({
+                "error": {"message": "Rate limit reached", "type": "rate_limit_error"}
+            })),
+        ResponseTemplate::new(200).set_body_json(sample_reasoning_response()),
+        ResponseTemplate::new(200).set_body_json(sample_answer_response()),
+    ])
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.max_retry_after_secs = max_retry_after_secs;
+    let service = ReasoningService::new(Client::new());
+
+    let started = std::time::Instant::now();
+    let result = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await;
+
+    assert!(
+        result.is_ok(),
+        "Expected completion, got {:?}",
+        result.err()
+    );
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    assert!(started.elapsed() < Duration::from_secs(3));
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_integration_rate_limit_propagates_when_retries_disabled() {
+    let mock_server = crate::common::mock_server::setup_sequential_mocks(vec![
+        ResponseTemplate::new(429)
+            .insert_header("Retry-After", "1")
+            .set_body_json(json!({
+                "error": {"message": "Rate limit reached", "type": "rate_limit_error"}
+            })),
+    ])
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.max_retries = Some(0);
+    let service = ReasoningService::new(Client::new());
+
+    let result = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await;
+
+    match result {
+        Err(adaptive_reasoner::errors::ReasonerError::RateLimitError { message: msg, .. }) => {
+            assert!(msg.contains("status 429"));
+        }
+        other => panic!("Expected RateLimitError, got {:?}", other),
+    }
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 1);
+}
+
+fn sse_stream_without_usage(
+    mut chunks: Vec<adaptive_reasoner::models::response_stream::ChatCompletionChunk>,
+) -> String {
+    chunks.iter_mut().for_each(|chunk| chunk.usage = None);
+    crate::common::sse::build_sse_stream(&chunks)
+}
+
+#[tokio::test]
+async fn test_integration_stream_options_not_forwarded_when_disabled() {
+    let mock_server = crate::common::mock_server::setup_sequential_mocks(vec![
+        ResponseTemplate::new(200).set_body_raw(
+            sse_stream_without_usage(sample_reasoning_chunks()),
+            "text/event-stream",
+        ),
+        ResponseTemplate::new(200).set_body_raw(
+            sse_stream_without_usage(sample_answer_chunks()),
+            "text/event-stream",
+        ),
+    ])
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.forward_stream_options = Some(false);
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+    request.stream_options = Some(request::StreamOptions {
+        include_usage: Some(true),
+    });
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    let handle = tokio::spawn(async move {
+        service
+            .stream_completion(request, &model_config, sender)
+            .await
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    assert!(handle.await.unwrap().is_ok());
+
+    let received_requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(received_requests.len(), 2);
+    for upstream_request in received_requests {
+        let body: serde_json::Value = upstream_request.body_json().unwrap();
+        assert!(body.get("stream_options").is_none());
+    }
+
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    let usage = &chunks.last().unwrap()["usage"];
+    assert_eq!(usage["completion_tokens"], 6);
+    assert_eq!(usage["total_tokens"], 6);
+}
+
+#[tokio::test]
+async fn test_integration_field_renames_apply_to_both_phases() {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.field_renames = [("max_tokens".to_string(), "maxTokens".to_string())].into();
+    let service = ReasoningService::new(Client::new());
+
+    let result = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await;
+    assert!(
+        result.is_ok(),
+        "Expected completion, got {:?}",
+        result.err()
+    );
+
+    let received = mock_server.received_requests().await.unwrap();
+    assert_eq!(received.len(), 2);
+    for upstream_request in received {
+        let body: serde_json::Value = upstream_request.body_json().unwrap();
+        assert!(body.get("max_tokens").is_none());
+        assert!(body["maxTokens"].is_number());
+    }
+}
+
+async fn setup_failing_primary_with_fallback() -> (MockServer, MockServer, ReasoningService) {
+    let primary = crate::common::mock_server::setup_error_mock(
+        500,
+        "Internal server error",
+        "internal_error",
+    )
+    .await;
+    let fallback = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut fallback_config = create_model_config(fallback.uri());
+    fallback_config.model_name = "fallback-model".to_string();
+    let service = ReasoningService::builder(Client::new())
+        .fallback_models([("fallback".to_string(), fallback_config)].into())
+        .build();
+    (primary, fallback, service)
+}
+
+#[tokio::test]
+async fn test_integration_falls_back_when_primary_fails() {
+    let (primary, fallback, service) = setup_failing_primary_with_fallback().await;
+
+    let mut model_config = create_model_config(primary.uri());
+    model_config.fallbacks = Some(vec!["missing".to_string(), "fallback".to_string()]);
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+    assert!(
+        completion.choices[0]
+            .message
+            .content
+            .as_deref()
+            .unwrap()
+            .contains("I'm doing great, thank you!")
+    );
+
+    assert_eq!(primary.received_requests().await.unwrap().len(), 1);
+    let received = fallback.received_requests().await.unwrap();
+    assert_eq!(received.len(), 2);
+    let body: serde_json::Value = received[0].body_json().unwrap();
+    assert_eq!(body["model"], "fallback-model");
+}
+
+#[tokio::test]
+async fn test_integration_does_not_fall_back_on_validation_error() {
+    let (primary, fallback, service) = setup_failing_primary_with_fallback().await;
+
+    let mut model_config = create_model_config(primary.uri());
+    model_config.fallbacks = Some(vec!["fallback".to_string()]);
+
+    let mut request = sample_chat_request();
+    request.messages = vec![];
+    let result = service.create_completion(request, &model_config).await;
+
+    assert!(matches!(
+        result,
+        Err(adaptive_reasoner::errors::ReasonerError::ValidationError { .. })
+    ));
+    assert!(fallback.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_integration_streaming_falls_back_before_any_output() {
+    let primary = crate::common::mock_server::setup_error_mock(
+        500,
+        "Internal server error",
+        "internal_error",
+    )
+    .await;
+    let fallback = crate::common::mock_server::setup_sequential_mocks(vec![
+        ResponseTemplate::new(200).set_body_raw(
+            crate::common::sse::build_sse_stream(&sample_reasoning_chunks()),
+            "text/event-stream",
+        ),
+        ResponseTemplate::new(200).set_body_raw(
+            crate::common::sse::build_sse_stream(&sample_answer_chunks()),
+            "text/event-stream",
+        ),
+    ])
+    .await;
+
+    let service = ReasoningService::builder(Client::new())
+        .fallback_models([("fallback".to_string(), create_model_config(fallback.uri()))].into())
+        .build();
+    let mut model_config = create_model_config(primary.uri());
+    model_config.fallbacks = Some(vec!["fallback".to_string()]);
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    let handle = tokio::spawn(async move {
+        service
+            .stream_completion(request, &model_config, sender)
+            .await
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    assert!(handle.await.unwrap().is_ok());
+
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    assert!(chunks.iter().all(|chunk| chunk.get("error").is_none()));
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "<think>Let me think</think>I'm doing great!");
+    assert_eq!(primary.received_requests().await.unwrap().len(), 1);
+    assert_eq!(fallback.received_requests().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_integration_reasoning_prompt_augment_only_in_reasoning_phase() {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.reasoning_prompt_augment = Some("Let's think step by step.".to_string());
+    let service = ReasoningService::new(Client::new());
+
+    let result = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await;
+    assert!(
+        result.is_ok(),
+        "Expected completion, got {:?}",
+        result.err()
+    );
+
+    let received = mock_server.received_requests().await.unwrap();
+    assert_eq!(received.len(), 2);
+    let user_content = |index: usize| {
+        let body: serde_json::Value = received[index].body_json().unwrap();
+        body["messages"][0]["content"].as_str().unwrap().to_string()
+    };
+    assert!(user_content(0).ends_with("\n\nLet's think step by step."));
+    assert!(!user_content(1).contains("Let's think step by step."));
+}
+
+#[tokio::test]
+async fn test_integration_strips_leaked_think_tags_from_answer() {
+    let mut answer_response = sample_answer_response();
+    answer_response.choices[0].message.content = Some("</think>Hello".to_string());
+
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(&answer_response).unwrap(),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    let content = completion.choices[0].message.content.as_ref().unwrap();
+    let (_, answer) = content.split_once("</think>").unwrap();
+    assert_eq!(answer.trim_start(), "Hello");
+}
+
+#[tokio::test]
+async fn test_integration_streaming_strips_leaked_think_tags_from_answer() {
+    let mut answer_chunks = sample_answer_chunks();
+    answer_chunks[0].choices[0].delta.content = Some("</th".to_string());
+    answer_chunks[1].choices[0].delta.content = Some("ink>Hello".to_string());
+    answer_chunks[2].choices[0].delta.content = Some("".to_string());
+
+    let mock_server = crate::common::mock_server::setup_streaming_mocks(
+        crate::common::sse::build_sse_stream(&sample_reasoning_chunks()),
+        crate::common::sse::build_sse_stream(&answer_chunks),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    let (_, answer) = content.split_once("</think>").unwrap();
+    assert_eq!(answer, "Hello");
+}
+
+#[tokio::test]
+async fn test_integration_answer_phase_uses_answer_api_key() {
+    let mock_server = crate::common::mock_server::setup_two_phase_mocks(
+        serde_json::to_value(sample_reasoning_response()).unwrap(),
+        serde_json::to_value(sample_answer_response()).unwrap(),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.answer_api_key = Some("answer-key".to_string());
+    let service = ReasoningService::new(Client::new());
+
+    service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    let authorizations: Vec<_> = mock_server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| {
+            request.headers["authorization"]
+                .to_str()
+                .unwrap()
+                .to_string()
+        })
+        .collect();
+    assert_eq!(authorizations, vec!["Bearer test-key", "Bearer answer-key"]);
+}
+
+#[tokio::test]
+async fn test_integration_native_reasoning_makes_single_call() {
+    let mut native_response = sample_answer_response();
+    native_response.choices[0].message.reasoning_content = Some("Let me think".to_string());
+
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(native_response))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.native_reasoning = true;
+    model_config.emit_reasoning_content = true;
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    let message = &completion.choices[0].message;
+    assert_eq!(message.reasoning_content.as_deref(), Some("Let me think"));
+    assert_eq!(
+        message.content.as_deref(),
+        Some("I'm doing great, thank you!")
+    );
+
+    let upstream_body: serde_json::Value = mock_server.received_requests().await.unwrap()[0]
+        .body_json()
+        .unwrap();
+    assert!(upstream_body.get("stop").is_none());
+}
+
+#[rstest]
+#[case::xml_tags("xml_tags", "<think>Let me think</think> doing great!")]
+#[case::markdown_fence("markdown_fence", "```thinking\nLet me think\n``` doing great!")]
+#[tokio::test]
+async fn test_integration_streaming_native_reasoning_inlined_in_content(
+    #[case] reasoning_delimiter_style: &str,
+    #[case] expected_content: &str,
+) {
+    let mut native_chunks = sample_answer_chunks();
+    native_chunks[0].choices[0].delta.content = None;
+    native_chunks[0].choices[0].delta.reasoning_content = Some("Let me think".to_string());
+
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            crate::common::sse::build_sse_stream(&native_chunks),
+            "text/event-stream",
+        ))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.native_reasoning = true;
+    model_config.reasoning_delimiter_style =
+        serde_json::from_value(json!(reasoning_delimiter_style)).unwrap();
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, expected_content);
+    assert!(
+        chunks
+            .iter()
+            .all(|chunk| chunk["choices"][0]["delta"]["reasoning_content"].is_null())
+    );
+}
+
+#[rstest]
+#[case::non_streaming(false)]
+#[case::streaming(true)]
+#[tokio::test]
+async fn test_integration_two_phase_reasoning_uses_configured_think_tags(#[case] stream: bool) {
+    let mock_server = if stream {
+        crate::common::mock_server::setup_streaming_mocks(
+            crate::common::sse::build_sse_stream(&sample_reasoning_chunks()),
+            crate::common::sse::build_sse_stream(&sample_answer_chunks()),
+        )
+        .await
+    } else {
+        crate::common::mock_server::setup_two_phase_mocks(
+            serde_json::to_value(sample_reasoning_response()).unwrap(),
+            serde_json::to_value(sample_answer_response()).unwrap(),
+        )
+        .await
+    };
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.think_start = Some("<reasoning>".to_string());
+    model_config.think_end = Some("</reasoning>".to_string());
+    let service = ReasoningService::new(Client::new());
+
+    let content = if stream {
+        let mut request = sample_chat_request();
+        request.stream = Some(true);
+        let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+        tokio::spawn(async move {
+            let _ = service
+                .stream_completion(request, &model_config, sender)
+                .await;
+        });
+
+        let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+        crate::common::streaming::parse_stream_chunks(&received)
+            .iter()
+            .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+            .collect::<String>()
+    } else {
+        let completion = service
+            .create_completion(sample_chat_request(), &model_config)
+            .await
+            .unwrap();
+        completion.choices[0].message.content.clone().unwrap()
+    };
+
+    assert!(content.starts_with("<reasoning>"), "{content:?}");
+    assert!(content.contains("</reasoning>"), "{content:?}");
+    assert!(!content.contains("think>"), "{content:?}");
+}
+
+#[tokio::test]
+async fn test_integration_blank_reasoning_retried_once() {
+    let mut blank_reasoning = sample_reasoning_response();
+    blank_reasoning.choices[0].message.content = Some("\n\n  \n".to_string());
+    blank_reasoning.usage.completion_tokens = 5;
+    blank_reasoning.usage.total_tokens = 15;
+
+    let mock_server = crate::common::mock_server::setup_sequential_mocks(vec![
+        ResponseTemplate::new(200).set_body_json(blank_reasoning),
+        ResponseTemplate::new(200).set_body_json(sample_reasoning_response()),
+        ResponseTemplate::new(200).set_body_json(sample_answer_response()),
+    ])
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.retry_blank_reasoning = true;
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    let content = completion.choices[0].message.content.as_ref().unwrap();
+    assert!(content.contains("Let me think about this carefully..."));
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 3);
+    assert_eq!(completion.usage.prompt_tokens, 20);
+    assert_eq!(completion.usage.completion_tokens, 85);
+    assert_eq!(completion.usage.total_tokens, 105);
+}
+
+#[tokio::test]
+async fn test_integration_empty_answer_retried() {
+    let mut empty_answer = sample_answer_response();
+    empty_answer.choices[0].message.content = Some(String::new());
+    empty_answer.usage.completion_tokens = 2;
+    empty_answer.usage.total_tokens = 12;
+
+    let mock_server = crate::common::mock_server::setup_sequential_mocks(vec![
+        ResponseTemplate::new(200).set_body_json(sample_reasoning_response()),
+        ResponseTemplate::new(200).set_body_json(empty_answer.clone()),
+        ResponseTemplate::new(200).set_body_json(empty_answer),
+        ResponseTemplate::new(200).set_body_json(sample_answer_response()),
+    ])
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.retry_empty_answer = 3;
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    let content = completion.choices[0].message.content.as_ref().unwrap();
+    assert!(content.ends_with("I'm doing great, thank you!"));
+    assert_eq!(completion.usage.completion_tokens, 50 + 2 + 2 + 30);
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 4);
+}
+
+#[tokio::test]
+async fn test_integration_empty_answer_not_retried_when_streaming() {
+    let mut empty_answer_chunks = sample_answer_chunks();
+    for chunk in empty_answer_chunks.iter_mut() {
+        chunk.choices[0].delta.content = Some(String::new());
+    }
+
+    let mock_server = crate::common::mock_server::setup_streaming_mocks(
+        crate::common::sse::build_sse_stream(&sample_reasoning_chunks()),
+        crate::common::sse::build_sse_stream(&empty_answer_chunks),
+    )
+    .await;
+
+    let mut model_config = create_model_config(mock_server.uri());
+    model_config.retry_empty_answer = 3;
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert!(content.ends_with("</think>"), "{content:?}");
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_integration_custom_completions_path() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/openai/v1/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(sample_reasoning_response()))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/openai/v1/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(sample_answer_response()))
+        .mount(&mock_server)
+        .await;
+
+    let mut model_config = create_model_config(format!("{}/", mock_server.uri()));
+    model_config.completions_path = Some("/openai/v1/chat/completions".to_string());
+    let service = ReasoningService::new(Client::new());
+
+    let completion = service
+        .create_completion(sample_chat_request(), &model_config)
+        .await
+        .unwrap();
+
+    let content = completion.choices[0].message.content.as_ref().unwrap();
+    assert!(content.ends_with("I'm doing great, thank you!"));
+    let requests = mock_server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    assert!(
+        requests
+            .iter()
+            .all(|request| request.url.path() == "/openai/v1/chat/completions")
+    );
+}
+
+#[tokio::test]
+async fn test_integration_streaming_chunks_share_id_and_created() {
+    let mut reasoning_chunks = sample_reasoning_chunks();
+    for (offset, chunk) in reasoning_chunks.iter_mut().enumerate() {
+        chunk.id = format!("chatcmpl-reasoning-{offset}");
+        chunk.created += offset as i64;
+    }
+
+    let mock_server = crate::common::mock_server::setup_streaming_mocks(
+        crate::common::sse::build_sse_stream(&reasoning_chunks),
+        crate::common::sse::build_sse_stream(&sample_answer_chunks()),
+    )
+    .await;
+
+    let model_config = create_model_config(mock_server.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+    request.stream_options = Some(request::StreamOptions {
+        include_usage: Some(true),
+    });
+
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _ = service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let received = crate::common::streaming::collect_stream_chunks(&mut receiver).await;
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    assert!(chunks.len() > 1);
+
+    let id = chunks[0]["id"].as_str().unwrap();
+    assert!(id.starts_with("chatcmpl-"));
+    assert!(!id.starts_with("chatcmpl-reasoning-"));
+    assert!(chunks.iter().all(|chunk| chunk["id"] == id));
+    assert!(
+        chunks
+            .iter()
+            .all(|chunk| chunk["created"] == chunks[0]["created"])
+    );
+}
+
+#[tokio::test]
+async fn test_integration_drain_waits_for_in_flight_stream() {
+    let upstream = TrickleServer::start(vec![
+        trickled_sse_response(
+            &sse_events(&sample_reasoning_chunks()),
+            Duration::from_millis(100),
+        ),
+        trickled_sse_response(
+            &sse_events(&sample_answer_chunks()),
+            Duration::from_millis(100),
+        ),
+    ])
+    .await;
+
+    let model_config = create_model_config(upstream.uri());
+    let service = ReasoningService::new(Client::new());
+
+    let mut request = sample_chat_request();
+    request.stream = Some(true);
+
+    let stream_permit = service.try_acquire_stream().unwrap();
+    let stream_service = service.clone();
+    let (sender, mut receiver) = mpsc::channel(consts::CHANNEL_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let _stream_permit = stream_permit;
+        let _ = stream_service
+            .stream_completion(request, &model_config, sender)
+            .await;
+    });
+
+    let (drained, received) = tokio::join!(
+        service.drain_streams(Duration::from_secs(5)),
+        crate::common::streaming::collect_stream_chunks(&mut receiver),
+    );
+    assert!(drained);
+    assert_eq!(service.active_streams(), 0);
+
+    let chunks = crate::common::streaming::parse_stream_chunks(&received);
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+        .collect();
+    assert_eq!(content, "<think>Let me think</think>I'm doing great!");
+}
+
+#[tokio::test]
+async fn test_integration_drain_gives_up_after_grace_period() {
+    let service = ReasoningService::new(Client::new());
+    let _stream_permit = service.try_acquire_stream().unwrap();
+
+    assert!(!service.drain_streams(Duration::from_millis(100)).await);
+}